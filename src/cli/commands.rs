@@ -1,17 +1,41 @@
 use anyhow::{anyhow, Result};
-use clap::Subcommand;
+use clap::{Subcommand, ValueEnum};
+use std::collections::BTreeSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::config::defaults::CellarDefaults;
 use crate::config::game::{
-    DesktopConfig, GameConfig, GameInfo, GamescopeConfig, LaunchConfig, WineConfig,
+    DesktopConfig, GameConfig, GameInfo, GameStats, GamescopeConfig, InstallationInfo,
+    LaunchConfig, WineConfig, CURRENT_CONFIG_VERSION,
 };
+use crate::config::migrate::parse_game_config_with_unknown_keys;
+use crate::config::presets::PRESET_FIELDS;
+use crate::config::settings::{set_config_value, set_defaults_value};
 use crate::config::validation::validate_game_config;
 use crate::desktop;
-use crate::runners::dxvk::DxvkManager;
-use crate::runners::proton::ProtonManager;
-use crate::runners::{RunnerCache, RunnerManager, RunnerType};
-use crate::utils::fs::{sanitize_filename, CellarDirectories};
+use crate::launch::command::DEFAULT_UMU_GAME_ID;
+use crate::launch::lock::running_pid;
+use crate::launch::process::{find_umu_processes, process_is_alive, terminate};
+use crate::runners::common::{download_timeout, download_to_file, is_leftover_download};
+use crate::runners::dxvk::{installed_dxvk_version, is_dxvk_dll, missing_dxvk_files, DxvkManager};
+use crate::runners::proton::{missing_proton_files, ProtonManager};
+use crate::runners::wine::WineManager;
+use crate::runners::{
+    find_game_runner, newest_version, Runner, RunnerCache, RunnerManager, RunnerPins, RunnerType,
+};
+use crate::utils::archive::STAGING_SUFFIX;
+use crate::utils::fs::{
+    check_windows_executable, dir_size, find_gog_root, find_steam_root, format_size,
+    sanitize_filename, write_file_atomic, CellarDirectories,
+};
+use crate::utils::output::{
+    filter_wine_errors, is_verbose, output_with_spinner, print_verbose_stderr,
+};
+use crate::utils::prefix::{detect_prefix_arch, windows_version, PrefixArch};
+use crate::utils::tools::{
+    command_available, require_umu_run, require_wine, EXTERNAL_TOOLS, UMU_LAUNCHER_URL,
+};
 
 #[derive(Subcommand)]
 pub enum Commands {
@@ -22,11 +46,14 @@ pub enum Commands {
         /// Path to existing executable
         #[arg(long)]
         exe: Option<String>,
-        /// Path to installer executable
+        /// Path to installer executable (requires --exe for the installed game)
         #[arg(long)]
         installer: Option<String>,
-        /// Interactive setup
-        #[arg(short, long)]
+        /// Treat --installer as an https URL and download it first
+        #[arg(long, requires = "installer")]
+        from_url: bool,
+        /// Ask for each setting instead of using defaults
+        #[arg(short, long, conflicts_with = "installer")]
         interactive: bool,
         /// Proton version to use for the game
         #[arg(long)]
@@ -34,14 +61,66 @@ pub enum Commands {
         /// Prefix name to use (defaults to game name)
         #[arg(long)]
         prefix: Option<String>,
+        /// Add the executable even if it doesn't look like a Windows program (e.g. a .bat launcher)
+        #[arg(long)]
+        force: bool,
+        /// Don't create a desktop shortcut, now or when shortcuts are synced
+        #[arg(long)]
+        no_shortcut: bool,
+        /// The executable is a native Linux program; run it directly without Proton or a prefix
+        #[arg(long, requires = "exe", conflicts_with_all = ["installer", "interactive", "proton", "prefix"])]
+        native: bool,
+        /// Use the existing prefix named by --prefix and fail if it's missing, instead of creating it
+        #[arg(long, requires = "prefix", conflicts_with_all = ["installer", "interactive"])]
+        no_prefix: bool,
+        /// Install layout of the game; its root directory becomes the working directory
+        #[arg(long = "format", value_enum, value_name = "LAYOUT")]
+        layout: Option<InstallLayout>,
     },
     /// Launch a game
     Launch {
         /// Name of the game to launch
         name: String,
+        /// Print the launch command and environment without starting the game
+        #[arg(long)]
+        dry_run: bool,
+        /// Write a diagnostics report to the logs directory if the launch fails
+        #[arg(long)]
+        capture_crash: bool,
+        /// Enable the Steam overlay when launched from a non-Steam shortcut
+        #[arg(long)]
+        steam_overlay: bool,
+        /// Run in gamescope this time, whatever the config says
+        #[arg(long, overrides_with = "no_gamescope")]
+        gamescope: bool,
+        /// Run without gamescope this time, whatever the config says
+        #[arg(long, overrides_with = "gamescope")]
+        no_gamescope: bool,
+        /// Enable gamemode for this launch only
+        #[arg(long, overrides_with = "no_gamemode")]
+        gamemode: bool,
+        /// Disable gamemode for this launch only
+        #[arg(long, overrides_with = "gamemode")]
+        no_gamemode: bool,
+        /// Show the MangoHud overlay for this launch only
+        #[arg(long, overrides_with = "no_mangohud")]
+        mangohud: bool,
+        /// Hide the MangoHud overlay for this launch only
+        #[arg(long, overrides_with = "mangohud")]
+        no_mangohud: bool,
     },
     /// List all games
-    List,
+    List {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+        format: ListFormat,
+        /// Only list games whose Proton version contains this (e.g. GE-Proton8)
+        #[arg(long)]
+        proton: Option<String>,
+        /// Order of the listed games
+        #[arg(long, value_enum, default_value_t = ListSort::Name)]
+        sort: ListSort,
+    },
     /// Remove a game
     Remove {
         /// Name of the game to remove
@@ -51,6 +130,52 @@ pub enum Commands {
     Info {
         /// Name of the game
         name: String,
+        /// Print the full game config as JSON instead
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show whether a game is running and when it was last launched
+    Status {
+        /// Name of the game
+        name: String,
+    },
+    /// Stop a running game and any wine processes left in its prefix
+    Kill {
+        /// Name of the game
+        name: String,
+    },
+    /// Show the output captured from a game's last launch
+    Logs {
+        /// Name of the game
+        name: String,
+    },
+    /// Check that every game can be launched, or run detailed checks for one game or prefix
+    Doctor {
+        /// Check every game using this prefix
+        #[arg(long, conflicts_with = "game")]
+        prefix: Option<String>,
+        /// Check a single game
+        #[arg(long)]
+        game: Option<String>,
+    },
+    /// Back up game configs, icons and the runner cache to a .tar.gz
+    Backup {
+        /// Output file (e.g. cellar-backup.tar.gz)
+        output: PathBuf,
+    },
+    /// Restore a backup created by `cellar backup`
+    Restore {
+        /// Backup file to restore
+        input: PathBuf,
+        /// Overwrite existing configs and icons
+        #[arg(long)]
+        force: bool,
+    },
+    /// Per-game maintenance commands
+    #[command(alias = "game")]
+    Games {
+        #[command(subcommand)]
+        command: GameCommands,
     },
     /// Runner management commands
     Runners {
@@ -67,12 +192,129 @@ pub enum Commands {
         #[command(subcommand)]
         command: ShortcutCommands,
     },
+    /// View or change the defaults used for newly added games
+    Defaults {
+        #[command(subcommand)]
+        command: DefaultsCommands,
+    },
+    /// Check game config files
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+}
+
+/// Most detailed diagnostics `--log-level` shows
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for tracing_subscriber::filter::LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => Self::ERROR,
+            LogLevel::Warn => Self::WARN,
+            LogLevel::Info => Self::INFO,
+            LogLevel::Debug => Self::DEBUG,
+            LogLevel::Trace => Self::TRACE,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// One readable line per event
+    Text,
+    /// One JSON object per event, for log collectors
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ListSort {
+    /// Alphabetically by config name
+    Name,
+    /// Most recently played first
+    Recent,
+}
+
+/// Install layouts `cellar add --format` finds a game's root directory in
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum InstallLayout {
+    /// GOG install: the directory holding `goggame-<id>.info`
+    Gog,
+    /// Steam library: the game's directory under `steamapps/common`
+    Steam,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ListFormat {
+    /// Human-readable listing
+    Text,
+    /// One row per game, for spreadsheets and scripts
+    Csv,
+}
+
+#[derive(Subcommand)]
+pub enum GameCommands {
+    /// List all games
+    List {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+        format: ListFormat,
+        /// Only list games whose Proton version contains this (e.g. GE-Proton8)
+        #[arg(long)]
+        proton: Option<String>,
+        /// Order of the listed games
+        #[arg(long, value_enum, default_value_t = ListSort::Name)]
+        sort: ListSort,
+    },
+    /// Print the path of a game's config file
+    OpenConfig {
+        /// Name of the game
+        name: String,
+        /// Also show the config directory in the file manager
+        #[arg(long)]
+        open: bool,
+    },
+    /// Change a single config setting, e.g. `wine_config.esync false`
+    Set {
+        /// Name of the game
+        name: String,
+        /// Dotted setting key, e.g. gamescope.width
+        key: String,
+        /// New value; an empty string unsets optional settings
+        value: String,
+    },
+    /// Delete and recreate a game's prefix with its configured Proton and DXVK
+    ResetPrefix {
+        /// Name of the game
+        name: String,
+    },
+    /// Move a game to a new prefix of its own, optionally copying the old prefix into it
+    MovePrefix {
+        /// Name of the game
+        name: String,
+        /// Name of the new prefix (must not exist yet)
+        new_prefix: String,
+        /// Copy the old prefix, with its saves and installed software, instead of creating a fresh one
+        #[arg(long)]
+        copy: bool,
+    },
 }
 
 #[derive(Subcommand)]
 pub enum RunnerCommands {
     /// List installed runners
-    List,
+    List {
+        /// Print the runners with their disk usage as JSON, scanning instead of using the cache
+        #[arg(long)]
+        json: bool,
+    },
     /// Refresh runner cache
     Refresh,
     /// Show available runners for download
@@ -81,7 +323,20 @@ pub enum RunnerCommands {
     Install {
         /// Runner type (proton, dxvk)
         runner_type: String,
-        /// Version to install
+        /// Version to install, or "latest" for the newest release
+        version: String,
+        /// Disable the download size limit for this install
+        #[arg(long)]
+        allow_large: bool,
+        /// Also install the latest DXVK release, unless it's already installed
+        #[arg(long, visible_alias = "all-deps")]
+        with_dxvk: bool,
+    },
+    /// Show a runner's install path, disk usage and the games using it
+    Info {
+        /// Runner type (proton, dxvk)
+        runner_type: String,
+        /// Version to inspect
         version: String,
     },
     /// Install DXVK into a prefix
@@ -91,13 +346,50 @@ pub enum RunnerCommands {
         /// Prefix name to install into
         prefix: String,
     },
+    /// Remove DXVK from a prefix, restoring Wine's own DLLs
+    UninstallDxvk {
+        /// Prefix name to uninstall from
+        prefix: String,
+    },
     /// Remove/uninstall a runner
     Remove {
         /// Runner type (proton, dxvk)
         runner_type: String,
         /// Version to remove
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        version: Option<String>,
+        /// Remove every Cellar-installed version of this runner type
+        #[arg(long)]
+        all: bool,
+    },
+    /// Remove old Proton-GE versions, keeping the newest ones
+    Prune {
+        /// Number of newest versions to keep
+        #[arg(long)]
+        keep: usize,
+    },
+    /// Protect a runner from bulk cleanup
+    Pin {
+        /// Runner type (proton, dxvk)
+        runner_type: String,
+        /// Version to pin
+        version: String,
+    },
+    /// Remove a runner's protection from bulk cleanup
+    Unpin {
+        /// Runner type (proton, dxvk)
+        runner_type: String,
+        /// Version to unpin
         version: String,
     },
+    /// Check installed Proton and DXVK runners for missing files
+    Verify,
+    /// Remove downloads and extraction leftovers from interrupted runner installs
+    ///
+    /// Failed installs keep their partial downloads so the next install can resume
+    /// them; this is how to get rid of them. Anything changed within the download
+    /// timeout is kept, since an install may still be using it.
+    CleanupCache,
 }
 
 #[derive(Subcommand)]
@@ -109,9 +401,17 @@ pub enum PrefixCommands {
         /// Proton version to use
         #[arg(long)]
         proton: Option<String>,
+        /// Delete the prefix first if it already exists
+        #[arg(long)]
+        force: bool,
     },
     /// List all prefixes
     List,
+    /// Show a prefix's Proton and DXVK versions, Windows version, size and the games using it
+    Info {
+        /// Name of the prefix
+        name: String,
+    },
     /// Remove a prefix
     Remove {
         /// Name of the prefix to remove
@@ -126,6 +426,83 @@ pub enum PrefixCommands {
         /// Proton version to use (optional, autodetects if not provided)
         #[arg(long)]
         proton: Option<String>,
+        /// Extra environment variable as KEY=VALUE, overriding Cellar's (repeatable)
+        #[arg(long = "env", value_name = "KEY=VALUE")]
+        env: Vec<String>,
+        /// Arguments to pass to the executable
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Open the Wine registry editor in a prefix
+    Regedit {
+        /// Name of the prefix
+        prefix: String,
+    },
+    /// Open the Wine configuration dialog in a prefix
+    Winecfg {
+        /// Name of the prefix
+        prefix: String,
+    },
+    /// Open $SHELL with WINEPREFIX, WINE and PATH set up for a prefix's Proton build
+    Shell {
+        /// Name of the prefix
+        prefix: String,
+    },
+    /// Show disk usage of a prefix broken down by subdirectory
+    Du {
+        /// Name of the prefix
+        name: String,
+    },
+    /// Copy a prefix to a plain directory outside Cellar
+    CopyTo {
+        /// Name of the prefix
+        name: String,
+        /// Destination directory (must not exist yet)
+        destination: PathBuf,
+    },
+    /// Back up a prefix to a zstd-compressed tarball
+    Export {
+        /// Name of the prefix
+        name: String,
+        /// Output file (e.g. backup.tar.zst)
+        output: PathBuf,
+    },
+    /// Restore a prefix from a tarball created by `prefix export`
+    Import {
+        /// Tarball to import
+        file: PathBuf,
+        /// Name of the new prefix
+        name: String,
+    },
+    /// Run winetricks verbs in a prefix (e.g. vcrun2019, corefonts)
+    Winetricks {
+        /// Name of the prefix
+        prefix: String,
+        /// Winetricks verbs to run
+        #[arg(required = true)]
+        verbs: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Check that configs parse and have valid settings, without launching anything
+    Validate {
+        /// Game to check (all games when omitted)
+        name: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DefaultsCommands {
+    /// Show the defaults file and the values in effect
+    Show,
+    /// Change a default, e.g. launch.gamemode or game.proton_version
+    Set {
+        /// Dotted setting key, e.g. wine_config.fsync
+        key: String,
+        /// New value; an empty string unsets game.proton_version
+        value: String,
     },
 }
 
@@ -142,7 +519,11 @@ pub enum ShortcutCommands {
         name: String,
     },
     /// Sync all desktop shortcuts
-    Sync,
+    Sync {
+        /// Delete extracted icons first so they're re-extracted from the executables
+        #[arg(long)]
+        force_icons: bool,
+    },
     /// List all desktop shortcuts
     List,
     /// Extract icon from game executable
@@ -150,27 +531,94 @@ pub enum ShortcutCommands {
         /// Name of the game
         name: String,
     },
+    /// Use an image file (PNG, JPG or ICO) as a game's icon
+    SetIcon {
+        /// Name of the game
+        name: String,
+        /// Path to the image
+        path: PathBuf,
+    },
     /// List all extracted icons
     ListIcons,
 }
 
+/// Largest installer `--from-url` will download
+const MAX_INSTALLER_SIZE: u64 = 100 * 1024 * 1024 * 1024;
+
+#[allow(clippy::too_many_arguments)]
 pub async fn add_game(
+    dirs: &CellarDirectories,
     name: String,
     exe: Option<String>,
     installer: Option<String>,
+    from_url: bool,
     interactive: bool,
     proton: Option<String>,
     prefix: Option<String>,
+    force: bool,
+    no_shortcut: bool,
+    native: bool,
+    no_prefix: bool,
+    layout: Option<InstallLayout>,
 ) -> Result<()> {
-    let dirs = CellarDirectories::new()?;
     dirs.ensure_all_exist()?;
 
-    if interactive {
-        println!("Interactive mode not yet implemented. Using basic mode.");
+    let mut config = new_game_from_args(
+        dirs,
+        name,
+        exe,
+        installer,
+        from_url,
+        interactive,
+        proton,
+        prefix,
+        force,
+        native,
+        no_prefix,
+    )
+    .await?;
+
+    if let Some(layout) = layout {
+        let root = detect_install_root(&config.game.executable, layout);
+        println!("Using {} as the working directory", root.display());
+        config.launch.working_dir = Some(root);
+    }
+
+    let name = config.game.name.clone();
+    finish_adding_game(dirs, &name, config, no_shortcut).await
+}
+
+/// Build the config for `cellar add`, creating the prefix and running the installer as needed
+#[allow(clippy::too_many_arguments)]
+async fn new_game_from_args(
+    dirs: &CellarDirectories,
+    name: String,
+    exe: Option<String>,
+    installer: Option<String>,
+    from_url: bool,
+    interactive: bool,
+    proton: Option<String>,
+    prefix: Option<String>,
+    force: bool,
+    native: bool,
+    no_prefix: bool,
+) -> Result<GameConfig> {
+    let proton = match proton {
+        Some(proton) => Some(proton),
+        None => {
+            CellarDefaults::load(&dirs.defaults_file)?
+                .game
+                .proton_version
+        }
+    };
+
+    if let Some(installer) = installer {
+        return add_game_from_installer(dirs, &name, &installer, from_url, exe, proton, prefix)
+            .await;
     }
 
-    if installer.is_some() {
-        return Err(anyhow!("Installer mode not yet implemented in Phase 1"));
+    if interactive {
+        return add_game_interactive(dirs, &name, exe, proton, prefix, force).await;
     }
 
     let exe_path =
@@ -185,153 +633,186 @@ pub async fn add_game(
         return Err(anyhow!("Path is not a file: {}", exe_path.display()));
     }
 
-    if name.trim().is_empty() {
-        return Err(anyhow!("Game name cannot be empty"));
+    if native {
+        return create_native_game_config(&name, exe_path, dirs);
     }
 
-    let config =
-        create_basic_game_config(&name, exe_path, &dirs, proton.as_deref(), prefix.as_deref())
-            .await?;
-    save_game_config(&dirs, &name, &config)?;
-
-    // Create desktop shortcut if enabled
-    let config_name = sanitize_filename(&name);
-    if let Err(e) = desktop::create_desktop_shortcut(&config, &config_name).await {
-        eprintln!("Warning: Failed to create desktop shortcut: {}", e);
+    if let Err(e) = check_windows_executable(&exe_path) {
+        if !force {
+            return Err(anyhow!(
+                "{e}. Wine can only run Windows programs; use --force to add it anyway"
+            ));
+        }
+        eprintln!("Warning: {e}. Adding it anyway because of --force");
     }
 
-    println!("Successfully added game: {name}");
-    println!(
-        "  Config saved to: {}",
-        dirs.get_game_config_path(&name).display()
-    );
+    if name.trim().is_empty() {
+        return Err(anyhow!("Game name cannot be empty"));
+    }
 
-    Ok(())
-}
+    if no_prefix {
+        if let Some(prefix) = &prefix {
+            require_existing_prefix(dirs, prefix)?;
+        }
+    }
 
-pub async fn launch_game(name: String) -> Result<()> {
-    let launcher = crate::launch::GameLauncher::new()?;
-    launcher.launch_game_by_name(&name).await
+    create_basic_game_config(&name, exe_path, dirs, proton.as_deref(), prefix.as_deref()).await
 }
 
-pub fn list_games(name: Option<String>) -> Result<()> {
-    let dirs = CellarDirectories::new()?;
+/// Build a game config by asking for each setting, using the command-line values as defaults
+async fn add_game_interactive(
+    dirs: &CellarDirectories,
+    name: &str,
+    exe: Option<String>,
+    proton: Option<String>,
+    prefix: Option<String>,
+    force: bool,
+) -> Result<GameConfig> {
+    if crate::utils::output::assume_yes() {
+        return Err(anyhow!(
+            "--interactive asks for every setting and can't be combined with --yes"
+        ));
+    }
 
-    match name {
-        Some(game_name) => {
-            let config = load_game_config(&dirs, &game_name)?;
-            println!("Game: {}", config.game.name);
+    let name = loop {
+        let answer = prompt_with_default("Game name", Some(name))?;
+        if !answer.trim().is_empty() {
+            break answer;
         }
-        None => {
-            let games = dirs.list_game_configs()?;
+        println!("Game name cannot be empty.");
+    };
 
-            if games.is_empty() {
-                println!("No games configured.");
-                return Ok(());
+    let exe_path = loop {
+        let answer = prompt_with_default("Path to the game executable", exe.as_deref())?;
+        let path = crate::utils::fs::expand_tilde(&answer)?;
+        if !path.is_file() {
+            println!("Executable not found: {}", path.display());
+            continue;
+        }
+        match check_windows_executable(&path) {
+            Ok(()) => break path,
+            Err(e) if force => {
+                eprintln!("Warning: {e}. Adding it anyway because of --force");
+                break path;
             }
-
-            println!("Configured games:");
-            for game_name in &games {
-                match load_game_config(&dirs, game_name) {
-                    Ok(config) => {
-                        println!("  {}", config.game.name);
-                        println!("    Executable: {}", config.game.executable.display());
-                        println!("    Proton: {}", config.game.proton_version);
-                    }
-                    Err(_) => {
-                        println!("  {game_name} [error loading config]");
-                    }
+            Err(e) => {
+                println!("{e}.");
+                if prompt_yes_no("Use it anyway?", false)? {
+                    break path;
                 }
             }
         }
-    }
+    };
 
-    Ok(())
-}
+    let proton_version = prompt_for_proton_version(dirs, proton.as_deref()).await?;
 
-pub fn remove_game(name: String) -> Result<()> {
-    let dirs = CellarDirectories::new()?;
-    let config_path = dirs.get_game_config_path(&name);
+    let default_prefix = prefix.unwrap_or_else(|| sanitize_filename(&name));
+    let prefix_name = prompt_with_default("Prefix name", Some(&default_prefix))?;
 
-    if !config_path.exists() {
-        return Err(anyhow!("Game '{}' not found", name));
-    }
+    let defaults = CellarDefaults::load(&dirs.defaults_file)?;
+    let gamescope = prompt_yes_no("Run the game in gamescope?", false)?;
+    let gamemode = prompt_yes_no("Enable gamemode?", defaults.launch.gamemode)?;
+    let mangohud = prompt_yes_no("Show the MangoHud overlay?", defaults.launch.mangohud)?;
 
-    // Load the config to get the prefix path
-    let config = load_game_config(&dirs, &name)?;
-    let prefix_path = &config.game.wine_prefix;
-    let prefix_name = prefix_path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown");
+    let (_, wine_prefix, proton_version) =
+        prepare_game_prefix(&name, dirs, Some(&proton_version), Some(&prefix_name)).await?;
 
-    // Remove the game config file
-    fs::remove_file(&config_path).map_err(|e| anyhow!("Failed to remove config file: {}", e))?;
+    let mut config = new_game_config(&name, exe_path, wine_prefix, proton_version);
+    defaults.apply(&mut config);
+    config.gamescope.enabled = gamescope;
+    config.launch.gamemode = gamemode;
+    config.launch.mangohud = mangohud;
 
-    // Remove desktop shortcut if it exists
-    if let Err(e) = desktop::remove_desktop_shortcut(&name) {
-        eprintln!("Warning: Failed to remove desktop shortcut: {}", e);
+    validate_game_config(&config)?;
+    Ok(config)
+}
+
+/// Let the user pick an installed Proton version, type one in, or install the latest Proton-GE
+async fn prompt_for_proton_version(
+    dirs: &CellarDirectories,
+    default: Option<&str>,
+) -> Result<String> {
+    let proton_manager = ProtonManager::new(dirs.get_runners_path());
+    let mut installed = proton_manager.discover_local_runners().await?;
+    installed.sort_by_key(|r| std::cmp::Reverse(r.version_tuple()));
+
+    println!("Proton versions:");
+    for (i, runner) in installed.iter().enumerate() {
+        println!("  {}) {}", i + 1, runner.version);
     }
+    let install_latest = installed.len() + 1;
+    println!("  {install_latest}) Install the latest Proton-GE");
 
-    // Check if other games are using the same prefix
-    let other_games_using_prefix = check_other_games_using_prefix(&dirs, prefix_path, &name)?;
+    let default = match default {
+        Some(version) => version.to_string(),
+        None if installed.is_empty() => install_latest.to_string(),
+        None => "1".to_string(),
+    };
 
-    if other_games_using_prefix.is_empty() && prefix_path.exists() {
-        // Prompt user to delete the prefix
-        if prompt_user_for_prefix_deletion(prefix_name)? {
-            if let Err(e) = fs::remove_dir_all(prefix_path) {
-                eprintln!("Warning: Failed to remove prefix '{}': {}", prefix_name, e);
-            } else {
-                println!("Successfully removed prefix: {}", prefix_name);
-            }
+    let answer = prompt_with_default("Proton version (number or version name)", Some(&default))?;
+    match answer.parse::<usize>() {
+        Ok(n) if n == install_latest => {
+            let latest = proton_manager
+                .get_available_versions()
+                .await?
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow!("No Proton-GE releases found"))?;
+            download_and_install_proton(dirs, &latest).await?;
+            Ok(latest)
         }
-    } else if !other_games_using_prefix.is_empty() {
-        println!(
-            "Note: Prefix '{}' is still being used by: {}",
-            prefix_name,
-            other_games_using_prefix.join(", ")
-        );
+        Ok(n) if (1..=installed.len()).contains(&n) => Ok(installed[n - 1].version.clone()),
+        // Anything else is a version name, resolved (and offered for download) later
+        _ => Ok(answer),
     }
+}
 
-    println!("Successfully removed game: {name}");
+/// Ask a question on stdin, returning the default for an empty answer
+fn prompt_with_default(question: &str, default: Option<&str>) -> Result<String> {
+    use std::io::{self, Write};
 
-    Ok(())
+    match default {
+        Some(default) if !default.is_empty() => print!("{question} [{default}]: "),
+        _ => print!("{question}: "),
+    }
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input)? == 0 {
+        return Err(anyhow!("No input received, setup cancelled"));
+    }
+
+    let input = input.trim();
+    if input.is_empty() {
+        Ok(default.unwrap_or_default().to_string())
+    } else {
+        Ok(input.to_string())
+    }
 }
 
-/// Check if other games are using the same prefix
-fn check_other_games_using_prefix(
-    dirs: &CellarDirectories,
-    prefix_path: &std::path::Path,
-    current_game: &str,
-) -> Result<Vec<String>> {
-    let mut games_using_prefix = Vec::new();
-    let config_dir = &dirs.configs_dir;
+/// Ask a yes/no question on stdin
+fn prompt_yes_no(question: &str, default: bool) -> Result<bool> {
+    let hint = if default { "Y/n" } else { "y/N" };
+    let answer = prompt_with_default(&format!("{question} [{hint}]"), None)?.to_lowercase();
 
-    if let Ok(entries) = fs::read_dir(config_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("toml") {
-                if let Some(game_name) = path.file_stem().and_then(|s| s.to_str()) {
-                    if game_name != current_game {
-                        if let Ok(config) = load_game_config(dirs, game_name) {
-                            if config.game.wine_prefix == prefix_path {
-                                games_using_prefix.push(game_name.to_string());
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    Ok(games_using_prefix)
+    Ok(match answer.as_str() {
+        "" => default,
+        answer => answer == "y" || answer == "yes",
+    })
 }
 
-/// Prompt user for permission to delete a prefix
-fn prompt_user_for_prefix_deletion(prefix_name: &str) -> Result<bool> {
+/// Ask before doing something destructive or slow; anything but "y"/"yes" is a no
+///
+/// With `--yes` this answers yes without reading stdin.
+fn confirm(question: &str) -> Result<bool> {
     use std::io::{self, Write};
 
-    print!("Also delete wine prefix '{}'? [y/N]: ", prefix_name);
+    if crate::utils::output::assume_yes() {
+        println!("{question} [y/N]: y (--yes)");
+        return Ok(true);
+    }
+
+    print!("{question} [y/N]: ");
     io::stdout().flush()?;
 
     let mut input = String::new();
@@ -341,509 +822,2316 @@ fn prompt_user_for_prefix_deletion(prefix_name: &str) -> Result<bool> {
     Ok(input == "y" || input == "yes")
 }
 
-pub fn show_game_info(name: String) -> Result<()> {
-    let dirs = CellarDirectories::new()?;
-    let config = load_game_config(&dirs, &name)?;
-
-    println!("Game Information for: {}", config.game.name);
-    println!("  Executable: {}", config.game.executable.display());
-    println!("  Wine Prefix: {}", config.game.wine_prefix.display());
-    println!("  Proton Version: {}", config.game.proton_version);
-
-    if let Some(dxvk_version) = &config.game.dxvk_version {
-        println!("  DXVK Version: {dxvk_version}");
+/// Save a newly created game config and create its desktop shortcut
+///
+/// `no_shortcut` turns shortcuts off in the saved config, so later syncs skip the game too.
+async fn finish_adding_game(
+    dirs: &CellarDirectories,
+    name: &str,
+    mut config: GameConfig,
+    no_shortcut: bool,
+) -> Result<()> {
+    if no_shortcut {
+        config.desktop.create_shortcut = false;
     }
+    save_game_config(dirs, &config)?;
 
-    println!("\nWine Configuration:");
-    println!("  esync: {}", config.wine_config.esync);
-    println!("  fsync: {}", config.wine_config.fsync);
-    println!("  dxvk: {}", config.wine_config.dxvk);
-    println!("  dxvk_async: {}", config.wine_config.dxvk_async);
-
-    if config.gamescope.enabled {
-        println!("\nGamescope Configuration:");
-        println!(
-            "  Game Resolution: {}x{}",
-            config.gamescope.width, config.gamescope.height
-        );
-        println!(
-            "  Output Resolution: {}x{}",
-            config.gamescope.output_width, config.gamescope.output_height
-        );
-        println!("  Refresh Rate: {}Hz", config.gamescope.refresh_rate);
-        println!("  Upscaling: {}", config.gamescope.upscaling);
+    // Create desktop shortcut if enabled
+    if config.desktop.create_shortcut {
+        let config_name = dirs.get_game_file_stem(name);
+        if let Err(e) = desktop::create_desktop_shortcut(dirs, &config, &config_name).await {
+            eprintln!("Warning: Failed to create desktop shortcut: {}", e);
+        }
     }
 
+    println!("Successfully added game: {name}");
+    println!(
+        "  Config saved to: {}",
+        dirs.get_game_config_path(name).display()
+    );
+
     Ok(())
 }
 
-async fn create_basic_game_config(
-    name: &str,
-    exe_path: PathBuf,
+/// Run an installer in the game's prefix and build a config for the installed executable
+async fn add_game_from_installer(
     dirs: &CellarDirectories,
-    proton_version: Option<&str>,
-    prefix_name: Option<&str>,
+    name: &str,
+    installer: &str,
+    from_url: bool,
+    exe: Option<String>,
+    proton: Option<String>,
+    prefix: Option<String>,
 ) -> Result<GameConfig> {
-    // Determine prefix name: use provided or default to game name
-    let prefix_name = match prefix_name {
-        Some(provided_prefix) => provided_prefix.to_string(),
-        None => sanitize_filename(name), // Only sanitize when using game name as prefix
-    };
-    let wine_prefix = dirs.get_prefixes_path().join(&prefix_name);
-
-    // Determine Proton version to use BEFORE creating prefix
-    let proton_version = match proton_version {
-        Some(version) => {
-            println!("Using specified Proton version: {version}");
+    if name.trim().is_empty() {
+        return Err(anyhow!("Game name cannot be empty"));
+    }
 
-            // Check if the specified version is available locally
-            let proton_manager = ProtonManager::new(dirs.get_runners_path());
-            let local_runners = proton_manager.discover_local_runners().await?;
+    let exe = exe.ok_or_else(|| {
+        anyhow!("--exe is required with --installer: the path of the installed game executable, absolute or relative to the prefix's drive_c")
+    })?;
 
-            // Find the matching runner and get its full version name
-            if let Some(matched_runner) = local_runners
-                .iter()
-                .find(|r| r.version == version || r.name.contains(version))
-            {
-                // Use the full version name from the matched runner
-                matched_runner.version.clone()
-            } else {
-                println!("Proton version '{version}' not found locally.");
+    if !from_url {
+        let installer_path = crate::utils::fs::expand_tilde(installer)?;
+        if !installer_path.is_file() {
+            return Err(anyhow!("Installer not found: {}", installer_path.display()));
+        }
+        return install_game(dirs, name, &installer_path, installer, &exe, proton, prefix).await;
+    }
 
-                // Check if version is available for download
-                match check_proton_version_available(&proton_manager, version).await {
-                    Ok(download_version) => {
-                        // Ask user for permission to download
-                        if prompt_user_for_download(version).await? {
-                            download_and_install_proton(&proton_manager, &download_version).await?;
-                            println!("Successfully installed Proton version: {version}");
+    let download_dir = dirs
+        .get_downloads_path()
+        .join(format!("installer-{}", std::process::id()));
+    fs::create_dir_all(&download_dir)?;
 
-                            // After installation, find the full version name
-                            let updated_runners = proton_manager.discover_local_runners().await?;
-                            if let Some(installed_runner) = updated_runners.iter().find(|r| {
-                                r.version == version
-                                    || r.name.contains(version)
-                                    || r.version.contains(version)
-                            }) {
-                                installed_runner.version.clone()
-                            } else {
-                                download_version
-                            }
-                        } else {
-                            return Err(anyhow!("Proton version '{}' is required but not available locally. Operation cancelled.", version));
-                        }
-                    }
-                    Err(_) => {
-                        return Err(anyhow!(
-                            "Proton version '{}' not found locally and not available for download.\nAvailable versions can be seen with: cellar runners available",
-                            version
-                        ));
-                    }
-                }
-            }
-        }
-        None => {
-            println!("No Proton version specified, finding latest available...");
-            let latest_version = get_latest_proton_version(dirs).await?;
-            println!("Using latest available Proton version: {latest_version}");
-            latest_version
+    let result = match download_installer(installer, &download_dir).await {
+        Ok(installer_path) => {
+            install_game(dirs, name, &installer_path, installer, &exe, proton, prefix).await
         }
+        Err(e) => Err(e),
     };
 
-    // Check if prefix exists, if not create it
-    if !wine_prefix.exists() {
-        create_prefix(&prefix_name, Some(&proton_version)).await?;
-    } else {
-        println!("Using existing prefix: {prefix_name}");
+    if let Err(e) = fs::remove_dir_all(&download_dir) {
+        eprintln!("Warning: Failed to remove downloaded installer: {}", e);
     }
 
-    let config = GameConfig {
-        game: GameInfo {
-            name: name.to_string(),
-            executable: exe_path,
-            wine_prefix,
-            proton_version,
-            dxvk_version: None,
-        },
-        launch: LaunchConfig::default(),
-        wine_config: WineConfig::default(),
-        dxvk: Default::default(),
-        gamescope: GamescopeConfig::default(),
-        desktop: DesktopConfig::default(),
-        installation: None,
-    };
-
-    validate_game_config(&config)?;
-    Ok(config)
+    result
 }
 
-/// Get the latest available Proton version from cache, or discover if cache is missing/old
-async fn get_latest_proton_version(dirs: &CellarDirectories) -> Result<String> {
-    let cache_path = dirs.get_cache_path().join("runners.toml");
-
-    // Try to load from cache first
-    let mut proton_runners = Vec::new();
-
-    if cache_path.exists() {
-        if let Ok(cache_content) = fs::read_to_string(&cache_path) {
-            if let Ok(cache) = toml::from_str::<RunnerCache>(&cache_content) {
-                // Check if cache is recent (less than 1 hour old)
-                let cache_age = chrono::Utc::now().signed_duration_since(cache.last_updated);
-                if cache_age.num_hours() < 1 {
-                    proton_runners = cache
-                        .runners
-                        .into_iter()
-                        .filter(|r| matches!(r.runner_type, RunnerType::Proton))
-                        .collect();
-                }
-            }
-        }
-    }
-
-    // If cache is empty or old, discover live
-    if proton_runners.is_empty() {
-        let runners_path = dirs.get_runners_path();
-        let proton_manager = ProtonManager::new(runners_path);
-        proton_runners = proton_manager.discover_local_runners().await?;
-    }
-
-    if proton_runners.is_empty() {
+/// Download an installer into `download_dir`, rejecting responses that aren't a binary
+async fn download_installer(url: &str, download_dir: &Path) -> Result<PathBuf> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| anyhow!("Invalid URL '{}': {}", url, e))?;
+    if parsed.scheme() != "https" {
         return Err(anyhow!(
-            "No Proton versions found. Please install a Proton version first using:\n  cellar runners install proton <version>\n\nTo see available versions for download, use:\n  cellar runners available"
+            "Only https installer URLs are supported; download '{}' yourself and pass the file to --installer",
+            url
         ));
     }
 
-    // Sort versions to get the latest (assuming semantic versioning-like names)
-    proton_runners.sort_by(|a, b| {
-        // Extract version numbers for comparison (e.g., "GE-Proton9-1" -> "9.1")
-        let version_a = extract_version_number(&a.version);
-        let version_b = extract_version_number(&b.version);
-        version_b
-            .partial_cmp(&version_a)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
+    let file_name = parsed
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|segment| !segment.is_empty())
+        .unwrap_or("installer.exe")
+        .to_string();
 
-    Ok(proton_runners[0].version.clone())
-}
+    println!("Downloading installer from {url}...");
 
-/// Check if a Proton version is available for download
-async fn check_proton_version_available(
-    proton_manager: &ProtonManager,
-    version: &str,
-) -> Result<String> {
-    let available_versions = proton_manager.get_available_versions().await?;
+    let client =
+        crate::runners::common::http_client(concat!("cellar/", env!("CARGO_PKG_VERSION")))?;
+    let response = client.get(parsed).send().await?;
 
-    // Try exact match first
-    if available_versions.iter().any(|v| v == version) {
-        return Ok(version.to_string());
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "Failed to download installer: HTTP {}",
+            response.status()
+        ));
     }
 
-    // Try partial match (e.g., user provides "9-1" for "GE-Proton9-1")
-    if let Some(found) = available_versions
-        .iter()
-        .find(|v| v.contains(version) || v.ends_with(&format!("-{version}")))
+    // A text response is almost always an error or login page rather than an installer
+    if let Some(content_type) = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
     {
-        return Ok(found.clone());
+        if content_type.starts_with("text/") {
+            return Err(anyhow!(
+                "URL returned '{}' instead of an installer; the link may require logging in",
+                content_type
+            ));
+        }
     }
 
-    Err(anyhow!(
-        "Version '{}' not found in available releases",
-        version
-    ))
-}
-
-/// Prompt user for permission to download a Proton version
-async fn prompt_user_for_download(version: &str) -> Result<bool> {
-    use std::io::{self, Write};
+    let installer_path = download_dir.join(&file_name);
+    let size = download_to_file(response, &installer_path, 0, MAX_INSTALLER_SIZE).await?;
 
-    print!("Download Proton version '{version}'? [y/N]: ");
-    io::stdout().flush()?;
+    if size == 0 {
+        return Err(anyhow!("Downloaded installer is empty"));
+    }
 
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-    let input = input.trim().to_lowercase();
+    // MSI packages aren't PE files, but anything else should be a Windows program
+    let is_msi = Path::new(&file_name)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("msi"));
+    if !is_msi {
+        check_windows_executable(&installer_path)
+            .map_err(|e| anyhow!("Downloaded file is not an installer: {e}"))?;
+    }
 
-    Ok(input == "y" || input == "yes")
+    println!("Downloaded {} ({})", file_name, format_size(size));
+    Ok(installer_path)
 }
 
-/// Download and install a Proton version
-async fn download_and_install_proton(proton_manager: &ProtonManager, version: &str) -> Result<()> {
-    println!("Downloading Proton version: {version}");
-
-    // Extract the actual version number from the full version string
-    // e.g., "GE-Proton10-10" -> "10-10"
-    let version_number = if version.starts_with("GE-Proton") {
-        version.strip_prefix("GE-Proton").unwrap_or(version)
+async fn install_game(
+    dirs: &CellarDirectories,
+    name: &str,
+    installer_path: &Path,
+    installer_source: &str,
+    exe: &str,
+    proton: Option<String>,
+    prefix: Option<String>,
+) -> Result<GameConfig> {
+    let (prefix_name, wine_prefix, proton_version) =
+        prepare_game_prefix(name, dirs, proton.as_deref(), prefix.as_deref()).await?;
+
+    run_in_prefix(
+        dirs,
+        &prefix_name,
+        &installer_path.to_string_lossy(),
+        &[],
+        &[],
+        Some(&proton_version),
+    )
+    .await?;
+
+    // Relative paths are inside the prefix, e.g. "GOG Games/Game/game.exe"
+    let exe_path = if exe.starts_with('~') || Path::new(exe).is_absolute() {
+        crate::utils::fs::expand_tilde(exe)?
     } else {
-        version
+        wine_prefix.join("drive_c").join(exe)
     };
 
-    let download_path = proton_manager
-        .download_runner("proton-ge", version_number)
-        .await?;
-    println!("Installing Proton version: {version}");
+    if !exe_path.is_file() {
+        return Err(anyhow!(
+            "Installer finished but the game executable was not found: {}",
+            exe_path.display()
+        ));
+    }
 
-    proton_manager
-        .install_runner(&download_path, std::path::Path::new(""))
-        .await?;
+    let install_location = exe_path
+        .parent()
+        .map(|p| p.display().to_string())
+        .unwrap_or_default();
+
+    let mut config = new_game_config(name, exe_path, wine_prefix, proton_version);
+    CellarDefaults::load(&dirs.defaults_file)?.apply(&mut config);
+    config.installation = Some(InstallationInfo {
+        installer_path: PathBuf::from(installer_source),
+        install_date: chrono::Local::now().to_rfc3339(),
+        install_location,
+    });
 
-    // Refresh runner cache after installation
-    let dirs = CellarDirectories::new()?;
-    refresh_runners_cache(&dirs).await?;
+    validate_game_config(&config)?;
+    Ok(config)
+}
 
-    Ok(())
+/// Combine a `--<flag>`/`--no-<flag>` pair into an override, `None` when neither is given
+pub fn flag_override(enable: bool, disable: bool) -> Option<bool> {
+    match (enable, disable) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
+    }
 }
 
-/// Refresh runner cache without printing messages
-async fn refresh_runners_cache(dirs: &CellarDirectories) -> Result<()> {
-    let cache_path = dirs.get_cache_path().join("runners.toml");
+pub async fn launch_game(
+    dirs: &CellarDirectories,
+    name: String,
+    options: crate::launch::LaunchOptions,
+) -> Result<()> {
+    let name = resolve_game_name(dirs, &name)?;
+    let launcher = crate::launch::GameLauncher::new(dirs.clone());
+    launcher.launch_game_by_name(&name, options).await
+}
 
-    // Remove existing cache
-    if cache_path.exists() {
-        std::fs::remove_file(&cache_path)?;
+pub fn list_games(
+    dirs: &CellarDirectories,
+    name: Option<String>,
+    proton: Option<&str>,
+    sort: ListSort,
+    format: ListFormat,
+) -> Result<()> {
+    if let ListFormat::Csv = format {
+        return write_games_csv(dirs, proton, sort, std::io::stdout());
     }
 
-    // Discover all runners and cache them
-    let runners_path = dirs.get_runners_path();
-    let proton_manager = ProtonManager::new(runners_path.clone());
-    let dxvk_manager = DxvkManager::new(runners_path);
-
-    let mut all_runners = Vec::new();
-    all_runners.extend(proton_manager.discover_local_runners().await?);
-    all_runners.extend(dxvk_manager.discover_local_runners().await?);
+    match name {
+        Some(game_name) => {
+            let config = load_game_config(dirs, &game_name)?;
+            if uses_proton_version(&config, proton) {
+                println!("Game: {}", config.game.name);
+            }
+        }
+        None => {
+            let games = load_sorted_game_configs(dirs, sort)?;
 
-    // Save to cache
-    let cache = crate::runners::RunnerCache {
-        runners: all_runners,
-        last_updated: chrono::Utc::now(),
-    };
+            if games.is_empty() {
+                println!("No games configured.");
+                return Ok(());
+            }
 
-    let cache_content = toml::to_string_pretty(&cache)?;
-    std::fs::write(&cache_path, cache_content)?;
+            println!("Configured games:");
+            for (game_name, config) in &games {
+                match config {
+                    Ok(config) if !uses_proton_version(config, proton) => {}
+                    Ok(config) => {
+                        println!("  {}", config.game.name);
+                        println!("    Executable: {}", config.game.executable.display());
+                        println!("    Proton: {}", config.game.proton_version);
+                        if let Some(last_played) = config.stats.last_played {
+                            println!("    Last played: {}", format_last_played(last_played));
+                        }
+                    }
+                    Err(_) => {
+                        println!("  {game_name} [error loading config]");
+                    }
+                }
+            }
+        }
+    }
 
     Ok(())
 }
 
-/// Extract version number from Proton version string for comparison
-/// E.g., "GE-Proton9-1" -> 9.1, "GE-Proton10-10" -> 10.10
-pub fn extract_version_number(version: &str) -> f64 {
-    // Try to extract major.minor version from patterns like "GE-Proton9-1"
-    if let Some(captures) = regex::Regex::new(r"GE-Proton(\d+)-(\d+)")
-        .unwrap()
-        .captures(version)
-    {
-        let major: u32 = captures[1].parse().unwrap_or(0);
-        let minor: u32 = captures[2].parse().unwrap_or(0);
-        return major as f64 + (minor as f64 / 100.0);
+/// Every game config paired with its name, in the requested order
+///
+/// Games whose config fails to load keep their place when sorting by name and go last otherwise.
+fn load_sorted_game_configs(
+    dirs: &CellarDirectories,
+    sort: ListSort,
+) -> Result<Vec<(String, Result<GameConfig>)>> {
+    let mut games: Vec<_> = dirs
+        .list_game_configs()?
+        .into_iter()
+        .map(|name| {
+            let config = load_game_config(dirs, &name);
+            (name, config)
+        })
+        .collect();
+
+    if let ListSort::Recent = sort {
+        // Stable sort, so games never played stay in name order
+        games.sort_by_key(|(_, config)| {
+            std::cmp::Reverse(
+                config
+                    .as_ref()
+                    .ok()
+                    .and_then(|config| config.stats.last_played),
+            )
+        });
     }
 
-    // Fallback: try to extract any number from the version string
-    if let Some(captures) = regex::Regex::new(r"(\d+)").unwrap().captures(version) {
-        return captures[1].parse::<f64>().unwrap_or(0.0);
+    Ok(games)
+}
+
+/// Playtime as hours and minutes, e.g. "3h 24m"
+fn format_playtime(secs: u64) -> String {
+    let hours = secs / 3600;
+    let minutes = secs % 3600 / 60;
+    if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
     }
+}
 
-    0.0
+fn format_last_played(last_played: chrono::DateTime<chrono::Utc>) -> String {
+    last_played
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
 }
 
-fn save_game_config(dirs: &CellarDirectories, name: &str, config: &GameConfig) -> Result<()> {
-    let config_path = dirs.get_game_config_path(name);
-    let toml_content =
-        toml::to_string_pretty(config).map_err(|e| anyhow!("Failed to serialize config: {}", e))?;
+/// Whether a game matches a `--proton` filter, by exact name or substring
+fn uses_proton_version(config: &GameConfig, proton: Option<&str>) -> bool {
+    match proton {
+        Some(version) => config.game.proton_version.contains(version),
+        None => true,
+    }
+}
 
-    fs::write(&config_path, toml_content)
-        .map_err(|e| anyhow!("Failed to write config file: {}", e))?;
+/// Write every game as a CSV row; games whose config fails to load are skipped
+fn write_games_csv<W: std::io::Write>(
+    dirs: &CellarDirectories,
+    proton: Option<&str>,
+    sort: ListSort,
+    out: W,
+) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record([
+        "name",
+        "proton_version",
+        "dxvk_version",
+        "prefix",
+        "executable",
+        "gamescope",
+        "last_played",
+        "playtime",
+    ])?;
+
+    for (_, config) in load_sorted_game_configs(dirs, sort)? {
+        let Ok(config) = config else {
+            continue;
+        };
+        if !uses_proton_version(&config, proton) {
+            continue;
+        }
 
+        writer.write_record([
+            config.game.name.as_str(),
+            config.game.proton_version.as_str(),
+            config.game.dxvk_version.as_deref().unwrap_or(""),
+            &config.game.wine_prefix.to_string_lossy(),
+            &config.game.executable.to_string_lossy(),
+            if config.gamescope.enabled {
+                "true"
+            } else {
+                "false"
+            },
+            &config
+                .stats
+                .last_played
+                .map(|time| time.to_rfc3339())
+                .unwrap_or_default(),
+            &config.stats.total_playtime_secs.to_string(),
+        ])?;
+    }
+
+    writer.flush()?;
     Ok(())
 }
 
-fn load_game_config(dirs: &CellarDirectories, name: &str) -> Result<GameConfig> {
-    let config_path = dirs.get_game_config_path(name);
+/// What `cellar backup` saves, relative to the Cellar data directory; prefixes
+/// and runners are left out since they're large and can be recreated
+const BACKUP_ENTRIES: [&str; 3] = ["configs", "icons", "cache/runners.toml"];
 
-    if !config_path.exists() {
-        return Err(anyhow!("Game '{}' not found", name));
+pub fn backup_cellar(dirs: &CellarDirectories, output: &Path) -> Result<()> {
+    let output = crate::utils::fs::expand_tilde(output)?;
+    if output.exists() {
+        return Err(anyhow!("Output file already exists: {}", output.display()));
     }
 
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| anyhow!("Failed to read config file: {}", e))?;
+    println!("Backing up Cellar data to {}...", output.display());
+    crate::utils::archive::create_tar_gz(&dirs.base_dir, &BACKUP_ENTRIES, &output)?;
 
-    let config: GameConfig =
-        toml::from_str(&content).map_err(|e| anyhow!("Failed to parse config file: {}", e))?;
-
-    Ok(config)
+    let size = fs::metadata(&output)?.len();
+    println!("Backup complete ({})", format_size(size));
+    Ok(())
 }
 
-// Runner management functions
-pub async fn handle_runners_command(command: RunnerCommands) -> Result<()> {
-    match command {
-        RunnerCommands::List => list_runners().await,
-        RunnerCommands::Refresh => refresh_runners().await,
-        RunnerCommands::Available => show_available_runners().await,
-        RunnerCommands::Install {
-            runner_type,
-            version,
-        } => install_runner(&runner_type, &version).await,
-        RunnerCommands::InstallDxvk { version, prefix } => {
-            install_dxvk_to_prefix(&version, &prefix).await
-        }
-        RunnerCommands::Remove {
-            runner_type,
-            version,
-        } => remove_runner(&runner_type, &version).await,
+pub fn restore_cellar(dirs: &CellarDirectories, input: &Path, force: bool) -> Result<()> {
+    let input = crate::utils::fs::expand_tilde(input)?;
+    if !input.is_file() {
+        return Err(anyhow!("Backup not found: {}", input.display()));
+    }
+
+    let files = crate::utils::archive::list_tar_gz_files(&input)?;
+    if let Some(unexpected) = files
+        .iter()
+        .find(|file| !BACKUP_ENTRIES.iter().any(|entry| file.starts_with(entry)))
+    {
+        return Err(anyhow!(
+            "{} is not a Cellar backup (unexpected entry: {})",
+            input.display(),
+            unexpected.display()
+        ));
+    }
+
+    // The runner cache is regenerated anyway, so only configs and icons are protected
+    let existing: Vec<_> = files
+        .iter()
+        .filter(|file| !file.starts_with("cache") && dirs.base_dir.join(file).exists())
+        .collect();
+    if !existing.is_empty() && !force {
+        let names: Vec<_> = existing
+            .iter()
+            .map(|file| format!("  {}", file.display()))
+            .collect();
+        return Err(anyhow!(
+            "Restoring would overwrite existing files:\n{}\nUse --force to overwrite them",
+            names.join("\n")
+        ));
+    }
+
+    crate::utils::archive::extract_tar_gz_secure(&input, &dirs.base_dir)?;
+
+    println!("Restored {} file(s):", files.len());
+    for file in &files {
+        println!("  {}", file.display());
     }
+    Ok(())
 }
 
-async fn list_runners() -> Result<()> {
-    let dirs = CellarDirectories::new()?;
-    dirs.ensure_all_exist()?; // Ensure all directories exist
-    let cache_path = dirs.get_cache_path().join("runners.toml");
+pub fn remove_game(dirs: &CellarDirectories, name: String) -> Result<()> {
+    let name = resolve_game_name(dirs, &name)?;
+    let config_path = dirs.get_game_config_path(&name);
 
-    // Try to load from cache first
-    if cache_path.exists() {
-        if let Ok(cache_content) = fs::read_to_string(&cache_path) {
-            if let Ok(cache) = toml::from_str::<crate::runners::RunnerCache>(&cache_content) {
-                // Check if cache is recent (less than 1 hour old)
-                let cache_age = chrono::Utc::now().signed_duration_since(cache.last_updated);
-                if cache_age.num_hours() < 1 {
-                    println!("Installed Runners (cached):");
+    if !config_path.exists() {
+        return Err(anyhow!("Game '{}' not found", name));
+    }
 
-                    let proton_runners: Vec<_> = cache
-                        .runners
-                        .iter()
-                        .filter(|r| matches!(r.runner_type, crate::runners::RunnerType::Proton))
-                        .collect();
+    // Load the config to get the prefix path
+    let config = load_game_config(dirs, &name)?;
+    let prefix_path = &config.game.wine_prefix;
+    let prefix_name = prefix_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown");
 
-                    let dxvk_runners: Vec<_> = cache
-                        .runners
-                        .iter()
-                        .filter(|r| matches!(r.runner_type, crate::runners::RunnerType::Dxvk))
-                        .collect();
+    // Remove desktop shortcut if it exists, while the config still names its file
+    if let Err(e) = desktop::remove_desktop_shortcut(dirs, &name) {
+        eprintln!("Warning: Failed to remove desktop shortcut: {}", e);
+    }
 
-                    if !proton_runners.is_empty() {
-                        println!("\nProton Runners:");
-                        for runner in &proton_runners {
-                            println!("  {} ({})", runner.name, runner.version);
-                            println!("    Path: {}", runner.path.display());
-                        }
-                    }
+    // Remove the game config file
+    fs::remove_file(&config_path).map_err(|e| anyhow!("Failed to remove config file: {}", e))?;
 
-                    if !dxvk_runners.is_empty() {
-                        println!("\nDXVK Runners:");
-                        for runner in &dxvk_runners {
-                            println!("  {} ({})", runner.name, runner.version);
-                            println!("    Path: {}", runner.path.display());
-                        }
-                    }
+    // Native games have no prefix to clean up
+    if config.game.native {
+        println!("Successfully removed game: {name}");
+        return Ok(());
+    }
 
-                    if proton_runners.is_empty() && dxvk_runners.is_empty() {
-                        println!(
-                            "  No runners found. Use 'cellar runners install' to install runners."
-                        );
-                    }
+    // Check if other games are using the same prefix
+    let other_games_using_prefix = check_other_games_using_prefix(dirs, prefix_path, &name)?;
 
-                    return Ok(());
-                }
+    if other_games_using_prefix.is_empty() && prefix_path.exists() {
+        // Prompt user to delete the prefix
+        if confirm(&format!("Also delete wine prefix '{prefix_name}'?"))? {
+            if let Err(e) = fs::remove_dir_all(prefix_path) {
+                eprintln!("Warning: Failed to remove prefix '{}': {}", prefix_name, e);
+            } else {
+                println!("Successfully removed prefix: {}", prefix_name);
             }
         }
+    } else if !other_games_using_prefix.is_empty() {
+        println!(
+            "Note: Prefix '{}' is still being used by: {}",
+            prefix_name,
+            other_games_using_prefix.join(", ")
+        );
     }
 
-    // Cache is old or doesn't exist, scan live
-    let runners_path = dirs.get_runners_path();
+    println!("Successfully removed game: {name}");
 
-    let proton_manager = ProtonManager::new(runners_path.clone());
-    let dxvk_manager = DxvkManager::new(runners_path);
+    Ok(())
+}
 
-    println!("Installed Runners:");
+/// Check if other games are using the same prefix
+fn check_other_games_using_prefix(
+    dirs: &CellarDirectories,
+    prefix_path: &std::path::Path,
+    current_game: &str,
+) -> Result<Vec<String>> {
+    let mut games_using_prefix = Vec::new();
+    let config_dir = &dirs.configs_dir;
 
-    // List Proton runners
-    let proton_runners = proton_manager.discover_local_runners().await?;
-    if !proton_runners.is_empty() {
-        println!("\nProton Runners:");
-        for runner in &proton_runners {
-            println!("  {} ({})", runner.name, runner.version);
-            println!("    Path: {}", runner.path.display());
+    if let Ok(entries) = fs::read_dir(config_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("toml") {
+                if let Some(game_name) = path.file_stem().and_then(|s| s.to_str()) {
+                    if game_name != current_game {
+                        if let Ok((config, _)) = read_game_config(dirs, game_name) {
+                            if config.game.wine_prefix == prefix_path {
+                                games_using_prefix.push(game_name.to_string());
+                            }
+                        }
+                    }
+                }
+            }
         }
     }
 
-    // List DXVK runners
-    let dxvk_runners = dxvk_manager.discover_local_runners().await?;
-    if !dxvk_runners.is_empty() {
-        println!("\nDXVK Runners:");
-        for runner in &dxvk_runners {
-            println!("  {} ({})", runner.name, runner.version);
-            println!("    Path: {}", runner.path.display());
-        }
-    }
+    Ok(games_using_prefix)
+}
 
-    if proton_runners.is_empty() && dxvk_runners.is_empty() {
-        println!("  No runners found. Use 'cellar runners install' to install runners.");
+pub async fn handle_games_command(dirs: &CellarDirectories, command: GameCommands) -> Result<()> {
+    match command {
+        GameCommands::List {
+            format,
+            proton,
+            sort,
+        } => list_games(dirs, None, proton.as_deref(), sort, format),
+        GameCommands::OpenConfig { name, open } => open_game_config(dirs, &name, open).await,
+        GameCommands::Set { name, key, value } => set_game_value(dirs, &name, &key, &value),
+        GameCommands::ResetPrefix { name } => reset_game_prefix(dirs, &name).await,
+        GameCommands::MovePrefix {
+            name,
+            new_prefix,
+            copy,
+        } => move_game_prefix(dirs, &name, &new_prefix, copy).await,
     }
+}
 
-    Ok(())
+pub fn handle_config_command(dirs: &CellarDirectories, command: ConfigCommands) -> Result<()> {
+    match command {
+        ConfigCommands::Validate { name } => validate_configs(dirs, name),
+    }
 }
 
-async fn refresh_runners() -> Result<()> {
-    let dirs = CellarDirectories::new()?;
-    dirs.ensure_all_exist()?; // Ensure all directories exist including cache
-    let cache_path = dirs.get_cache_path().join("runners.toml");
+/// Parse and validate one game's config, or all of them, without touching runners or prefixes
+fn validate_configs(dirs: &CellarDirectories, name: Option<String>) -> Result<()> {
+    let games = match name {
+        Some(name) => vec![resolve_game_name(dirs, &name)?],
+        None => dirs.list_game_configs()?,
+    };
+    if games.is_empty() {
+        println!("No games configured.");
+        return Ok(());
+    }
 
-    // Remove existing cache
-    if cache_path.exists() {
-        fs::remove_file(&cache_path)?;
+    let checks: Vec<DoctorCheck> = games
+        .iter()
+        .flat_map(|game_name| {
+            let config_path = dirs.get_game_config_path(game_name);
+            let result = read_game_config(dirs, game_name).and_then(|(config, unknown_keys)| {
+                validate_game_config(&config).map(|()| (config, unknown_keys))
+            });
+            match result {
+                Ok((config, unknown_keys)) => {
+                    let mut checks = vec![DoctorCheck::pass(format!(
+                        "{} ({})",
+                        config.game.name,
+                        config_path.display()
+                    ))];
+                    checks.extend(unknown_keys.into_iter().map(|key| {
+                        DoctorCheck::warn(
+                            format!("{game_name}: unknown key '{key}' is ignored"),
+                            Some(format!("Fix or remove it in {}", config_path.display())),
+                        )
+                    }));
+                    checks
+                }
+                Err(e) => vec![DoctorCheck::fail(
+                    format!("{game_name}: {e}"),
+                    format!("Edit {}", config_path.display()),
+                )],
+            }
+        })
+        .collect();
+
+    for check in &checks {
+        check.print();
     }
 
-    println!("Refreshing runner cache...");
+    finish_doctor_report(&checks)
+}
 
-    // Discover all runners and cache them
-    let runners_path = dirs.get_runners_path();
-    let proton_manager = ProtonManager::new(runners_path.clone());
-    let dxvk_manager = DxvkManager::new(runners_path);
+pub fn handle_defaults_command(dirs: &CellarDirectories, command: DefaultsCommands) -> Result<()> {
+    match command {
+        DefaultsCommands::Show => show_defaults(dirs),
+        DefaultsCommands::Set { key, value } => set_default_value(dirs, &key, &value),
+    }
+}
 
-    let mut all_runners = Vec::new();
-    all_runners.extend(proton_manager.discover_local_runners().await?);
-    all_runners.extend(dxvk_manager.discover_local_runners().await?);
+fn show_defaults(dirs: &CellarDirectories) -> Result<()> {
+    let path = &dirs.defaults_file;
+    let defaults = CellarDefaults::load(path)?;
 
-    // Save to cache
-    let cache = crate::runners::RunnerCache {
-        runners: all_runners,
-        last_updated: chrono::Utc::now(),
-    };
+    if path.exists() {
+        println!("Defaults file: {}", path.display());
+    } else {
+        println!(
+            "Defaults file: {} (not created yet, using built-in defaults)",
+            path.display()
+        );
+    }
+    println!();
+    print!("{}", toml::to_string_pretty(&defaults)?);
 
-    let cache_content = toml::to_string_pretty(&cache)?;
-    fs::write(&cache_path, cache_content)?;
+    Ok(())
+}
 
-    println!(
-        "Runner cache refreshed with {} runners.",
-        cache.runners.len()
-    );
+fn set_default_value(dirs: &CellarDirectories, key: &str, value: &str) -> Result<()> {
+    let defaults = CellarDefaults::load(&dirs.defaults_file)?;
+    let updated = set_defaults_value(&defaults, key, value)?;
+    updated.save(&dirs.defaults_file)?;
 
+    println!("Set default {key} = '{value}'");
     Ok(())
 }
 
-async fn show_available_runners() -> Result<()> {
-    let dirs = CellarDirectories::new()?;
-    let runners_path = dirs.get_runners_path();
+fn set_game_value(dirs: &CellarDirectories, name: &str, key: &str, value: &str) -> Result<()> {
+    let name = &resolve_game_name(dirs, name)?;
+    let config = load_game_config(dirs, name)?;
+    let updated = set_config_value(&config, key, value)?;
 
-    println!("Fetching available runners...");
+    validate_game_config(&updated)
+        .map_err(|e| anyhow!("Not saving {} = '{}': {}", key, value, e))?;
+    save_game_config(dirs, &updated)?;
 
-    // Get available Proton versions
-    let proton_manager = ProtonManager::new(runners_path.clone());
-    match proton_manager.get_available_versions().await {
-        Ok(versions) => {
-            println!("\nAvailable Proton-GE versions:");
-            for version in versions.iter().take(10) {
-                // Show first 10
-                println!("  {version}");
-            }
-            if versions.len() > 10 {
-                println!("  ... and {} more", versions.len() - 10);
-            }
+    println!("Set {key} = '{value}' for '{}'", updated.game.name);
+    Ok(())
+}
+
+async fn open_game_config(dirs: &CellarDirectories, name: &str, open: bool) -> Result<()> {
+    let name = &resolve_game_name(dirs, name)?;
+    let config_path = dirs.get_game_config_path(name);
+
+    if !config_path.exists() {
+        return Err(anyhow!("Game '{}' not found", name));
+    }
+
+    println!("{}", config_path.display());
+
+    if open {
+        let status = tokio::process::Command::new("xdg-open")
+            .arg(&dirs.configs_dir)
+            .status()
+            .await
+            .map_err(|e| anyhow!("Failed to run xdg-open: {}", e))?;
+
+        if !status.success() {
+            return Err(anyhow!("xdg-open exited with {}", status));
         }
-        Err(e) => println!("Failed to fetch Proton versions: {e}"),
     }
 
-    // Get available DXVK versions
-    let dxvk_manager = DxvkManager::new(runners_path);
-    match dxvk_manager.get_available_versions().await {
+    Ok(())
+}
+
+/// Rebuild a single game's prefix from scratch
+async fn reset_game_prefix(dirs: &CellarDirectories, name: &str) -> Result<()> {
+    let name = &resolve_game_name(dirs, name)?;
+    let config = load_game_config(dirs, name)?;
+    let prefix_path = &config.game.wine_prefix;
+    let prefix_name = prefix_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow!("Invalid wine prefix path: {}", prefix_path.display()))?
+        .to_string();
+
+    // Only prefixes managed by Cellar can be recreated by name
+    if prefix_path != &dirs.get_prefixes_path().join(&prefix_name) {
+        return Err(anyhow!(
+            "Prefix {} is outside the Cellar prefixes directory, refusing to reset it",
+            prefix_path.display()
+        ));
+    }
+
+    let other_games =
+        check_other_games_using_prefix(dirs, prefix_path, &dirs.get_game_file_stem(name))?;
+    if !other_games.is_empty() {
+        return Err(anyhow!(
+            "Prefix '{}' is shared with other games ({}), refusing to reset it",
+            prefix_name,
+            other_games.join(", ")
+        ));
+    }
+
+    if prefix_path.exists() {
+        if !confirm(&format!(
+            "Delete and recreate wine prefix '{prefix_name}'? Saves and settings stored in it will be lost."
+        ))? {
+            println!("Prefix reset cancelled.");
+            return Ok(());
+        }
+
+        remove_prefix(dirs, &prefix_name).await?;
+    }
+
+    create_prefix(dirs, &prefix_name, Some(&config.game.proton_version)).await?;
+
+    if let Some(dxvk_version) = &config.game.dxvk_version {
+        install_dxvk_to_prefix(dirs, dxvk_version, &prefix_name).await?;
+    }
+
+    println!("Successfully reset prefix for: {}", config.game.name);
+    Ok(())
+}
+
+/// Point a game at a new prefix, created fresh or copied from its current one
+async fn move_game_prefix(
+    dirs: &CellarDirectories,
+    name: &str,
+    new_prefix: &str,
+    copy: bool,
+) -> Result<()> {
+    let name = &resolve_game_name(dirs, name)?;
+    let mut config = load_game_config(dirs, name)?;
+    if config.game.native {
+        return Err(anyhow!(
+            "'{}' is a native Linux game and doesn't use a prefix",
+            config.game.name
+        ));
+    }
+
+    // A name, not a path, so the prefix stays in Cellar's prefixes directory
+    if matches!(new_prefix, "" | "." | "..") || new_prefix.contains('/') {
+        return Err(anyhow!("Invalid prefix name '{}'", new_prefix));
+    }
+    let old_path = config.game.wine_prefix.clone();
+    let new_path = dirs.get_prefixes_path().join(new_prefix);
+    if new_path.exists() {
+        return Err(anyhow!("Prefix '{}' already exists", new_prefix));
+    }
+
+    if copy {
+        if !old_path.is_dir() {
+            return Err(anyhow!(
+                "Current prefix {} does not exist, so there's nothing to copy",
+                old_path.display()
+            ));
+        }
+
+        println!(
+            "Copying prefix {} ({}) to '{new_prefix}'...",
+            old_path.display(),
+            format_size(dir_size(&old_path)?)
+        );
+        if let Err(e) = crate::utils::fs::copy_dir_recursive(&old_path, &new_path) {
+            // Don't leave a partial copy behind
+            let _ = fs::remove_dir_all(&new_path);
+            return Err(anyhow!("Failed to copy prefix: {}", e));
+        }
+    } else {
+        create_prefix(dirs, new_prefix, Some(&config.game.proton_version)).await?;
+        if let Some(dxvk_version) = &config.game.dxvk_version {
+            install_dxvk_to_prefix(dirs, dxvk_version, new_prefix).await?;
+        }
+    }
+
+    config.game.wine_prefix = new_path;
+    validate_game_config(&config)?;
+    save_game_config(dirs, &config)?;
+    println!(
+        "Moved '{}' to prefix: {}",
+        config.game.name,
+        config.game.wine_prefix.display()
+    );
+
+    let config_name = dirs.get_game_file_stem(name);
+    if config.desktop.create_shortcut {
+        if let Err(e) = desktop::create_desktop_shortcut(dirs, &config, &config_name).await {
+            eprintln!("Warning: Failed to update desktop shortcut: {}", e);
+        }
+    }
+
+    // The old prefix is only offered for deletion when nothing else needs it
+    let other_games = check_other_games_using_prefix(dirs, &old_path, &config_name)?;
+    let old_name = old_path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    if !other_games.is_empty() {
+        println!(
+            "Note: Old prefix {} is still used by: {}",
+            old_path.display(),
+            other_games.join(", ")
+        );
+    } else if old_path != dirs.get_prefixes_path().join(old_name) {
+        println!(
+            "Old prefix {} is outside the Cellar prefixes directory; remove it yourself if it's no longer needed",
+            old_path.display()
+        );
+    } else if old_path.exists()
+        && confirm(&format!(
+            "Delete the old prefix '{old_name}'? Nothing else uses it."
+        ))?
+    {
+        remove_prefix(dirs, old_name).await?;
+    }
+
+    Ok(())
+}
+
+pub fn show_status(dirs: &CellarDirectories, name: String) -> Result<()> {
+    let name = resolve_game_name(dirs, &name)?;
+    let config = load_game_config(dirs, &name)?;
+
+    // The lock is authoritative; the process scan also finds games started outside Cellar
+    let lock_path = dirs.get_game_lock_path(&config.game.name);
+    let state = match running_pid(&lock_path) {
+        Some(pid) => format!("running (PID {pid})"),
+        None => match find_umu_processes(&config.game.wine_prefix).first() {
+            Some(pid) => format!("running (PID {pid}, umu-run in this game's prefix)"),
+            None => "stopped".to_string(),
+        },
+    };
+    println!("{}: {state}", config.game.name);
+
+    let last_launched = fs::metadata(dirs.get_game_log_path(&config.game.name))
+        .and_then(|metadata| metadata.modified())
+        .map(|modified| {
+            chrono::DateTime::<chrono::Local>::from(modified)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|_| "never".to_string());
+    println!("  Last launched: {last_launched}");
+
+    Ok(())
+}
+
+pub async fn kill_game(dirs: &CellarDirectories, name: String) -> Result<()> {
+    let name = resolve_game_name(dirs, &name)?;
+    let config = load_game_config(dirs, &name)?;
+    let timeout = std::time::Duration::from_secs(config.wine_config.wineserver_kill_timeout.into());
+
+    // Games launched by Cellar lead their own process group; otherwise fall back
+    // to umu-run processes in the game's prefix
+    let pids = match running_pid(&dirs.get_game_lock_path(&config.game.name)) {
+        Some(pid) => vec![pid],
+        None => find_umu_processes(&config.game.wine_prefix),
+    };
+
+    if pids.is_empty() {
+        println!("'{}' is not running", config.game.name);
+    }
+
+    for pid in pids {
+        println!("Sending SIGTERM to '{}' (PID {pid})...", config.game.name);
+        if terminate(pid, timeout).await? {
+            println!(
+                "PID {pid} was still running after {}s, sent SIGKILL",
+                timeout.as_secs()
+            );
+        } else {
+            println!("PID {pid} exited");
+        }
+    }
+
+    if config.game.native {
+        return Ok(());
+    }
+
+    // Wine processes outlive the launcher, so stop the prefix's wineserver too
+    let wineserver = find_game_runner(&dirs.get_runners_path(), &config.game.proton_version)
+        .await?
+        .map(|r| r.wine_bin_dir().join("wineserver"))
+        .filter(|path| path.exists())
+        .unwrap_or_else(|| PathBuf::from("wineserver"));
+
+    match tokio::process::Command::new(&wineserver)
+        .arg("-k")
+        .env("WINEPREFIX", &config.game.wine_prefix)
+        .status()
+        .await
+    {
+        Ok(status) if status.success() => println!(
+            "Stopped wine processes in {}",
+            config.game.wine_prefix.display()
+        ),
+        // wineserver -k fails when nothing is running in the prefix
+        Ok(_) => println!(
+            "No wine processes left in {}",
+            config.game.wine_prefix.display()
+        ),
+        Err(e) => eprintln!("Warning: Failed to run {}: {e}", wineserver.display()),
+    }
+
+    Ok(())
+}
+
+pub fn show_game_logs(dirs: &CellarDirectories, name: String) -> Result<()> {
+    // Logs are written under the stored name, which may differ from what was typed
+    let name = resolve_game_name(dirs, &name)?;
+    let name = read_game_config(dirs, &name)
+        .map(|(config, _)| config.game.name)
+        .unwrap_or(name);
+    let log_path = dirs.get_game_log_path(&name);
+
+    if !log_path.exists() {
+        return Err(anyhow!(
+            "No log found for '{}'. Launch the game first to capture its output.",
+            name
+        ));
+    }
+
+    let contents = fs::read(&log_path)?;
+    if contents.is_empty() {
+        println!("The last launch of '{name}' produced no output.");
+    } else {
+        print!("{}", String::from_utf8_lossy(&contents));
+    }
+
+    Ok(())
+}
+
+pub fn show_game_info(dirs: &CellarDirectories, name: String, json: bool) -> Result<()> {
+    let name = resolve_game_name(dirs, &name)?;
+    let mut config = load_game_config(dirs, &name)?;
+
+    if json {
+        // Report the settings in effect, including those the preset fills in
+        config.gamescope.preset_overrides = PRESET_FIELDS.iter().map(|f| f.to_string()).collect();
+        println!("{}", serde_json::to_string_pretty(&config)?);
+        return Ok(());
+    }
+
+    println!("Game Information for: {}", config.game.name);
+    println!("  Executable: {}", config.game.executable.display());
+    if config.game.native {
+        println!("  Native Linux game");
+    } else {
+        println!("  Wine Prefix: {}", config.game.wine_prefix.display());
+        println!("  Proton Version: {}", config.game.proton_version);
+    }
+
+    if let Some(dxvk_version) = &config.game.dxvk_version {
+        println!("  DXVK Version: {dxvk_version}");
+    }
+    if let Some(game_id) = &config.game.umu_game_id {
+        println!("  umu Game ID: {game_id}");
+    }
+    if let Some(installed) = installed_dxvk_version(&config.game.wine_prefix) {
+        println!("  DXVK in Prefix: {installed}");
+    }
+
+    match config.stats.last_played {
+        Some(last_played) => println!("  Last Played: {}", format_last_played(last_played)),
+        None => println!("  Last Played: never"),
+    }
+    println!("  Play Count: {}", config.stats.play_count);
+    println!(
+        "  Total Playtime: {}",
+        format_playtime(config.stats.total_playtime_secs)
+    );
+
+    println!("\nWine Configuration:");
+    println!("  esync: {}", config.wine_config.esync);
+    println!("  fsync: {}", config.wine_config.fsync);
+    println!("  dxvk: {}", config.wine_config.dxvk);
+    println!("  dxvk_async: {}", config.wine_config.dxvk_async);
+    if config.wine_config.proton_fsr {
+        match config.wine_config.proton_fsr_strength {
+            Some(strength) => println!("  proton_fsr: true (strength {strength})"),
+            None => println!("  proton_fsr: true"),
+        }
+    }
+
+    if config.gamescope.enabled {
+        println!("\nGamescope Configuration:");
+        if let Some(preset) = &config.gamescope.preset {
+            println!("  Preset: {preset}");
+        }
+        println!(
+            "  Game Resolution: {}x{}",
+            config.gamescope.width, config.gamescope.height
+        );
+        println!(
+            "  Output Resolution: {}x{}",
+            config.gamescope.output_width, config.gamescope.output_height
+        );
+        println!("  Refresh Rate: {}Hz", config.gamescope.refresh_rate);
+        println!("  Upscaling: {}", config.gamescope.upscaling);
+    }
+
+    Ok(())
+}
+
+async fn create_basic_game_config(
+    name: &str,
+    exe_path: PathBuf,
+    dirs: &CellarDirectories,
+    proton_version: Option<&str>,
+    prefix_name: Option<&str>,
+) -> Result<GameConfig> {
+    let (_, wine_prefix, proton_version) =
+        prepare_game_prefix(name, dirs, proton_version, prefix_name).await?;
+
+    let mut config = new_game_config(name, exe_path, wine_prefix, proton_version);
+    CellarDefaults::load(&dirs.defaults_file)?.apply(&mut config);
+
+    validate_game_config(&config)?;
+    Ok(config)
+}
+
+/// Config for a native Linux game, which needs no prefix or Proton version
+fn create_native_game_config(
+    name: &str,
+    exe_path: PathBuf,
+    dirs: &CellarDirectories,
+) -> Result<GameConfig> {
+    if name.trim().is_empty() {
+        return Err(anyhow!("Game name cannot be empty"));
+    }
+
+    let mut config = new_game_config(name, exe_path, PathBuf::new(), String::new());
+    CellarDefaults::load(&dirs.defaults_file)?.apply(&mut config);
+    config.game.native = true;
+    config.desktop.comment = "Linux game via Cellar".to_string();
+    config.desktop.keywords = vec!["game".to_string()];
+
+    validate_game_config(&config)?;
+    Ok(config)
+}
+
+fn new_game_config(
+    name: &str,
+    exe_path: PathBuf,
+    wine_prefix: PathBuf,
+    proton_version: String,
+) -> GameConfig {
+    GameConfig {
+        version: CURRENT_CONFIG_VERSION,
+        game: GameInfo {
+            name: name.to_string(),
+            executable: exe_path,
+            wine_prefix,
+            proton_version,
+            dxvk_version: None,
+            umu_game_id: None,
+            native: false,
+        },
+        launch: LaunchConfig::default(),
+        wine_config: WineConfig::default(),
+        dxvk: Default::default(),
+        mangohud: Default::default(),
+        gamescope: GamescopeConfig::default(),
+        desktop: DesktopConfig::default(),
+        stats: GameStats::default(),
+        installation: None,
+    }
+}
+
+/// Directory a game from the given install layout should run in, falling back to
+/// the executable's own directory when the layout isn't recognized
+fn detect_install_root(exe: &Path, layout: InstallLayout) -> PathBuf {
+    let (root, label) = match layout {
+        InstallLayout::Gog => (find_gog_root(exe), "GOG"),
+        InstallLayout::Steam => (find_steam_root(exe), "Steam"),
+    };
+
+    root.unwrap_or_else(|| {
+        eprintln!(
+            "Warning: {} doesn't look like a {label} install, using the executable's directory",
+            exe.display()
+        );
+        exe.parent().unwrap_or(exe).to_path_buf()
+    })
+}
+
+/// Check that a prefix exists, so a game can be attached to it without creating a new one
+fn require_existing_prefix(dirs: &CellarDirectories, prefix_name: &str) -> Result<()> {
+    let prefix_path = dirs.get_prefixes_path().join(prefix_name);
+    if !prefix_path.is_dir() {
+        return Err(anyhow!(
+            "Prefix '{}' not found at {}. --no-prefix only uses an existing prefix; see 'cellar prefix list'",
+            prefix_name,
+            prefix_path.display()
+        ));
+    }
+
+    if !prefix_path.join("drive_c/windows/system32").exists() {
+        eprintln!(
+            "Warning: Prefix '{}' has no drive_c/windows/system32 and may not be initialized",
+            prefix_name
+        );
+    }
+
+    Ok(())
+}
+
+/// Resolve the Proton version for a new game and create its prefix if needed
+///
+/// Returns the prefix name, prefix path and full Proton version.
+async fn prepare_game_prefix(
+    name: &str,
+    dirs: &CellarDirectories,
+    proton_version: Option<&str>,
+    prefix_name: Option<&str>,
+) -> Result<(String, PathBuf, String)> {
+    // Determine prefix name: use provided or default to game name
+    let prefix_name = match prefix_name {
+        Some(provided_prefix) => provided_prefix.to_string(),
+        None => sanitize_filename(name), // Only sanitize when using game name as prefix
+    };
+    let wine_prefix = dirs.get_prefixes_path().join(&prefix_name);
+
+    // Determine Proton version to use BEFORE creating prefix
+    let proton_version = match proton_version {
+        Some(version) => {
+            println!("Using specified Proton version: {version}");
+
+            // Check if the specified version is available locally
+            let proton_manager = ProtonManager::new(dirs.get_runners_path());
+
+            // Find the matching runner and get its full version name
+            if let Some(matched_runner) =
+                find_game_runner(&dirs.get_runners_path(), version).await?
+            {
+                // Wine runners share version numbers with Proton builds, so keep their full name
+                match matched_runner.runner_type {
+                    RunnerType::Wine => matched_runner.name,
+                    _ => matched_runner.version,
+                }
+            } else {
+                println!("Proton version '{version}' not found locally.");
+
+                // Check if version is available for download
+                match check_proton_version_available(&proton_manager, version).await {
+                    Ok(download_version) => {
+                        // Ask user for permission to download
+                        if confirm(&format!("Download Proton version '{version}'?"))? {
+                            download_and_install_proton(dirs, &download_version).await?;
+                            println!("Successfully installed Proton version: {version}");
+
+                            // After installation, find the full version name
+                            let updated_runners = proton_manager.discover_local_runners().await?;
+                            if let Some(installed_runner) =
+                                updated_runners.iter().find(|r| r.matches_version(version))
+                            {
+                                installed_runner.version.clone()
+                            } else {
+                                download_version
+                            }
+                        } else {
+                            return Err(anyhow!("Proton version '{}' is required but not available locally. Operation cancelled.", version));
+                        }
+                    }
+                    Err(_) => {
+                        return Err(anyhow!(
+                            "Proton version '{}' not found locally and not available for download.\nAvailable versions can be seen with: cellar runners available",
+                            version
+                        ));
+                    }
+                }
+            }
+        }
+        None => {
+            println!("No Proton version specified, finding latest available...");
+            let latest_version = get_latest_proton_version(dirs).await?;
+            println!("Using latest available Proton version: {latest_version}");
+            latest_version
+        }
+    };
+
+    // Check if prefix exists, if not create it
+    if !wine_prefix.exists() {
+        create_prefix(dirs, &prefix_name, Some(&proton_version)).await?;
+    } else {
+        println!("Using existing prefix: {prefix_name}");
+    }
+
+    Ok((prefix_name, wine_prefix, proton_version))
+}
+
+/// Get the latest available Proton version from cache, or discover if cache is missing/old
+async fn get_latest_proton_version(dirs: &CellarDirectories) -> Result<String> {
+    let cache_path = dirs.get_cache_path().join("runners.toml");
+
+    // Try to load from cache first
+    let mut proton_runners = Vec::new();
+
+    if cache_path.exists() {
+        if let Ok(cache_content) = fs::read_to_string(&cache_path) {
+            if let Ok(cache) = toml::from_str::<RunnerCache>(&cache_content) {
+                // Check if cache is recent (less than 1 hour old)
+                let cache_age = chrono::Utc::now().signed_duration_since(cache.last_updated);
+                if cache_age.num_hours() < 1 {
+                    proton_runners = cache
+                        .runners
+                        .into_iter()
+                        .filter(|r| matches!(r.runner_type, RunnerType::Proton))
+                        .collect();
+                }
+            }
+        }
+    }
+
+    // If cache is empty or old, discover live
+    if proton_runners.is_empty() {
+        let runners_path = dirs.get_runners_path();
+        let proton_manager = ProtonManager::new(runners_path);
+        proton_runners = proton_manager.discover_local_runners().await?;
+    }
+
+    if proton_runners.is_empty() {
+        return Err(anyhow!(
+            "No Proton versions found. Please install a Proton version first using:\n  cellar runners install proton <version>\n\nTo see available versions for download, use:\n  cellar runners available"
+        ));
+    }
+
+    // Sort newest first (e.g., "GE-Proton10-1" before "GE-Proton9-27")
+    proton_runners.sort_by_key(|r| std::cmp::Reverse(r.version_tuple()));
+
+    Ok(proton_runners[0].version.clone())
+}
+
+/// Check if a Proton version is available for download
+async fn check_proton_version_available(
+    proton_manager: &ProtonManager,
+    version: &str,
+) -> Result<String> {
+    let available_versions = proton_manager.get_available_versions().await?;
+
+    // Try exact match first
+    if available_versions.iter().any(|v| v == version) {
+        return Ok(version.to_string());
+    }
+
+    // Try partial match (e.g., user provides "9-1" for "GE-Proton9-1")
+    if let Some(found) = available_versions
+        .iter()
+        .find(|v| v.contains(version) || v.ends_with(&format!("-{version}")))
+    {
+        return Ok(found.clone());
+    }
+
+    Err(anyhow!(
+        "Version '{}' not found in available releases",
+        version
+    ))
+}
+
+/// Download and install a Proton version
+async fn download_and_install_proton(dirs: &CellarDirectories, version: &str) -> Result<()> {
+    println!("Downloading Proton version: {version}");
+
+    let mut proton_manager = ProtonManager::new(dirs.get_runners_path());
+    proton_manager
+        .base_runner
+        .set_download_dir(dirs.get_downloads_path());
+
+    // Extract the actual version number from the full version string
+    // e.g., "GE-Proton10-10" -> "10-10"
+    let version_number = if version.starts_with("GE-Proton") {
+        version.strip_prefix("GE-Proton").unwrap_or(version)
+    } else {
+        version
+    };
+
+    let download_path = proton_manager
+        .download_runner("proton-ge", version_number)
+        .await?;
+    println!("Installing Proton version: {version}");
+
+    proton_manager
+        .install_runner(&download_path, std::path::Path::new(""))
+        .await?;
+
+    // Refresh runner cache after installation
+    refresh_runners_cache(dirs).await?;
+
+    Ok(())
+}
+
+/// Refresh runner cache without printing messages
+async fn refresh_runners_cache(dirs: &CellarDirectories) -> Result<RunnerCache> {
+    // Discover all runners and cache them
+    let mut all_runners = discover_all_runners(dirs).await?;
+    load_runner_pins(dirs).apply(&mut all_runners);
+
+    // Save to cache
+    let cache = crate::runners::RunnerCache {
+        runners: all_runners,
+        last_updated: chrono::Utc::now(),
+    };
+
+    save_runner_cache(dirs, &cache)?;
+
+    Ok(cache)
+}
+
+/// Write a game config, to a suffixed file if its name collides with another game's
+fn save_game_config(dirs: &CellarDirectories, config: &GameConfig) -> Result<()> {
+    let config_path = dirs.game_config_path_for_save(&config.game.name);
+    let toml_content =
+        toml::to_string_pretty(config).map_err(|e| anyhow!("Failed to serialize config: {}", e))?;
+
+    fs::write(&config_path, toml_content)
+        .map_err(|e| anyhow!("Failed to write config file: {}", e))?;
+
+    Ok(())
+}
+
+/// Resolve a game given on the command line to a name its config can be found by
+///
+/// Without an exact match, `name` is matched case-insensitively against part of every
+/// game's name, so `portal` finds "Portal 2". A unique match resolves to that game's
+/// config name; several matches are an error listing them. If nothing matches, `name`
+/// is returned unchanged and the caller reports the game as not found.
+fn resolve_game_name(dirs: &CellarDirectories, name: &str) -> Result<String> {
+    if dirs.get_game_config_path(name).exists() {
+        return Ok(name.to_string());
+    }
+
+    let query = name.to_lowercase();
+    let matches: Vec<(String, String)> = dirs
+        .list_game_configs()?
+        .into_iter()
+        .filter_map(|config_name| {
+            let (config, _) = read_game_config(dirs, &config_name).ok()?;
+            config
+                .game
+                .name
+                .to_lowercase()
+                .contains(&query)
+                .then_some((config_name, config.game.name))
+        })
+        .collect();
+
+    match matches.as_slice() {
+        [] => Ok(name.to_string()),
+        [(config_name, game_name)] => {
+            // On stderr, so output meant for other programs (like `info --json`) stays parseable
+            eprintln!("Using '{game_name}' for '{name}'");
+            Ok(config_name.clone())
+        }
+        _ => {
+            let candidates: Vec<&str> = matches.iter().map(|(_, game)| game.as_str()).collect();
+            Err(anyhow!(
+                "'{}' matches several games: {}. Use the full name",
+                name,
+                candidates.join(", ")
+            ))
+        }
+    }
+}
+
+fn load_game_config(dirs: &CellarDirectories, name: &str) -> Result<GameConfig> {
+    let (config, unknown_keys) = read_game_config(dirs, name)?;
+    for key in unknown_keys {
+        eprintln!(
+            "Warning: unknown key '{key}' in {} is ignored",
+            dirs.get_game_config_path(name).display()
+        );
+    }
+    Ok(config)
+}
+
+/// Load a game's config along with any keys Cellar doesn't recognize, without
+/// warning about them; for scans over every game and for `config validate`
+fn read_game_config(dirs: &CellarDirectories, name: &str) -> Result<(GameConfig, Vec<String>)> {
+    let config_path = dirs.get_game_config_path(name);
+
+    if !config_path.exists() {
+        return Err(anyhow!("Game '{}' not found", name));
+    }
+
+    let content = fs::read_to_string(&config_path)
+        .map_err(|e| anyhow!("Failed to read config file: {}", e))?;
+
+    parse_game_config_with_unknown_keys(&content)
+        .map_err(|e| anyhow!("Failed to parse config file: {}", e))
+}
+
+/// Open file limit Proton needs for esync to work reliably
+const ESYNC_MIN_FILE_LIMIT: u64 = 524288;
+
+/// First kernel with `futex_waitv`, which fsync needs
+const FSYNC_MIN_KERNEL: (u32, u32) = (5, 16);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// Outcome of a single `cellar doctor` check
+struct DoctorCheck {
+    status: CheckStatus,
+    message: String,
+    fix: Option<String>,
+}
+
+impl DoctorCheck {
+    fn pass(message: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Pass,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(message: impl Into<String>, fix: Option<String>) -> Self {
+        Self {
+            status: CheckStatus::Warn,
+            message: message.into(),
+            fix,
+        }
+    }
+
+    fn fail(message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Fail,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn print(&self) {
+        let symbol = match self.status {
+            CheckStatus::Pass => "✓",
+            CheckStatus::Warn => "⚠",
+            CheckStatus::Fail => "✗",
+        };
+        println!("  {symbol} {}", self.message);
+        if let Some(fix) = &self.fix {
+            println!("      Fix: {fix}");
+        }
+    }
+}
+
+pub async fn run_doctor(
+    dirs: &CellarDirectories,
+    prefix: Option<String>,
+    game: Option<String>,
+) -> Result<()> {
+    let checks = if let Some(name) = game {
+        let name = resolve_game_name(dirs, &name)?;
+        let config = load_game_config(dirs, &name)?;
+        doctor_game(dirs, &name, &config).await
+    } else if let Some(name) = prefix {
+        doctor_prefix(dirs, &name).await?
+    } else {
+        doctor_all_games(dirs).await?
+    };
+
+    finish_doctor_report(&checks)
+}
+
+/// Check that every configured game resolves its Proton, prefix and executable
+async fn doctor_all_games(dirs: &CellarDirectories) -> Result<Vec<DoctorCheck>> {
+    let games = dirs.list_game_configs()?;
+    if games.is_empty() {
+        println!("No games configured.");
+    }
+
+    let mut checks = Vec::new();
+    for (i, game_name) in games.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+
+        let game_checks = match load_game_config(dirs, game_name) {
+            Ok(config) => {
+                println!("{}", config.game.name);
+                basic_game_checks(dirs, &config).await
+            }
+            Err(e) => {
+                println!("{game_name}");
+                vec![DoctorCheck::fail(
+                    format!("{e}"),
+                    format!("Edit {}", dirs.get_game_config_path(game_name).display()),
+                )]
+            }
+        };
+
+        for check in &game_checks {
+            check.print();
+        }
+        checks.extend(game_checks);
+    }
+
+    println!("\nExternal tools");
+    let tool_checks = external_tool_checks().await;
+    for check in &tool_checks {
+        check.print();
+    }
+    checks.extend(tool_checks);
+
+    Ok(checks)
+}
+
+/// Whether each program Cellar may run is on PATH
+async fn external_tool_checks() -> Vec<DoctorCheck> {
+    let mut checks = Vec::new();
+    for tool in EXTERNAL_TOOLS {
+        checks.push(if command_available(tool.program).await {
+            DoctorCheck::pass(format!("{} is installed", tool.program))
+        } else if tool.required {
+            DoctorCheck::fail(
+                format!(
+                    "{} not found in PATH, needed for {}",
+                    tool.program, tool.purpose
+                ),
+                format!("Install umu-launcher ({UMU_LAUNCHER_URL})"),
+            )
+        } else {
+            DoctorCheck::warn(
+                format!(
+                    "{} not found in PATH, needed for {}",
+                    tool.program, tool.purpose
+                ),
+                None,
+            )
+        });
+    }
+    checks
+}
+
+async fn doctor_prefix(dirs: &CellarDirectories, name: &str) -> Result<Vec<DoctorCheck>> {
+    let prefix_path = dirs.get_prefixes_path().join(name);
+    if !prefix_path.exists() {
+        return Err(anyhow!("Prefix '{}' not found", name));
+    }
+
+    let games = check_other_games_using_prefix(dirs, &prefix_path, "")?;
+    let mut checks = Vec::new();
+
+    if games.is_empty() {
+        println!("Doctor report for prefix '{name}'");
+        println!("  (no games use this prefix)");
+        for check in prefix_checks(dirs, &prefix_path, None).await {
+            check.print();
+            checks.push(check);
+        }
+    }
+
+    for (i, game_name) in games.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let config = load_game_config(dirs, game_name)?;
+        checks.extend(doctor_game(dirs, game_name, &config).await);
+    }
+
+    Ok(checks)
+}
+
+/// Run and print every check for one game, followed by the tail of its last log
+async fn doctor_game(
+    dirs: &CellarDirectories,
+    config_name: &str,
+    config: &GameConfig,
+) -> Vec<DoctorCheck> {
+    println!("Doctor report for '{}'", config.game.name);
+
+    let config_path = dirs.get_game_config_path(config_name);
+    let mut checks = Vec::new();
+
+    checks.push(match validate_game_config(config) {
+        Ok(()) => DoctorCheck::pass("Config is valid"),
+        Err(e) => DoctorCheck::fail(
+            format!("Config is invalid: {e}"),
+            format!("Edit {}", config_path.display()),
+        ),
+    });
+
+    checks.extend(basic_game_checks(dirs, config).await);
+
+    if !config.game.native {
+        checks.extend(wine_game_checks(dirs, config).await);
+    }
+    checks.extend(wrapper_tool_checks(config).await);
+
+    for check in &checks {
+        check.print();
+    }
+
+    print_log_tail(dirs, &config.game.name, 10);
+    checks
+}
+
+/// DXVK, prefix and sync checks, which don't apply to native games
+async fn wine_game_checks(dirs: &CellarDirectories, config: &GameConfig) -> Vec<DoctorCheck> {
+    let mut checks = Vec::new();
+
+    if let Some(dxvk_version) = &config.game.dxvk_version {
+        let dxvk_manager = DxvkManager::new(dirs.get_runners_path());
+        let installed = dxvk_manager
+            .discover_local_runners()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .any(|r| r.matches_version(dxvk_version));
+        checks.push(if installed {
+            DoctorCheck::pass(format!("DXVK {dxvk_version} is installed"))
+        } else {
+            DoctorCheck::fail(
+                format!("DXVK {dxvk_version} is not installed"),
+                format!("cellar runners install dxvk {dxvk_version}"),
+            )
+        });
+    }
+
+    if config
+        .game
+        .wine_prefix
+        .join("drive_c/windows/system32")
+        .exists()
+    {
+        checks.extend(prefix_checks(dirs, &config.game.wine_prefix, Some(config)).await);
+    }
+
+    checks.extend(sync_checks(config));
+    checks
+}
+
+/// Checks that decide whether a game can be launched at all
+async fn basic_game_checks(dirs: &CellarDirectories, config: &GameConfig) -> Vec<DoctorCheck> {
+    let mut checks = Vec::new();
+    let config_path = dirs.get_game_config_path(&config.game.name);
+
+    checks.push(if config.game.executable.exists() {
+        DoctorCheck::pass(format!(
+            "Executable found: {}",
+            config.game.executable.display()
+        ))
+    } else {
+        DoctorCheck::fail(
+            format!("Executable not found: {}", config.game.executable.display()),
+            format!("Update game.executable in {}", config_path.display()),
+        )
+    });
+
+    if config.game.native {
+        checks.push(DoctorCheck::pass(
+            "Native Linux game, no Proton or prefix needed",
+        ));
+        return checks;
+    }
+
+    let proton_version = &config.game.proton_version;
+    checks.push(
+        match find_game_runner(&dirs.get_runners_path(), proton_version)
+            .await
+            .unwrap_or_default()
+        {
+            Some(runner) => DoctorCheck::pass(format!(
+                "Proton '{proton_version}' resolves to {}",
+                runner.name
+            )),
+            None => DoctorCheck::fail(
+                format!("Proton '{proton_version}' is not installed"),
+                format!("cellar runners install proton {proton_version}"),
+            ),
+        },
+    );
+
+    let prefix_path = &config.game.wine_prefix;
+    checks.push(if prefix_path.join("drive_c/windows/system32").exists() {
+        DoctorCheck::pass(format!("Prefix is initialized: {}", prefix_path.display()))
+    } else if prefix_path.exists() {
+        DoctorCheck::fail(
+            format!(
+                "Prefix is incomplete (no system32): {}",
+                prefix_path.display()
+            ),
+            format!("cellar games reset-prefix \"{}\"", config.game.name),
+        )
+    } else {
+        DoctorCheck::fail(
+            format!("Prefix not found: {}", prefix_path.display()),
+            format!("cellar games reset-prefix \"{}\"", config.game.name),
+        )
+    });
+
+    checks
+}
+
+/// Architecture, creating Proton and DXVK state of an initialized prefix
+async fn prefix_checks(
+    dirs: &CellarDirectories,
+    prefix_path: &Path,
+    config: Option<&GameConfig>,
+) -> Vec<DoctorCheck> {
+    let mut checks = Vec::new();
+
+    // Cellar always launches with WINEARCH=win64
+    match detect_prefix_arch(prefix_path) {
+        Some(PrefixArch::Win32) => checks.push(DoctorCheck::fail(
+            "Prefix is 32-bit, but Cellar launches games as win64",
+            "Recreate the prefix as a 64-bit prefix",
+        )),
+        Some(PrefixArch::Win64) => checks.push(DoctorCheck::pass("Prefix architecture is win64")),
+        Some(PrefixArch::Wow64) => checks.push(DoctorCheck::pass(
+            "Prefix architecture is win64 (new-style WoW64, no syswow64)",
+        )),
+        None => checks.push(DoctorCheck::warn(
+            "Prefix has no system.reg, so its architecture is unknown",
+            Some("Launch the game once to finish initializing the prefix".to_string()),
+        )),
+    }
+
+    match fs::read_to_string(prefix_path.join("version")) {
+        Ok(version) => {
+            let version = version.trim().to_string();
+            match detect_prefix_proton(dirs, prefix_path).await {
+                Ok(Some(runner)) => checks.push(DoctorCheck::pass(format!(
+                    "Prefix was created by {}",
+                    runner.name
+                ))),
+                _ => checks.push(DoctorCheck::warn(
+                    format!("Prefix was created by '{version}', which is not installed"),
+                    None,
+                )),
+            }
+        }
+        Err(_) => checks.push(DoctorCheck::warn(
+            "No Proton version file in prefix; it may not be a Proton prefix",
+            None,
+        )),
+    }
+
+    let dxvk_expected = config.map(|c| c.wine_config.dxvk).unwrap_or(true);
+    if dxvk_expected {
+        let system32 = prefix_path.join("drive_c/windows/system32");
+        let missing: Vec<&str> = ["d3d11.dll", "dxgi.dll"]
+            .into_iter()
+            .filter(|dll| !is_dxvk_dll(&system32.join(dll)))
+            .collect();
+
+        if missing.is_empty() {
+            checks.push(DoctorCheck::pass("DXVK DLLs are installed in the prefix"));
+        } else {
+            let prefix_name = prefix_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let fix = config
+                .and_then(|c| c.game.dxvk_version.as_ref())
+                .map(|v| format!("cellar runners install-dxvk {v} {prefix_name}"));
+            checks.push(DoctorCheck::warn(
+                format!(
+                    "DXVK DLLs missing from system32: {} (Proton installs its bundled DXVK at launch)",
+                    missing.join(", ")
+                ),
+                fix,
+            ));
+        }
+    }
+
+    checks
+}
+
+/// Whether esync and fsync will actually take effect on this system
+fn sync_checks(config: &GameConfig) -> Vec<DoctorCheck> {
+    let mut checks = Vec::new();
+
+    if config.wine_config.esync {
+        let limit = fs::read_to_string("/proc/self/limits")
+            .ok()
+            .and_then(|limits| parse_open_file_limit(&limits));
+        match limit {
+            Some(limit) if limit >= ESYNC_MIN_FILE_LIMIT => {
+                checks.push(DoctorCheck::pass("esync is effective"))
+            }
+            Some(limit) => checks.push(DoctorCheck::warn(
+                format!(
+                    "esync may run out of file descriptors: the open file limit is {limit} (needs {ESYNC_MIN_FILE_LIMIT})"
+                ),
+                Some(format!(
+                    "Raise the hard limit, e.g. DefaultLimitNOFILE={ESYNC_MIN_FILE_LIMIT} in /etc/systemd/system.conf"
+                )),
+            )),
+            None => {}
+        }
+    }
+
+    if config.wine_config.fsync {
+        let kernel = fs::read_to_string("/proc/sys/kernel/osrelease")
+            .ok()
+            .and_then(|release| parse_kernel_version(&release));
+        match kernel {
+            Some(version) if version >= FSYNC_MIN_KERNEL => {
+                checks.push(DoctorCheck::pass("fsync is effective"))
+            }
+            Some((major, minor)) => checks.push(DoctorCheck::warn(
+                format!(
+                    "fsync needs Linux {}.{}+ but this is {major}.{minor}; Proton will fall back to esync",
+                    FSYNC_MIN_KERNEL.0, FSYNC_MIN_KERNEL.1
+                ),
+                None,
+            )),
+            None => {}
+        }
+    }
+
+    checks
+}
+
+/// Hard "Max open files" limit from /proc/<pid>/limits
+fn parse_open_file_limit(limits: &str) -> Option<u64> {
+    let line = limits.lines().find(|l| l.starts_with("Max open files"))?;
+    let hard = line
+        .trim_start_matches("Max open files")
+        .split_whitespace()
+        .nth(1)?;
+    if hard == "unlimited" {
+        Some(u64::MAX)
+    } else {
+        hard.parse().ok()
+    }
+}
+
+/// (major, minor) from a kernel release string like "6.8.0-45-generic"
+fn parse_kernel_version(release: &str) -> Option<(u32, u32)> {
+    let mut parts = release.trim().split(['.', '-']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// The launcher and any wrappers the game's config enables must be on PATH
+async fn wrapper_tool_checks(config: &GameConfig) -> Vec<DoctorCheck> {
+    let mut tools = Vec::new();
+    if !config.game.native {
+        tools.push(("umu-run", "umu-launcher", None));
+    }
+    if config.launch.gamemode {
+        tools.push(("gamemoderun", "gamemode", Some("launch.gamemode")));
+    }
+    if config.launch.mangohud {
+        tools.push(("mangohud", "MangoHud", Some("launch.mangohud")));
+    }
+    if config.gamescope.enabled {
+        tools.push(("gamescope", "gamescope", Some("gamescope.enabled")));
+    }
+
+    let mut checks = Vec::new();
+    for (program, package, option) in tools {
+        checks.push(if command_available(program).await {
+            DoctorCheck::pass(format!("{program} is installed"))
+        } else {
+            let fix = match option {
+                Some(option) => format!("Install {package} or set {option} = false"),
+                None => format!("Install {package} ({UMU_LAUNCHER_URL})"),
+            };
+            DoctorCheck::fail(format!("{program} not found in PATH"), fix)
+        });
+    }
+
+    checks
+}
+
+fn print_log_tail(dirs: &CellarDirectories, game_name: &str, lines: usize) {
+    let log_path = dirs.get_game_log_path(game_name);
+    let Ok(contents) = fs::read(&log_path) else {
+        println!("\nNo launch log yet.");
+        return;
+    };
+
+    let contents = String::from_utf8_lossy(&contents);
+    let all_lines: Vec<&str> = contents.lines().collect();
+    println!("\nLast launch output ({}):", log_path.display());
+    if all_lines.is_empty() {
+        println!("  (empty)");
+    }
+    for line in &all_lines[all_lines.len().saturating_sub(lines)..] {
+        println!("  {line}");
+    }
+}
+
+/// Print the totals, failing if any check failed so scripts can rely on the exit status
+fn finish_doctor_report(checks: &[DoctorCheck]) -> Result<()> {
+    let failures = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Fail)
+        .count();
+    let warnings = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Warn)
+        .count();
+
+    println!();
+    if failures > 0 {
+        return Err(anyhow!(
+            "{} problem(s), {} warning(s) found",
+            failures,
+            warnings
+        ));
+    }
+
+    if warnings == 0 {
+        println!("No problems found.");
+    } else {
+        println!("No problems found, {warnings} warning(s).");
+    }
+
+    Ok(())
+}
+
+// Runner management functions
+pub async fn handle_runners_command(
+    dirs: &CellarDirectories,
+    command: RunnerCommands,
+) -> Result<()> {
+    match command {
+        RunnerCommands::List { json: false } => list_runners(dirs).await,
+        RunnerCommands::List { json: true } => list_runners_json(dirs).await,
+        RunnerCommands::Refresh => refresh_runners(dirs).await,
+        RunnerCommands::Available => show_available_runners(dirs).await,
+        RunnerCommands::Install {
+            runner_type,
+            version,
+            allow_large,
+            with_dxvk,
+        } => {
+            if with_dxvk && runner_type.eq_ignore_ascii_case("dxvk") {
+                return Err(anyhow!(
+                    "--with-dxvk only applies when installing a proton or wine runner"
+                ));
+            }
+
+            let installed = install_runner(dirs, &runner_type, &version, allow_large).await?;
+            if with_dxvk {
+                let dxvk_version = install_latest_dxvk(dirs, allow_large).await?;
+                println!("\nReady: {runner_type} {installed} and DXVK {dxvk_version}");
+            }
+            Ok(())
+        }
+        RunnerCommands::Info {
+            runner_type,
+            version,
+        } => show_runner_info(dirs, &runner_type, &version).await,
+        RunnerCommands::InstallDxvk { version, prefix } => {
+            install_dxvk_to_prefix(dirs, &version, &prefix).await
+        }
+        RunnerCommands::UninstallDxvk { prefix } => uninstall_dxvk_from_prefix(dirs, &prefix).await,
+        RunnerCommands::Remove {
+            runner_type,
+            version,
+            all: _,
+        } => match version {
+            Some(version) => remove_runner(dirs, &runner_type, &version).await,
+            None => remove_all_runners(dirs, &runner_type).await,
+        },
+        RunnerCommands::Prune { keep } => prune_proton_runners(dirs, keep).await,
+        RunnerCommands::Pin {
+            runner_type,
+            version,
+        } => set_runner_pinned(dirs, &runner_type, &version, true).await,
+        RunnerCommands::Unpin {
+            runner_type,
+            version,
+        } => set_runner_pinned(dirs, &runner_type, &version, false).await,
+        RunnerCommands::Verify => verify_runners(dirs),
+        RunnerCommands::CleanupCache => cleanup_runner_cache(dirs),
+    }
+}
+
+/// Check every Cellar-installed Proton and DXVK runner for the files it needs,
+/// failing if any install is incomplete
+fn verify_runners(dirs: &CellarDirectories) -> Result<()> {
+    let runners_path = dirs.get_runners_path();
+    let mut checks = Vec::new();
+
+    for (runner_type, label) in [("proton", "Proton"), ("dxvk", "DXVK")] {
+        // Incomplete installs aren't discovered as runners, so scan the directories
+        let mut installs: Vec<PathBuf> = fs::read_dir(runners_path.join(runner_type))
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_dir())
+                    .collect()
+            })
+            .unwrap_or_default();
+        installs.sort();
+
+        for path in installs {
+            let version = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let missing = match runner_type {
+                "proton" => missing_proton_files(&path),
+                _ => missing_dxvk_files(&path),
+            };
+            checks.push(if missing.is_empty() {
+                DoctorCheck::pass(format!("{label} {version} is complete"))
+            } else {
+                DoctorCheck::fail(
+                    format!(
+                        "{label} {version} is incomplete, missing {}",
+                        missing.join(", ")
+                    ),
+                    format!("cellar runners install {runner_type} {version}"),
+                )
+            });
+        }
+    }
+
+    if checks.is_empty() {
+        println!("No Proton or DXVK runners installed");
+        return Ok(());
+    }
+
+    println!("Runners in {}", runners_path.display());
+    for check in &checks {
+        check.print();
+    }
+
+    finish_doctor_report(&checks)
+}
+
+/// Label a runner for listings, e.g. "GE-Proton9-1 (GE-Proton9-1) [pinned]"
+fn runner_label(runner: &Runner) -> String {
+    if runner.pinned {
+        format!("{} ({}) [pinned]", runner.name, runner.version)
+    } else {
+        format!("{} ({})", runner.name, runner.version)
+    }
+}
+
+/// Print a runner for `runners list`, with any other locations of the same version
+fn print_runner(runner: &Runner) {
+    println!("  {}", runner_label(runner));
+    println!("    Path: {}", runner.path.display());
+    for path in &runner.other_paths {
+        println!("    Also installed at: {} (not used)", path.display());
+    }
+}
+
+fn load_runner_cache(dirs: &CellarDirectories) -> Option<RunnerCache> {
+    let cache_content = fs::read_to_string(dirs.get_cache_path().join("runners.toml")).ok()?;
+    toml::from_str(&cache_content).ok()
+}
+
+/// Write `cache/runners.toml` atomically, so a command reading it concurrently never sees it half written
+fn save_runner_cache(dirs: &CellarDirectories, cache: &RunnerCache) -> Result<()> {
+    let cache_content = toml::to_string_pretty(cache)?;
+    write_file_atomic(&dirs.get_cache_path().join("runners.toml"), cache_content)
+}
+
+/// Read `runners/pins.toml`; without one, fall back to the pins older
+/// versions kept in the runner cache
+fn load_runner_pins(dirs: &CellarDirectories) -> RunnerPins {
+    let pins_path = dirs.get_runner_pins_path();
+    match fs::read_to_string(&pins_path) {
+        Ok(content) => toml::from_str(&content).unwrap_or_else(|e| {
+            eprintln!(
+                "Warning: Ignoring unreadable runner pins in {}: {}",
+                pins_path.display(),
+                e
+            );
+            RunnerPins::default()
+        }),
+        Err(_) => load_runner_cache(dirs)
+            .map(|cache| RunnerPins::from_cache(&cache))
+            .unwrap_or_default(),
+    }
+}
+
+fn save_runner_pins(dirs: &CellarDirectories, pins: &RunnerPins) -> Result<()> {
+    let pins_content = toml::to_string_pretty(pins)?;
+    write_file_atomic(&dirs.get_runner_pins_path(), pins_content)
+}
+
+async fn set_runner_pinned(
+    dirs: &CellarDirectories,
+    runner_type: &str,
+    version: &str,
+    pinned: bool,
+) -> Result<()> {
+    let runner_type = match runner_type.to_lowercase().as_str() {
+        "proton" => RunnerType::Proton,
+        "dxvk" => RunnerType::Dxvk,
+        "wine" => RunnerType::Wine,
+        _ => {
+            return Err(anyhow!(
+                "Unsupported runner type: {}. Supported types: proton, dxvk, wine",
+                runner_type
+            ))
+        }
+    };
+
+    dirs.ensure_all_exist()?;
+
+    // Start from a fresh scan so newly installed runners can be pinned
+    let mut cache = refresh_runners_cache(dirs).await?;
+
+    let runner = cache
+        .runners
+        .iter_mut()
+        .find(|r| r.runner_type == runner_type && r.matches_version(version))
+        .ok_or_else(|| anyhow!("Runner version '{}' not found", version))?;
+
+    let mut pins = load_runner_pins(dirs);
+    pins.set_pinned(runner, pinned);
+    save_runner_pins(dirs, &pins)?;
+
+    runner.pinned = pinned;
+    let label = runner.name.clone();
+    save_runner_cache(dirs, &cache)?;
+
+    if pinned {
+        println!("Pinned runner: {label}");
+    } else {
+        println!("Unpinned runner: {label}");
+    }
+
+    Ok(())
+}
+
+/// Installed Proton, DXVK and Wine runners, found by scanning their directories
+async fn discover_all_runners(dirs: &CellarDirectories) -> Result<Vec<Runner>> {
+    let runners_path = dirs.get_runners_path();
+    let proton_manager = ProtonManager::new(runners_path.clone());
+    let dxvk_manager = DxvkManager::new(runners_path.clone());
+    let wine_manager = WineManager::new(runners_path);
+
+    let mut all_runners = Vec::new();
+    all_runners.extend(proton_manager.discover_local_runners().await?);
+    all_runners.extend(dxvk_manager.discover_local_runners().await?);
+    all_runners.extend(wine_manager.discover_local_runners().await?);
+    Ok(all_runners)
+}
+
+/// A runner as printed by `runners list --json`
+#[derive(serde::Serialize)]
+struct RunnerListEntry<'a> {
+    #[serde(flatten)]
+    runner: &'a Runner,
+    size_bytes: u64,
+}
+
+async fn list_runners_json(dirs: &CellarDirectories) -> Result<()> {
+    dirs.ensure_all_exist()?;
+
+    let mut runners = discover_all_runners(dirs).await?;
+    load_runner_pins(dirs).apply(&mut runners);
+
+    let entries: Vec<RunnerListEntry> = runners
+        .iter()
+        .map(|runner| RunnerListEntry {
+            runner,
+            size_bytes: dir_size(&runner.path).unwrap_or(0),
+        })
+        .collect();
+
+    println!("{}", serde_json::to_string_pretty(&entries)?);
+    Ok(())
+}
+
+async fn list_runners(dirs: &CellarDirectories) -> Result<()> {
+    dirs.ensure_all_exist()?; // Ensure all directories exist
+    let cache_path = dirs.get_cache_path().join("runners.toml");
+
+    // Try to load from cache first
+    if cache_path.exists() {
+        if let Ok(cache_content) = fs::read_to_string(&cache_path) {
+            if let Ok(mut cache) = toml::from_str::<crate::runners::RunnerCache>(&cache_content) {
+                load_runner_pins(dirs).apply(&mut cache.runners);
+
+                // Check if cache is recent (less than 1 hour old)
+                let cache_age = chrono::Utc::now().signed_duration_since(cache.last_updated);
+                if cache_age.num_hours() < 1 {
+                    println!("Installed Runners (cached):");
+
+                    let proton_runners: Vec<_> = cache
+                        .runners
+                        .iter()
+                        .filter(|r| matches!(r.runner_type, crate::runners::RunnerType::Proton))
+                        .collect();
+
+                    let dxvk_runners: Vec<_> = cache
+                        .runners
+                        .iter()
+                        .filter(|r| matches!(r.runner_type, crate::runners::RunnerType::Dxvk))
+                        .collect();
+
+                    let wine_runners: Vec<_> = cache
+                        .runners
+                        .iter()
+                        .filter(|r| matches!(r.runner_type, crate::runners::RunnerType::Wine))
+                        .collect();
+
+                    if !proton_runners.is_empty() {
+                        println!("\nProton Runners:");
+                        for runner in &proton_runners {
+                            print_runner(runner);
+                        }
+                    }
+
+                    if !dxvk_runners.is_empty() {
+                        println!("\nDXVK Runners:");
+                        for runner in &dxvk_runners {
+                            print_runner(runner);
+                        }
+                    }
+
+                    if !wine_runners.is_empty() {
+                        println!("\nWine Runners:");
+                        for runner in &wine_runners {
+                            print_runner(runner);
+                        }
+                    }
+
+                    if proton_runners.is_empty()
+                        && dxvk_runners.is_empty()
+                        && wine_runners.is_empty()
+                    {
+                        println!(
+                            "  No runners found. Use 'cellar runners install' to install runners."
+                        );
+                    }
+
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    // Cache is old or doesn't exist, scan live
+    let runners_path = dirs.get_runners_path();
+
+    let proton_manager = ProtonManager::new(runners_path.clone());
+    let dxvk_manager = DxvkManager::new(runners_path.clone());
+    let wine_manager = WineManager::new(runners_path);
+
+    println!("Installed Runners:");
+
+    let pins = load_runner_pins(dirs);
+
+    let mut proton_runners = proton_manager.discover_local_runners().await?;
+    pins.apply(&mut proton_runners);
+
+    // List Proton runners
+    if !proton_runners.is_empty() {
+        println!("\nProton Runners:");
+        for runner in &proton_runners {
+            print_runner(runner);
+        }
+    }
+
+    // List DXVK runners
+    let mut dxvk_runners = dxvk_manager.discover_local_runners().await?;
+    pins.apply(&mut dxvk_runners);
+    if !dxvk_runners.is_empty() {
+        println!("\nDXVK Runners:");
+        for runner in &dxvk_runners {
+            print_runner(runner);
+        }
+    }
+
+    // List Wine runners
+    let mut wine_runners = wine_manager.discover_local_runners().await?;
+    pins.apply(&mut wine_runners);
+    if !wine_runners.is_empty() {
+        println!("\nWine Runners:");
+        for runner in &wine_runners {
+            print_runner(runner);
+        }
+    }
+
+    if proton_runners.is_empty() && dxvk_runners.is_empty() && wine_runners.is_empty() {
+        println!("  No runners found. Use 'cellar runners install' to install runners.");
+    }
+
+    Ok(())
+}
+
+async fn refresh_runners(dirs: &CellarDirectories) -> Result<()> {
+    dirs.ensure_all_exist()?; // Ensure all directories exist including cache
+
+    println!("Refreshing runner cache...");
+
+    let cache = refresh_runners_cache(dirs).await?;
+
+    println!(
+        "Runner cache refreshed with {} runners.",
+        cache.runners.len()
+    );
+
+    Ok(())
+}
+
+async fn show_available_runners(dirs: &CellarDirectories) -> Result<()> {
+    let runners_path = dirs.get_runners_path();
+
+    println!("Fetching available runners...");
+
+    // Get available Proton versions
+    let proton_manager = ProtonManager::new(runners_path.clone());
+    match proton_manager.get_available_versions().await {
+        Ok(versions) => {
+            println!("\nAvailable Proton-GE versions:");
+            for version in versions.iter().take(10) {
+                // Show first 10
+                println!("  {version}");
+            }
+            if versions.len() > 10 {
+                println!("  ... and {} more", versions.len() - 10);
+            }
+        }
+        Err(e) => println!("Failed to fetch Proton versions: {e}"),
+    }
+
+    // Get available DXVK versions
+    let dxvk_manager = DxvkManager::new(runners_path.clone());
+    match dxvk_manager.get_available_versions().await {
         Ok(versions) => {
             println!("\nAvailable DXVK versions:");
             for version in versions.iter().take(10) {
@@ -857,121 +3145,714 @@ async fn show_available_runners() -> Result<()> {
         Err(e) => println!("Failed to fetch DXVK versions: {e}"),
     }
 
-    Ok(())
-}
-
-async fn install_runner(runner_type: &str, version: &str) -> Result<()> {
-    let dirs = CellarDirectories::new()?;
-    let runners_path = dirs.get_runners_path();
+    // Get available Wine-GE versions
+    let wine_manager = WineManager::new(runners_path);
+    match wine_manager.get_available_versions().await {
+        Ok(versions) => {
+            println!("\nAvailable Wine-GE versions:");
+            for version in versions.iter().take(10) {
+                // Show first 10
+                println!("  {version}");
+            }
+            if versions.len() > 10 {
+                println!("  ... and {} more", versions.len() - 10);
+            }
+        }
+        Err(e) => println!("Failed to fetch Wine-GE versions: {e}"),
+    }
+
+    Ok(())
+}
+
+/// Download and install a runner, returning the version installed (resolved for "latest")
+async fn install_runner(
+    dirs: &CellarDirectories,
+    runner_type: &str,
+    version: &str,
+    allow_large: bool,
+) -> Result<String> {
+    let runners_path = dirs.get_runners_path();
+
+    if allow_large {
+        eprintln!("Warning: Download size limit disabled; make sure the release is trustworthy");
+    }
+
+    let latest;
+    let version = if version.eq_ignore_ascii_case("latest") {
+        latest = latest_available_version(&runners_path, runner_type).await?;
+        println!("Latest {runner_type} release: {latest}");
+        latest.as_str()
+    } else {
+        version
+    };
+
+    match runner_type.to_lowercase().as_str() {
+        "proton" => {
+            println!("Installing Proton-GE {version}...");
+            let mut proton_manager = ProtonManager::new(runners_path);
+            proton_manager
+                .base_runner
+                .set_download_dir(dirs.get_downloads_path());
+            if allow_large {
+                proton_manager.base_runner.allow_large_downloads();
+            }
+
+            // Extract the actual version number from the full version string
+            // e.g., "GE-Proton10-10" -> "10-10"
+            let version_number = if version.starts_with("GE-Proton") {
+                version.strip_prefix("GE-Proton").unwrap_or(version)
+            } else {
+                version
+            };
+
+            let download_path = proton_manager
+                .download_runner("proton-ge", version_number)
+                .await?;
+            proton_manager
+                .install_runner(&download_path, Path::new(""))
+                .await?;
+
+            println!("Successfully installed Proton-GE {version}");
+        }
+        "dxvk" => {
+            println!("Installing DXVK {version}...");
+            let mut dxvk_manager = DxvkManager::new(runners_path);
+            dxvk_manager
+                .base_runner
+                .set_download_dir(dirs.get_downloads_path());
+            if allow_large {
+                dxvk_manager.base_runner.allow_large_downloads();
+            }
+
+            let download_path = dxvk_manager.download_runner("dxvk", version).await?;
+            dxvk_manager
+                .install_runner(&download_path, Path::new(""))
+                .await?;
+
+            println!("Successfully installed DXVK {version}");
+        }
+        "wine" => {
+            println!("Installing Wine-GE {version}...");
+            let mut wine_manager = WineManager::new(runners_path);
+            wine_manager
+                .base_runner
+                .set_download_dir(dirs.get_downloads_path());
+            if allow_large {
+                wine_manager.base_runner.allow_large_downloads();
+            }
+
+            let download_path = wine_manager.download_runner("wine-ge", version).await?;
+            wine_manager
+                .install_runner(&download_path, Path::new(""))
+                .await?;
+
+            println!("Successfully installed Wine-GE {version}");
+        }
+        _ => {
+            return Err(anyhow!(
+                "Unsupported runner type: {}. Supported types: proton, dxvk, wine",
+                runner_type
+            ));
+        }
+    }
+
+    // Refresh cache after installation
+    refresh_runners(dirs).await?;
+
+    Ok(version.to_string())
+}
+
+/// Install the newest DXVK release for `runners install --with-dxvk`, skipping
+/// the download when it's already installed
+async fn install_latest_dxvk(dirs: &CellarDirectories, allow_large: bool) -> Result<String> {
+    let runners_path = dirs.get_runners_path();
+    let latest = latest_available_version(&runners_path, "dxvk").await?;
+
+    let installed = DxvkManager::new(runners_path)
+        .discover_local_runners()
+        .await?;
+    if installed.iter().any(|runner| runner.version == latest) {
+        println!("DXVK {latest} is already installed");
+        return Ok(latest);
+    }
+
+    install_runner(dirs, "dxvk", &latest, allow_large).await
+}
+
+/// Newest release of a runner type available for download
+async fn latest_available_version(runners_path: &Path, runner_type: &str) -> Result<String> {
+    let (kind, versions) = match runner_type.to_lowercase().as_str() {
+        "proton" => (
+            RunnerType::Proton,
+            ProtonManager::new(runners_path.to_path_buf())
+                .get_available_versions()
+                .await?,
+        ),
+        "dxvk" => (
+            RunnerType::Dxvk,
+            DxvkManager::new(runners_path.to_path_buf())
+                .get_available_versions()
+                .await?,
+        ),
+        "wine" => (
+            RunnerType::Wine,
+            WineManager::new(runners_path.to_path_buf())
+                .get_available_versions()
+                .await?,
+        ),
+        _ => {
+            return Err(anyhow!(
+                "Unsupported runner type: {}. Supported types: proton, dxvk, wine",
+                runner_type
+            ));
+        }
+    };
+
+    newest_version(kind, &versions)
+        .cloned()
+        .ok_or_else(|| anyhow!("No {} releases found", runner_type))
+}
+
+async fn show_runner_info(
+    dirs: &CellarDirectories,
+    runner_type: &str,
+    version: &str,
+) -> Result<()> {
+    let runners_path = dirs.get_runners_path();
+
+    let runners = match runner_type.to_lowercase().as_str() {
+        "proton" => {
+            ProtonManager::new(runners_path)
+                .discover_local_runners()
+                .await?
+        }
+        "dxvk" => {
+            DxvkManager::new(runners_path)
+                .discover_local_runners()
+                .await?
+        }
+        "wine" => {
+            WineManager::new(runners_path)
+                .discover_local_runners()
+                .await?
+        }
+        _ => {
+            return Err(anyhow!(
+                "Unsupported runner type: {}. Supported types: proton, dxvk, wine",
+                runner_type
+            ));
+        }
+    };
+
+    let mut runner = runners
+        .into_iter()
+        .find(|r| r.matches_version(version))
+        .ok_or_else(|| anyhow!("Runner version '{}' not found", version))?;
+
+    runner.pinned = load_runner_pins(dirs).is_pinned(&runner);
+
+    println!("{}", runner_label(&runner));
+    println!("  Path: {}", runner.path.display());
+    println!(
+        "  Size: {}",
+        format_size(dir_size(&runner.path).unwrap_or(0))
+    );
+
+    let installed = fs::metadata(&runner.path)
+        .and_then(|metadata| metadata.modified())
+        .map(|modified| {
+            chrono::DateTime::<chrono::Local>::from(modified)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|_| "unknown".to_string());
+    println!("  Installed: {installed}");
+
+    let games = find_games_using_runners(dirs, std::slice::from_ref(&runner))?;
+    if games.is_empty() {
+        println!("  Used by: no games");
+    } else {
+        println!("  Used by:");
+        for game in &games {
+            println!("    {game}");
+        }
+    }
+
+    Ok(())
+}
+
+async fn remove_runner(dirs: &CellarDirectories, runner_type: &str, version: &str) -> Result<()> {
+    let runners_path = dirs.get_runners_path();
+
+    match runner_type.to_lowercase().as_str() {
+        "proton" => {
+            println!("Removing Proton-GE {version}...");
+            let proton_manager = ProtonManager::new(runners_path);
+
+            let runners = proton_manager.discover_local_runners().await?;
+            let runner = runners
+                .iter()
+                .find(|r| r.matches_version(version))
+                .ok_or_else(|| anyhow!("Proton version '{}' not found", version))?;
+
+            proton_manager.delete_runner(&runner.path).await?;
+            println!("Successfully removed Proton-GE {version}");
+        }
+        "dxvk" => {
+            println!("Removing DXVK {version}...");
+            let dxvk_manager = DxvkManager::new(runners_path);
+
+            let runners = dxvk_manager.discover_local_runners().await?;
+            let runner = runners
+                .iter()
+                .find(|r| r.matches_version(version))
+                .ok_or_else(|| anyhow!("DXVK version '{}' not found", version))?;
+
+            dxvk_manager.delete_runner(&runner.path).await?;
+            println!("Successfully removed DXVK {version}");
+        }
+        "wine" => {
+            println!("Removing Wine-GE {version}...");
+            let wine_manager = WineManager::new(runners_path);
+
+            let runners = wine_manager.discover_local_runners().await?;
+            let runner = runners
+                .iter()
+                .find(|r| r.matches_version(version))
+                .ok_or_else(|| anyhow!("Wine version '{}' not found", version))?;
+
+            wine_manager.delete_runner(&runner.path).await?;
+            println!("Successfully removed Wine-GE {version}");
+        }
+        _ => {
+            return Err(anyhow!(
+                "Unsupported runner type: {}. Supported types: proton, dxvk, wine",
+                runner_type
+            ));
+        }
+    }
+
+    // Refresh cache after removal
+    refresh_runners(dirs).await?;
+
+    Ok(())
+}
+
+async fn remove_all_runners(dirs: &CellarDirectories, runner_type: &str) -> Result<()> {
+    let runners_path = dirs.get_runners_path();
+
+    // Only Cellar-managed installs are removed; Steam's own Proton builds are left alone
+    let (label, runners, base_runner) = match runner_type.to_lowercase().as_str() {
+        "proton" => {
+            let proton_manager = ProtonManager::new(runners_path);
+            let runners = proton_manager.discover_cellar_proton().await?;
+            ("Proton-GE", runners, proton_manager.base_runner)
+        }
+        "dxvk" => {
+            let dxvk_manager = DxvkManager::new(runners_path);
+            let runners = dxvk_manager.discover_cellar_dxvk().await?;
+            ("DXVK", runners, dxvk_manager.base_runner)
+        }
+        "wine" => {
+            let wine_manager = WineManager::new(runners_path);
+            let runners = wine_manager.discover_cellar_wine().await?;
+            ("Wine-GE", runners, wine_manager.base_runner)
+        }
+        _ => {
+            return Err(anyhow!(
+                "Unsupported runner type: {}. Supported types: proton, dxvk, wine",
+                runner_type
+            ));
+        }
+    };
+
+    if runners.is_empty() {
+        println!("No {label} runners installed.");
+        return Ok(());
+    }
+
+    let runners = without_pinned(runners, &load_runner_pins(dirs));
+    if runners.is_empty() {
+        println!("All {label} runners are pinned, nothing to remove.");
+        return Ok(());
+    }
+
+    println!("The following {label} runners will be removed:");
+    for runner in &runners {
+        println!("  {} ({})", runner.name, runner.version);
+    }
+
+    let affected_games = find_games_using_runners(dirs, &runners)?;
+    if !affected_games.is_empty() {
+        println!(
+            "\nThese games use one of these runners and will fail to launch until reconfigured:"
+        );
+        for game in &affected_games {
+            println!("  {game}");
+        }
+    }
+
+    if !confirm(&format!("Remove all {} {label} runners?", runners.len()))? {
+        println!("Operation cancelled.");
+        return Ok(());
+    }
+
+    let mut removed_count = 0;
+    for runner in &runners {
+        match base_runner.delete_runner_common(&runner.path).await {
+            Ok(()) => removed_count += 1,
+            Err(e) => eprintln!("Warning: Failed to remove {}: {}", runner.name, e),
+        }
+    }
+
+    println!(
+        "Removed {removed_count} of {} {label} runners.",
+        runners.len()
+    );
+    if !affected_games.is_empty() {
+        println!("Affected games: {}", affected_games.join(", "));
+    }
+
+    // Refresh cache after removal
+    refresh_runners(dirs).await?;
+
+    Ok(())
+}
+
+/// Drop pinned runners from a bulk removal, saying which ones are kept
+fn without_pinned(runners: Vec<Runner>, pins: &RunnerPins) -> Vec<Runner> {
+    runners
+        .into_iter()
+        .filter(|runner| {
+            let pinned = pins.is_pinned(runner);
+            if pinned {
+                println!("Keeping {} (pinned)", runner.name);
+            }
+            !pinned
+        })
+        .collect()
+}
+
+/// Remove all but the newest `keep` Cellar-installed Proton versions,
+/// skipping pinned runners and runners still used by a game
+async fn prune_proton_runners(dirs: &CellarDirectories, keep: usize) -> Result<()> {
+    dirs.ensure_all_exist()?;
+
+    let proton_manager = ProtonManager::new(dirs.get_runners_path());
+    let mut runners = proton_manager.discover_cellar_proton().await?;
+    runners.sort_by_key(|r| std::cmp::Reverse(r.version_tuple()));
+
+    let pins = load_runner_pins(dirs);
+    let mut to_remove = Vec::new();
+
+    for runner in runners.iter().skip(keep) {
+        if pins.is_pinned(runner) {
+            println!("Keeping {} (pinned)", runner.name);
+            continue;
+        }
+
+        let games = find_games_using_runners(dirs, std::slice::from_ref(runner))?;
+        if !games.is_empty() {
+            println!("Keeping {} (used by {})", runner.name, games.join(", "));
+            continue;
+        }
+
+        to_remove.push(runner);
+    }
+
+    if to_remove.is_empty() {
+        println!("Nothing to prune.");
+        return Ok(());
+    }
+
+    println!("The following Proton-GE runners will be removed:");
+    let mut total_size = 0;
+    for runner in &to_remove {
+        let size = dir_size(&runner.path).unwrap_or(0);
+        total_size += size;
+        println!("  {} ({})", runner.name, format_size(size));
+    }
+    println!("This will free {}.", format_size(total_size));
+
+    if !confirm(&format!("Remove these {} runners?", to_remove.len()))? {
+        println!("Operation cancelled.");
+        return Ok(());
+    }
+
+    let mut removed_count = 0;
+    for runner in &to_remove {
+        match proton_manager.delete_runner(&runner.path).await {
+            Ok(()) => removed_count += 1,
+            Err(e) => eprintln!("Warning: Failed to remove {}: {}", runner.name, e),
+        }
+    }
+
+    println!("Removed {removed_count} of {} runners.", to_remove.len());
 
-    match runner_type.to_lowercase().as_str() {
-        "proton" => {
-            println!("Installing Proton-GE {version}...");
-            let proton_manager = ProtonManager::new(runners_path);
+    refresh_runners_cache(dirs).await?;
 
-            // Extract the actual version number from the full version string
-            // e.g., "GE-Proton10-10" -> "10-10"
-            let version_number = if version.starts_with("GE-Proton") {
-                version.strip_prefix("GE-Proton").unwrap_or(version)
-            } else {
-                version
-            };
+    Ok(())
+}
 
-            let download_path = proton_manager
-                .download_runner("proton-ge", version_number)
-                .await?;
-            proton_manager
-                .install_runner(&download_path, Path::new(""))
-                .await?;
+/// Remove what interrupted runner installs leave behind, reporting the space freed
+///
+/// Failed downloads aren't cleaned up when they fail, on purpose: their `.part` files
+/// let the next install resume where they stopped.
+fn cleanup_runner_cache(dirs: &CellarDirectories) -> Result<()> {
+    let leftovers = find_install_leftovers(
+        &std::env::temp_dir(),
+        &dirs.get_downloads_path(),
+        &dirs.get_runners_path(),
+        download_timeout(),
+    );
+    if leftovers.is_empty() {
+        println!("No leftover downloads found.");
+        return Ok(());
+    }
 
-            println!("Successfully installed Proton-GE {version}");
+    let mut removed_count = 0;
+    let mut freed = 0;
+    for path in &leftovers {
+        let (size, result) = if path.is_dir() {
+            (dir_size(path).unwrap_or(0), fs::remove_dir_all(path))
+        } else {
+            let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+            (size, fs::remove_file(path))
+        };
+        match result {
+            Ok(()) => {
+                println!("Removed {} ({})", path.display(), format_size(size));
+                removed_count += 1;
+                freed += size;
+            }
+            Err(e) => eprintln!("Warning: Failed to remove {}: {}", path.display(), e),
         }
-        "dxvk" => {
-            println!("Installing DXVK {version}...");
-            let dxvk_manager = DxvkManager::new(runners_path);
+    }
 
-            let download_path = dxvk_manager.download_runner("dxvk", version).await?;
-            dxvk_manager
-                .install_runner(&download_path, Path::new(""))
-                .await?;
+    println!(
+        "Removed {removed_count} of {} leftovers, freeing {}.",
+        leftovers.len(),
+        format_size(freed)
+    );
+    Ok(())
+}
 
-            println!("Successfully installed DXVK {version}");
+/// Everything in `downloads_dir` except installers still being downloaded, the
+/// same leftovers in `temp_dir` from older versions of Cellar, and extraction
+/// staging dirs under `runners_path`
+///
+/// Runner downloads and staging dirs have no owner recorded, so any changed within
+/// `active_window` are assumed to belong to an install that is still running.
+fn find_install_leftovers(
+    temp_dir: &Path,
+    downloads_dir: &Path,
+    runners_path: &Path,
+    active_window: std::time::Duration,
+) -> Vec<PathBuf> {
+    // Installer downloads are named after the Cellar process doing them
+    let in_use = |name: &str, prefix: &str| {
+        name.strip_prefix(prefix)
+            .and_then(|pid| pid.parse().ok())
+            .is_some_and(process_is_alive)
+    };
+    let active = |path: &Path| modified_within(path, active_window);
+    let mut leftovers = Vec::new();
+
+    for entry in fs::read_dir(downloads_dir).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with("installer-") {
+            if !in_use(&name, "installer-") {
+                leftovers.push(entry.path());
+            }
+        } else if !active(&entry.path()) {
+            leftovers.push(entry.path());
         }
-        _ => {
-            return Err(anyhow!(
-                "Unsupported runner type: {}. Supported types: proton, dxvk",
-                runner_type
-            ));
+    }
+
+    for entry in fs::read_dir(temp_dir).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let stale_installer =
+            name.starts_with("cellar-installer-") && !in_use(&name, "cellar-installer-");
+        if (is_leftover_download(&name) && !active(&entry.path())) || stale_installer {
+            leftovers.push(entry.path());
         }
     }
 
-    // Refresh cache after installation
-    refresh_runners().await?;
+    // Staging dirs sit beside the runner being extracted, e.g. `proton/.GE-Proton9-20.partial`
+    for type_dir in fs::read_dir(runners_path).into_iter().flatten().flatten() {
+        for entry in fs::read_dir(type_dir.path())
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') && name.ends_with(STAGING_SUFFIX) && !active(&entry.path()) {
+                leftovers.push(entry.path());
+            }
+        }
+    }
 
-    Ok(())
+    leftovers.sort();
+    leftovers
 }
 
-async fn remove_runner(runner_type: &str, version: &str) -> Result<()> {
-    let dirs = CellarDirectories::new()?;
-    let runners_path = dirs.get_runners_path();
-
-    match runner_type.to_lowercase().as_str() {
-        "proton" => {
-            println!("Removing Proton-GE {version}...");
-            let proton_manager = ProtonManager::new(runners_path);
+/// Whether `path`, or anything under it, was modified less than `window` ago
+///
+/// Extraction writes deep inside a staging dir without touching the dir itself,
+/// so directories are walked. Symlinks are not followed.
+fn modified_within(path: &Path, window: std::time::Duration) -> bool {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return false;
+    };
+    let recent = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_none_or(|age| age < window);
+    if recent {
+        return true;
+    }
 
-            let runners = proton_manager.discover_local_runners().await?;
-            let runner = runners
-                .iter()
-                .find(|r| r.version == version || r.name.contains(version))
-                .ok_or_else(|| anyhow!("Proton version '{}' not found", version))?;
+    metadata.is_dir()
+        && fs::read_dir(path)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .any(|entry| modified_within(&entry.path(), window))
+}
 
-            proton_manager.delete_runner(&runner.path).await?;
-            println!("Successfully removed Proton-GE {version}");
-        }
-        "dxvk" => {
-            println!("Removing DXVK {version}...");
-            let dxvk_manager = DxvkManager::new(runners_path);
+/// Find games whose configured Proton or DXVK version resolves to one of the given runners
+fn find_games_using_runners(dirs: &CellarDirectories, runners: &[Runner]) -> Result<Vec<String>> {
+    let mut games = Vec::new();
 
-            let runners = dxvk_manager.discover_local_runners().await?;
-            let runner = runners
-                .iter()
-                .find(|r| r.version == version || r.name.contains(version))
-                .ok_or_else(|| anyhow!("DXVK version '{}' not found", version))?;
+    for game_name in dirs.list_game_configs()? {
+        if let Ok((config, _)) = read_game_config(dirs, &game_name) {
+            let uses_runner = runners.iter().any(|runner| {
+                let configured_version = match runner.runner_type {
+                    RunnerType::Proton | RunnerType::Wine => {
+                        Some(config.game.proton_version.as_str())
+                    }
+                    RunnerType::Dxvk => config.game.dxvk_version.as_deref(),
+                };
+                configured_version.is_some_and(|version| match runner.runner_type {
+                    // Wine runners are configured by name, since their version
+                    // numbers overlap with Proton-GE's
+                    RunnerType::Wine => runner.matches_name(version),
+                    _ => runner.matches_version(version),
+                })
+            });
 
-            dxvk_manager.delete_runner(&runner.path).await?;
-            println!("Successfully removed DXVK {version}");
-        }
-        _ => {
-            return Err(anyhow!(
-                "Unsupported runner type: {}. Supported types: proton, dxvk",
-                runner_type
-            ));
+            if uses_runner {
+                games.push(config.game.name);
+            }
         }
     }
 
-    // Refresh cache after removal
-    refresh_runners().await?;
-
-    Ok(())
+    Ok(games)
 }
 
 // Prefix management functions
-pub async fn handle_prefix_command(command: PrefixCommands) -> Result<()> {
+pub async fn handle_prefix_command(
+    dirs: &CellarDirectories,
+    command: PrefixCommands,
+) -> Result<()> {
     match command {
-        PrefixCommands::Create { name, proton } => create_prefix(&name, proton.as_deref()).await,
-        PrefixCommands::List => list_prefixes().await,
-        PrefixCommands::Remove { name } => remove_prefix(&name).await,
+        PrefixCommands::Create {
+            name,
+            proton,
+            force,
+        } => {
+            if force {
+                delete_prefix_for_recreation(dirs, &name)?;
+            }
+            create_prefix(dirs, &name, proton.as_deref()).await
+        }
+        PrefixCommands::List => list_prefixes(dirs).await,
+        PrefixCommands::Info { name } => show_prefix_info(dirs, &name).await,
+        PrefixCommands::Remove { name } => remove_prefix(dirs, &name).await,
         PrefixCommands::Run {
             prefix,
             exe,
             proton,
-        } => run_in_prefix(&prefix, &exe, proton.as_deref()).await,
+            env,
+            args,
+        } => {
+            let env = parse_env_vars(&env)?;
+            run_in_prefix(dirs, &prefix, &exe, &args, &env, proton.as_deref()).await
+        }
+        PrefixCommands::Regedit { prefix } => run_wine_tool(dirs, &prefix, "regedit").await,
+        PrefixCommands::Winecfg { prefix } => run_wine_tool(dirs, &prefix, "winecfg").await,
+        PrefixCommands::Shell { prefix } => open_prefix_shell(dirs, &prefix).await,
+        PrefixCommands::Du { name } => show_prefix_disk_usage(dirs, &name).await,
+        PrefixCommands::CopyTo { name, destination } => {
+            copy_prefix_to(dirs, &name, &destination).await
+        }
+        PrefixCommands::Export { name, output } => export_prefix(dirs, &name, &output).await,
+        PrefixCommands::Import { file, name } => import_prefix(dirs, &file, &name).await,
+        PrefixCommands::Winetricks { prefix, verbs } => run_winetricks(dirs, &prefix, &verbs).await,
+    }
+}
+
+/// Run a Wine runner's own `wine` binary in a prefix, without umu-run or Proton
+fn wine_runner_command(runner: &Runner, prefix_path: &Path) -> tokio::process::Command {
+    let mut command = tokio::process::Command::new(runner.wine_bin_dir().join("wine"));
+    command
+        .env("WINEPREFIX", prefix_path)
+        .env("WINEARCH", "win64");
+    command
+}
+
+/// Default for how long `prefix create` waits on umu-run or wineboot
+const DEFAULT_PREFIX_TIMEOUT_SECS: u64 = 300;
+
+/// Prefix initialization timeout, overridable with `CELLAR_PREFIX_TIMEOUT` in seconds
+fn prefix_init_timeout() -> std::time::Duration {
+    std::env::var("CELLAR_PREFIX_TIMEOUT")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .map(std::time::Duration::from_secs)
+        .unwrap_or(std::time::Duration::from_secs(DEFAULT_PREFIX_TIMEOUT_SECS))
+}
+
+/// Run a prefix initialization command under a spinner, killing it if it hangs,
+/// e.g. on a stalled Wine Mono or Gecko download
+async fn initialize_prefix(command: &mut tokio::process::Command) -> Result<std::process::Output> {
+    match output_with_spinner(command, "Initializing prefix", prefix_init_timeout()).await {
+        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => Err(anyhow!(
+            "Prefix initialization timed out ({}) and was stopped. Set CELLAR_PREFIX_TIMEOUT to allow longer, then retry with 'cellar prefix create --force'",
+            e
+        )),
+        result => Ok(result?),
+    }
+}
+
+/// Initialize a prefix with a Wine runner's `wineboot`
+async fn create_wine_runner_prefix(runner: &Runner, prefix_path: &Path) -> Result<()> {
+    fs::create_dir_all(prefix_path)?;
+
+    let output = initialize_prefix(
+        wine_runner_command(runner, prefix_path)
+            .env("WINEDEBUG", "-all")
+            .args(["wineboot", "--init"]),
+    )
+    .await?;
+
+    if !prefix_path.join("drive_c/windows/system32").exists() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("Failed to create Wine prefix: {}", stderr.trim()));
     }
+
+    println!("Prefix created successfully.");
+    Ok(())
 }
 
-async fn create_prefix(name: &str, proton_version: Option<&str>) -> Result<()> {
-    let dirs = CellarDirectories::new()?;
+#[tracing::instrument(skip(dirs))]
+async fn create_prefix(
+    dirs: &CellarDirectories,
+    name: &str,
+    proton_version: Option<&str>,
+) -> Result<()> {
     let prefix_path = dirs.get_prefixes_path().join(name);
 
     if prefix_path.exists() {
@@ -981,41 +3862,47 @@ async fn create_prefix(name: &str, proton_version: Option<&str>) -> Result<()> {
     println!("Creating wine prefix: {name}");
 
     if let Some(proton) = proton_version {
-        // Create Proton prefix using umu
-        println!("Using Proton version: {proton}");
-
-        let runners_path = dirs.get_runners_path();
-        let proton_manager = ProtonManager::new(runners_path);
-
         // Find the Proton installation
-        let runners = proton_manager.discover_local_runners().await?;
-        let proton_runner = runners.iter()
-            .find(|r| r.version == proton || r.name.contains(proton))
+        let proton_runner = find_game_runner(&dirs.get_runners_path(), proton)
+            .await?
             .ok_or_else(|| anyhow!("Proton version '{}' not found. Install it first with 'cellar runners install proton {}'", proton, proton))?;
 
-        println!("Initializing prefix...");
+        tracing::debug!(
+            runner = %proton_runner.name,
+            path = %proton_runner.path.display(),
+            "Found runner"
+        );
+        if proton_runner.runner_type == RunnerType::Wine {
+            println!("Using Wine runner: {}", proton_runner.name);
+            return create_wine_runner_prefix(&proton_runner, &prefix_path).await;
+        }
+
+        // Create Proton prefix using umu
+        require_umu_run().await?;
+        println!("Using Proton version: {proton}");
 
         // Set up cache directories for Wine Mono and Gecko like Lutris does
         let mono_cache = proton_runner.path.join("files/share/wine/mono");
         let gecko_cache = proton_runner.path.join("files/share/wine/gecko");
 
-        let output = tokio::process::Command::new("umu-run")
-            .env("WINEARCH", "win64")
-            .env("WINEPREFIX", &prefix_path)
-            .env("WINEDLLOVERRIDES", "")
-            .env("WINE_MONO_CACHE_DIR", &mono_cache)
-            .env("WINE_GECKO_CACHE_DIR", &gecko_cache)
-            .env("PROTON_VERB", "run")
-            .env("PROTONPATH", &proton_runner.path)
-            .env("GAMEID", "umu-default")
-            .arg("createprefix")
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::piped())
-            .output()
-            .await?;
+        let output = initialize_prefix(
+            tokio::process::Command::new("umu-run")
+                .env("WINEARCH", "win64")
+                .env("WINEPREFIX", &prefix_path)
+                .env("WINEDLLOVERRIDES", "")
+                .env("WINE_MONO_CACHE_DIR", &mono_cache)
+                .env("WINE_GECKO_CACHE_DIR", &gecko_cache)
+                .env("PROTON_VERB", "run")
+                .env("PROTONPATH", &proton_runner.path)
+                .env("GAMEID", "umu-default")
+                .arg("createprefix"),
+        )
+        .await?;
+        tracing::debug!(status = %output.status, "umu-run createprefix finished");
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
+            tracing::debug!(%stderr, "umu-run stderr");
 
             // Check if prefix was actually created despite non-zero exit code
             let system32_path = prefix_path.join("drive_c/windows/system32");
@@ -1027,19 +3914,7 @@ async fn create_prefix(name: &str, proton_version: Option<&str>) -> Result<()> {
                 println!("Prefix created successfully.");
             } else {
                 // Filter out common umu-run informational messages
-                let critical_errors: Vec<&str> = stderr
-                    .lines()
-                    .filter(|line| {
-                        let line_lower = line.to_lowercase();
-                        line_lower.contains("error")
-                            && !line.contains("INFO:")
-                            && !line.contains("WARN:")
-                            && !line.contains("Proton:")
-                            && !line.contains("ProtonFixes")
-                            && !line.contains("fsync:")
-                            && !line.trim().is_empty()
-                    })
-                    .collect();
+                let critical_errors = filter_wine_errors(&stderr, is_verbose());
 
                 if !critical_errors.is_empty() {
                     return Err(anyhow!(
@@ -1068,19 +3943,19 @@ async fn create_prefix(name: &str, proton_version: Option<&str>) -> Result<()> {
         }
     } else {
         // Create basic wine prefix
+        require_wine().await?;
         fs::create_dir_all(&prefix_path)?;
 
-        println!("Initializing prefix...");
-        let output = tokio::process::Command::new("wineboot")
-            .env("WINEPREFIX", &prefix_path)
-            .env("WINEARCH", "win64")
-            .env("WINEDEBUG", "-all") // Suppress all debug output
-            .env("WINEFSYNC", "1")
-            .env("WINEESYNC", "1")
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null()) // Completely suppress stderr during creation
-            .output()
-            .await?;
+        let output = initialize_prefix(
+            tokio::process::Command::new("wineboot")
+                .env("WINEPREFIX", &prefix_path)
+                .env("WINEARCH", "win64")
+                .env("WINEDEBUG", "-all") // Suppress all debug output
+                .env("WINEFSYNC", "1")
+                .env("WINEESYNC", "1"),
+        )
+        .await?;
+        tracing::debug!(status = %output.status, "wineboot finished");
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -1094,67 +3969,316 @@ async fn create_prefix(name: &str, proton_version: Option<&str>) -> Result<()> {
     Ok(())
 }
 
-async fn list_prefixes() -> Result<()> {
-    let dirs = CellarDirectories::new()?;
-    let prefixes_path = dirs.get_prefixes_path();
+async fn list_prefixes(dirs: &CellarDirectories) -> Result<()> {
+    let prefixes_path = dirs.get_prefixes_path();
+
+    if !prefixes_path.exists() {
+        println!("No prefixes found.");
+        return Ok(());
+    }
+
+    println!("Wine Prefixes:");
+
+    let mut entries = fs::read_dir(&prefixes_path)?;
+    let mut found_any = false;
+
+    while let Some(entry) = entries.next().transpose()? {
+        let path = entry.path();
+        if path.is_dir() {
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("invalid");
+
+            println!("  {name}");
+            println!("    Path: {}", path.display());
+
+            // Check if it's a valid wine prefix
+            let system32_path = path.join("drive_c/windows/system32");
+            if system32_path.exists() {
+                println!("    Status: Valid");
+            } else {
+                println!("    Status: Incomplete");
+            }
+            if let Some(dxvk_version) = installed_dxvk_version(&path) {
+                println!("    DXVK: {dxvk_version}");
+            }
+
+            found_any = true;
+        }
+    }
+
+    if !found_any {
+        println!("  No prefixes found.");
+    }
+
+    Ok(())
+}
+
+async fn show_prefix_info(dirs: &CellarDirectories, name: &str) -> Result<()> {
+    let prefix_path = dirs.get_prefixes_path().join(name);
+
+    if !prefix_path.is_dir() {
+        return Err(anyhow!("Prefix '{}' not found", name));
+    }
+
+    println!("Prefix Information for: {name}");
+    println!("  Path: {}", prefix_path.display());
+    if prefix_path.join("drive_c/windows/system32").exists() {
+        println!("  Status: Valid");
+    } else {
+        println!("  Status: Incomplete");
+    }
+
+    // Prefixes made with plain Wine or copied in from elsewhere have no version file
+    match fs::read_to_string(prefix_path.join("version")) {
+        Ok(version) if !version.trim().is_empty() => {
+            match detect_prefix_proton(dirs, &prefix_path).await {
+                Ok(Some(runner)) => println!("  Proton Version: {}", runner.name),
+                _ => println!("  Proton Version: {} (not installed)", version.trim()),
+            }
+        }
+        _ => println!("  Proton Version: none recorded (not created by Proton)"),
+    }
+
+    let arch = match detect_prefix_arch(&prefix_path) {
+        Some(PrefixArch::Win32) => "win32",
+        Some(PrefixArch::Win64) => "win64",
+        Some(PrefixArch::Wow64) => "win64 (WoW64)",
+        None => "unknown",
+    };
+    println!("  Architecture: {arch}");
+    println!(
+        "  Windows Version: {}",
+        windows_version(&prefix_path).unwrap_or_else(|| "unknown".to_string())
+    );
+    println!(
+        "  DXVK in Prefix: {}",
+        installed_dxvk_version(&prefix_path).unwrap_or_else(|| "not installed".to_string())
+    );
+    println!("  Size: {}", format_size(dir_size(&prefix_path)?));
+
+    let games = check_other_games_using_prefix(dirs, &prefix_path, "")?;
+    if games.is_empty() {
+        println!("  Used By: no games");
+    } else {
+        println!("  Used By: {}", games.join(", "));
+    }
+
+    Ok(())
+}
+
+/// Refuse to delete `prefix_path` unless it is a prefix inside the Cellar prefixes
+/// directory, after resolving `..` and symlinks
+fn ensure_inside_prefixes_dir(dirs: &CellarDirectories, prefix_path: &Path) -> Result<()> {
+    let prefixes_dir = fs::canonicalize(dirs.get_prefixes_path())?;
+    let resolved = fs::canonicalize(prefix_path)?;
+    if resolved == prefixes_dir || !resolved.starts_with(&prefixes_dir) {
+        return Err(anyhow!(
+            "Prefix {} resolves outside the Cellar prefixes directory, refusing to delete it",
+            prefix_path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Delete an existing prefix so `prefix create --force` can start over, asking first
+fn delete_prefix_for_recreation(dirs: &CellarDirectories, name: &str) -> Result<()> {
+    let prefix_path = dirs.get_prefixes_path().join(name);
+    if !prefix_path.exists() {
+        return Ok(());
+    }
+    ensure_inside_prefixes_dir(dirs, &prefix_path)?;
+
+    let games = check_other_games_using_prefix(dirs, &prefix_path, "")?;
+    if !games.is_empty() {
+        println!("Prefix '{}' is used by: {}", name, games.join(", "));
+    }
+    if !confirm(&format!(
+        "Delete prefix '{name}' and everything installed in it?"
+    ))? {
+        return Err(anyhow!("Prefix '{}' left unchanged", name));
+    }
+
+    println!("Removing existing prefix: {name}");
+    fs::remove_dir_all(&prefix_path)?;
+    Ok(())
+}
+
+async fn remove_prefix(dirs: &CellarDirectories, name: &str) -> Result<()> {
+    let prefix_path = dirs.get_prefixes_path().join(name);
+
+    if !prefix_path.exists() {
+        return Err(anyhow!("Prefix '{}' not found", name));
+    }
+    ensure_inside_prefixes_dir(dirs, &prefix_path)?;
+
+    println!("Removing prefix: {name}");
+    fs::remove_dir_all(&prefix_path)?;
+    println!("Successfully removed prefix: {name}");
+
+    Ok(())
+}
+
+async fn copy_prefix_to(dirs: &CellarDirectories, name: &str, destination: &Path) -> Result<()> {
+    let prefix_path = dirs.get_prefixes_path().join(name);
+
+    if !prefix_path.exists() {
+        return Err(anyhow!("Prefix '{}' not found", name));
+    }
+
+    let destination = crate::utils::fs::expand_tilde(destination)?;
+    if destination.exists() {
+        return Err(anyhow!(
+            "Destination already exists: {}",
+            destination.display()
+        ));
+    }
+
+    let parent = destination
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    if !parent.is_dir() {
+        return Err(anyhow!(
+            "Destination parent directory does not exist: {}",
+            parent.display()
+        ));
+    }
+    if fs::canonicalize(parent)?.starts_with(fs::canonicalize(&prefix_path)?) {
+        return Err(anyhow!("Destination cannot be inside the prefix itself"));
+    }
+
+    let size = dir_size(&prefix_path)?;
+    println!(
+        "Copying prefix '{name}' ({}) to {}...",
+        format_size(size),
+        destination.display()
+    );
+    println!("Note: the copy needs as much free space as the prefix itself.");
+
+    if let Err(e) = crate::utils::fs::copy_dir_recursive(&prefix_path, &destination) {
+        // Don't leave a partial copy behind
+        let _ = fs::remove_dir_all(&destination);
+        return Err(anyhow!("Failed to copy prefix: {}", e));
+    }
+
+    println!("Successfully copied prefix to: {}", destination.display());
+    println!("Use it with WINEPREFIX={}", destination.display());
+
+    Ok(())
+}
+
+async fn export_prefix(dirs: &CellarDirectories, name: &str, output: &Path) -> Result<()> {
+    let prefix_path = dirs.get_prefixes_path().join(name);
 
-    if !prefixes_path.exists() {
-        println!("No prefixes found.");
-        return Ok(());
+    if !prefix_path.exists() {
+        return Err(anyhow!("Prefix '{}' not found", name));
     }
 
-    println!("Wine Prefixes:");
+    let output = crate::utils::fs::expand_tilde(output)?;
+    if output.exists() {
+        return Err(anyhow!("Output file already exists: {}", output.display()));
+    }
 
-    let mut entries = fs::read_dir(&prefixes_path)?;
-    let mut found_any = false;
+    println!("Exporting prefix '{name}' to {}...", output.display());
+    crate::utils::archive::create_tar_zst(&prefix_path, &output)?;
 
-    while let Some(entry) = entries.next().transpose()? {
-        let path = entry.path();
-        if path.is_dir() {
-            let name = path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("invalid");
+    let size = fs::metadata(&output)?.len();
+    println!(
+        "Successfully exported prefix: {name} ({})",
+        format_size(size)
+    );
 
-            println!("  {name}");
-            println!("    Path: {}", path.display());
+    Ok(())
+}
 
-            // Check if it's a valid wine prefix
-            let system32_path = path.join("drive_c/windows/system32");
-            if system32_path.exists() {
-                println!("    Status: Valid");
-            } else {
-                println!("    Status: Incomplete");
-            }
+async fn import_prefix(dirs: &CellarDirectories, file: &Path, name: &str) -> Result<()> {
+    let file = crate::utils::fs::expand_tilde(file)?;
+    if !file.is_file() {
+        return Err(anyhow!("Archive not found: {}", file.display()));
+    }
 
-            found_any = true;
-        }
+    let prefix_path = dirs.get_prefixes_path().join(name);
+    if prefix_path.exists() {
+        return Err(anyhow!("Prefix '{}' already exists", name));
     }
 
-    if !found_any {
-        println!("  No prefixes found.");
+    println!("Importing prefix '{name}' from {}...", file.display());
+
+    if let Err(e) = crate::utils::archive::extract_tar_zst_secure(&file, &prefix_path) {
+        // Don't leave a half-extracted prefix behind
+        let _ = fs::remove_dir_all(&prefix_path);
+        return Err(e);
+    }
+
+    if !prefix_path.join("drive_c").exists() {
+        eprintln!("Warning: Imported archive does not look like a wine prefix (no drive_c)");
     }
 
+    println!("Successfully imported prefix: {name}");
     Ok(())
 }
 
-async fn remove_prefix(name: &str) -> Result<()> {
-    let dirs = CellarDirectories::new()?;
+async fn show_prefix_disk_usage(dirs: &CellarDirectories, name: &str) -> Result<()> {
     let prefix_path = dirs.get_prefixes_path().join(name);
 
     if !prefix_path.exists() {
         return Err(anyhow!("Prefix '{}' not found", name));
     }
 
-    println!("Removing prefix: {name}");
-    fs::remove_dir_all(&prefix_path)?;
-    println!("Successfully removed prefix: {name}");
+    // Break drive_c down one level further since that's where games, saves and
+    // the Windows install live
+    let mut usage = Vec::new();
+    for entry in fs::read_dir(&prefix_path)?.flatten() {
+        let entry_name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+
+        if entry_name == "drive_c" && path.is_dir() {
+            for drive_entry in fs::read_dir(&path)?.flatten() {
+                let label = format!("drive_c/{}", drive_entry.file_name().to_string_lossy());
+                usage.push((label, dir_size(&drive_entry.path())?));
+            }
+        } else {
+            usage.push((entry_name, dir_size(&path)?));
+        }
+    }
+
+    usage.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let total: u64 = usage.iter().map(|(_, size)| size).sum();
+
+    println!("Disk usage for prefix: {name}");
+    for (label, size) in &usage {
+        println!("  {:>10}  {label}", format_size(*size));
+    }
+    println!("  {:>10}  total", format_size(total));
 
     Ok(())
 }
 
-async fn run_in_prefix(prefix: &str, exe: &str, proton_version: Option<&str>) -> Result<()> {
-    let dirs = CellarDirectories::new()?;
+/// Parse `--env` entries into variable names and values
+fn parse_env_vars(entries: &[String]) -> Result<Vec<(String, String)>> {
+    entries
+        .iter()
+        .map(|entry| match entry.split_once('=') {
+            Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+            _ => Err(anyhow!(
+                "Invalid --env '{}': expected KEY=VALUE, e.g. WINEDEBUG=+seh",
+                entry
+            )),
+        })
+        .collect()
+}
+
+/// Run an executable in a prefix; `env` is applied last so it overrides Cellar's variables
+async fn run_in_prefix(
+    dirs: &CellarDirectories,
+    prefix: &str,
+    exe: &str,
+    args: &[String],
+    env: &[(String, String)],
+    proton_version: Option<&str>,
+) -> Result<()> {
     let prefix_path = dirs.get_prefixes_path().join(prefix);
 
     if !prefix_path.exists() {
@@ -1167,51 +4291,47 @@ async fn run_in_prefix(prefix: &str, exe: &str, proton_version: Option<&str>) ->
     }
 
     println!("Running {exe} in prefix {prefix}");
+    let game_id = prefix_umu_game_id(dirs, &prefix_path);
 
     if let Some(proton) = proton_version {
-        // Run using Proton via umu-run
-        println!("Using Proton version: {proton}");
-
-        let runners_path = dirs.get_runners_path();
-        let proton_manager = ProtonManager::new(runners_path);
-
         // Find the Proton installation
-        let runners = proton_manager.discover_local_runners().await?;
-        let proton_runner = runners.iter()
-            .find(|r| r.version == proton || r.name.contains(proton))
+        let proton_runner = find_game_runner(&dirs.get_runners_path(), proton)
+            .await?
             .ok_or_else(|| anyhow!("Proton version '{}' not found. Install it first with 'cellar runners install proton {}'", proton, proton))?;
 
-        let child = tokio::process::Command::new("umu-run")
-            .env("WINEARCH", "win64")
-            .env("WINEPREFIX", &prefix_path)
-            .env("PROTONPATH", &proton_runner.path)
-            .env("PROTON_VERB", "waitforexitandrun")
-            .env("GAMEID", "umu-default")
+        let mut command = if proton_runner.runner_type == RunnerType::Wine {
+            // Wine runners are run directly
+            println!("Using Wine runner: {}", proton_runner.name);
+            wine_runner_command(&proton_runner, &prefix_path)
+        } else {
+            // Run using Proton via umu-run
+            require_umu_run().await?;
+            println!("Using Proton version: {proton}");
+            let mut command = tokio::process::Command::new("umu-run");
+            command
+                .env("WINEARCH", "win64")
+                .env("WINEPREFIX", &prefix_path)
+                .env("PROTONPATH", &proton_runner.path)
+                .env("PROTON_VERB", "waitforexitandrun")
+                .env("GAMEID", &game_id);
+            command
+        };
+
+        let child = command
             .env("WINE_LARGE_ADDRESS_AWARE", "1")
+            .envs(env.iter().cloned())
             .arg(&exe_path)
+            .args(args)
             .stdout(std::process::Stdio::inherit())
             .stderr(std::process::Stdio::piped())
             .spawn()?;
 
         let output = child.wait_with_output().await?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        print_verbose_stderr(&stderr);
 
         if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            // Filter out Wine debug noise but show critical errors
-            let critical_errors: Vec<&str> = stderr
-                .lines()
-                .filter(|line| {
-                    let line_lower = line.to_lowercase();
-                    (line_lower.contains("error") || line_lower.contains("failed"))
-                        && !line.contains("fixme:")
-                        && !line.contains("err:setupapi:create_dest_file")
-                        && !line.contains("wine-staging")
-                        && !line.contains("experimental patches")
-                        && !line.contains("winediag:")
-                        && !line_lower.contains("stub")
-                        && !line.trim().is_empty()
-                })
-                .collect();
+            let critical_errors = filter_wine_errors(&stderr, is_verbose());
 
             if !critical_errors.is_empty() {
                 return Err(anyhow!(
@@ -1235,40 +4355,29 @@ async fn run_in_prefix(prefix: &str, exe: &str, proton_version: Option<&str>) ->
                     let proton_manager = ProtonManager::new(runners_path);
                     let runners = proton_manager.discover_local_runners().await?;
 
-                    if let Some(proton_runner) = runners
-                        .iter()
-                        .find(|r| r.version == version || r.name.contains(version))
+                    if let Some(proton_runner) = runners.iter().find(|r| r.matches_version(version))
                     {
+                        require_umu_run().await?;
                         let child = tokio::process::Command::new("umu-run")
                             .env("WINEARCH", "win64")
                             .env("WINEPREFIX", &prefix_path)
                             .env("PROTONPATH", &proton_runner.path)
                             .env("PROTON_VERB", "waitforexitandrun")
-                            .env("GAMEID", "umu-default")
+                            .env("GAMEID", &game_id)
                             .env("WINE_LARGE_ADDRESS_AWARE", "1")
+                            .envs(env.iter().cloned())
                             .arg(&exe_path)
+                            .args(args)
                             .stdout(std::process::Stdio::inherit())
                             .stderr(std::process::Stdio::piped())
                             .spawn()?;
 
                         let output = child.wait_with_output().await?;
+                        let stderr = String::from_utf8_lossy(&output.stderr);
+                        print_verbose_stderr(&stderr);
 
                         if !output.status.success() {
-                            let stderr = String::from_utf8_lossy(&output.stderr);
-                            let critical_errors: Vec<&str> = stderr
-                                .lines()
-                                .filter(|line| {
-                                    let line_lower = line.to_lowercase();
-                                    (line_lower.contains("error") || line_lower.contains("failed"))
-                                        && !line.contains("fixme:")
-                                        && !line.contains("err:setupapi:create_dest_file")
-                                        && !line.contains("wine-staging")
-                                        && !line.contains("experimental patches")
-                                        && !line.contains("winediag:")
-                                        && !line_lower.contains("stub")
-                                        && !line.trim().is_empty()
-                                })
-                                .collect();
+                            let critical_errors = filter_wine_errors(&stderr, is_verbose());
 
                             if !critical_errors.is_empty() {
                                 return Err(anyhow!(
@@ -1295,34 +4404,25 @@ async fn run_in_prefix(prefix: &str, exe: &str, proton_version: Option<&str>) ->
         }
 
         // Run using regular Wine
+        require_wine().await?;
         let child = tokio::process::Command::new("wine")
             .env("WINEPREFIX", &prefix_path)
             .env("WINEDEBUG", "-all,+dll,-setupapi")
             .env("WINEFSYNC", "1")
             .env("WINEESYNC", "1")
+            .envs(env.iter().cloned())
             .arg(&exe_path)
+            .args(args)
             .stdout(std::process::Stdio::inherit())
             .stderr(std::process::Stdio::piped())
             .spawn()?;
 
         let output = child.wait_with_output().await?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        print_verbose_stderr(&stderr);
 
         if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let critical_errors: Vec<&str> = stderr
-                .lines()
-                .filter(|line| {
-                    let line_lower = line.to_lowercase();
-                    (line_lower.contains("error") || line_lower.contains("failed"))
-                        && !line.contains("fixme:")
-                        && !line.contains("err:setupapi:create_dest_file")
-                        && !line.contains("wine-staging")
-                        && !line.contains("experimental patches")
-                        && !line.contains("winediag:")
-                        && !line_lower.contains("stub")
-                        && !line.trim().is_empty()
-                })
-                .collect();
+            let critical_errors = filter_wine_errors(&stderr, is_verbose());
 
             if !critical_errors.is_empty() {
                 return Err(anyhow!(
@@ -1337,8 +4437,208 @@ async fn run_in_prefix(prefix: &str, exe: &str, proton_version: Option<&str>) ->
     Ok(())
 }
 
-async fn install_dxvk_to_prefix(version: &str, prefix_name: &str) -> Result<()> {
-    let dirs = CellarDirectories::new()?;
+/// umu `GAMEID` for programs run in a prefix outside of a game launch
+///
+/// Uses the `umu_game_id` of the games in the prefix when they agree on one, so
+/// protonfixes also applies to installers and tools run there.
+fn prefix_umu_game_id(dirs: &CellarDirectories, prefix_path: &Path) -> String {
+    let game_ids: BTreeSet<String> = dirs
+        .list_game_configs()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|name| read_game_config(dirs, name).ok())
+        .map(|(config, _)| config)
+        .filter(|config| config.game.wine_prefix == prefix_path)
+        .filter_map(|config| config.game.umu_game_id)
+        .collect();
+
+    match game_ids.len() {
+        1 => game_ids.into_iter().next().unwrap(),
+        _ => DEFAULT_UMU_GAME_ID.to_string(),
+    }
+}
+
+/// Run a builtin Wine program such as `regedit` or `winecfg` in a prefix
+///
+/// Uses the Proton version recorded in the prefix through umu-run, and plain `wine`
+/// for prefixes that weren't created by an installed Proton.
+async fn run_wine_tool(dirs: &CellarDirectories, prefix: &str, tool: &str) -> Result<()> {
+    let prefix_path = dirs.get_prefixes_path().join(prefix);
+
+    if !prefix_path.exists() {
+        return Err(anyhow!("Prefix '{}' not found", prefix));
+    }
+
+    let game_id = prefix_umu_game_id(dirs, &prefix_path);
+    let mut command = match detect_prefix_proton(dirs, &prefix_path).await? {
+        Some(proton_runner) => {
+            require_umu_run().await?;
+            println!("Using Proton runner: {}", proton_runner.name);
+            let mut command = tokio::process::Command::new("umu-run");
+            command
+                .env("WINEARCH", "win64")
+                .env("WINEPREFIX", &prefix_path)
+                .env("PROTONPATH", &proton_runner.path)
+                .env("PROTON_VERB", "run")
+                .env("GAMEID", &game_id);
+            command
+        }
+        None => {
+            println!(
+                "No installed Proton version detected for prefix '{prefix}', using system Wine"
+            );
+            require_wine().await?;
+            let mut command = tokio::process::Command::new("wine");
+            command.env("WINEPREFIX", &prefix_path);
+            command
+        }
+    };
+
+    println!("Running {tool} in prefix {prefix}");
+
+    let child = command
+        .arg(tool)
+        .stdout(std::process::Stdio::inherit())
+        .stderr(std::process::Stdio::piped())
+        .spawn()?;
+
+    let output = child.wait_with_output().await?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    print_verbose_stderr(&stderr);
+
+    if !output.status.success() {
+        let critical_errors = filter_wine_errors(&stderr, is_verbose());
+
+        if !critical_errors.is_empty() {
+            return Err(anyhow!(
+                "{} failed with errors:\n{}",
+                tool,
+                critical_errors.join("\n")
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Replace Cellar with the user's shell, set up to run the prefix's Wine directly
+async fn open_prefix_shell(dirs: &CellarDirectories, prefix: &str) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+
+    let prefix_path = dirs.get_prefixes_path().join(prefix);
+
+    if !prefix_path.exists() {
+        return Err(anyhow!("Prefix '{}' not found", prefix));
+    }
+
+    let shell = std::env::var("SHELL")
+        .ok()
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or_else(|| "/bin/sh".to_string());
+    let mut command = std::process::Command::new(&shell);
+    command.env("WINEPREFIX", &prefix_path);
+
+    match detect_prefix_proton(dirs, &prefix_path).await? {
+        Some(proton_runner) => {
+            println!("Using Proton runner: {}", proton_runner.name);
+            let bin_path = proton_runner.wine_bin_dir();
+            let mut path = vec![bin_path.clone()];
+            if let Some(current) = std::env::var_os("PATH") {
+                path.extend(std::env::split_paths(&current));
+            }
+            command
+                .env("WINE", bin_path.join("wine"))
+                .env("WINESERVER", bin_path.join("wineserver"))
+                .env("PATH", std::env::join_paths(path)?);
+        }
+        None => println!(
+            "No installed Proton version detected for prefix '{prefix}', using system Wine"
+        ),
+    }
+
+    println!("Starting {shell} in prefix {prefix}; exit the shell to leave it");
+    println!("  WINEPREFIX={}", prefix_path.display());
+
+    // Only returns if the shell couldn't be started
+    let error = command.exec();
+    Err(anyhow!("Failed to start {}: {}", shell, error))
+}
+
+async fn run_winetricks(dirs: &CellarDirectories, prefix: &str, verbs: &[String]) -> Result<()> {
+    let prefix_path = dirs.get_prefixes_path().join(prefix);
+
+    if !prefix_path.exists() {
+        return Err(anyhow!("Prefix '{}' not found", prefix));
+    }
+
+    if !command_available("winetricks").await {
+        return Err(anyhow!(
+            "winetricks not found. Please install the winetricks package (e.g., 'sudo apt install winetricks' on Ubuntu)"
+        ));
+    }
+
+    let mut command = tokio::process::Command::new("winetricks");
+    command.env("WINEPREFIX", &prefix_path);
+
+    // Point winetricks at the Proton build that created the prefix so it doesn't
+    // upgrade the prefix with a mismatched system Wine
+    match detect_prefix_proton(dirs, &prefix_path).await? {
+        Some(proton_runner) => {
+            println!("Using Proton runner: {}", proton_runner.name);
+            let bin_path = proton_runner.wine_bin_dir();
+            command
+                .env("WINE", bin_path.join("wine"))
+                .env("WINESERVER", bin_path.join("wineserver"));
+        }
+        None => println!(
+            "No installed Proton version detected for prefix '{prefix}', using system Wine"
+        ),
+    }
+
+    println!("Running winetricks {} in prefix {prefix}", verbs.join(" "));
+
+    let status = command
+        .args(verbs)
+        .stdout(std::process::Stdio::inherit())
+        .stderr(std::process::Stdio::inherit())
+        .status()
+        .await?;
+
+    if !status.success() {
+        return Err(anyhow!("winetricks failed with {}", status));
+    }
+
+    println!("Winetricks completed.");
+    Ok(())
+}
+
+/// Resolve the installed Proton runner recorded in a prefix's version file
+async fn detect_prefix_proton(
+    dirs: &CellarDirectories,
+    prefix_path: &Path,
+) -> Result<Option<Runner>> {
+    let version_file = prefix_path.join("version");
+    let version_content = match fs::read_to_string(&version_file) {
+        Ok(content) => content,
+        Err(_) => return Ok(None),
+    };
+
+    let version = version_content.trim();
+    if version.is_empty() {
+        return Ok(None);
+    }
+
+    let proton_manager = ProtonManager::new(dirs.get_runners_path());
+    let runners = proton_manager.discover_local_runners().await?;
+
+    Ok(runners.into_iter().find(|r| r.matches_version(version)))
+}
+
+async fn install_dxvk_to_prefix(
+    dirs: &CellarDirectories,
+    version: &str,
+    prefix_name: &str,
+) -> Result<()> {
     let prefix_path = dirs.get_prefixes_path().join(prefix_name);
 
     if !prefix_path.exists() {
@@ -1351,7 +4651,7 @@ async fn install_dxvk_to_prefix(version: &str, prefix_name: &str) -> Result<()>
     // Find the DXVK installation
     let runners = dxvk_manager.discover_local_runners().await?;
     let dxvk_runner = runners.iter()
-        .find(|r| r.version == version || r.name.contains(version))
+        .find(|r| r.matches_version(version))
         .ok_or_else(|| anyhow!("DXVK version '{}' not found. Install it first with 'cellar runners install dxvk {}'", version, version))?;
 
     println!("Installing DXVK {version} to prefix '{prefix_name}'...");
@@ -1366,41 +4666,103 @@ async fn install_dxvk_to_prefix(version: &str, prefix_name: &str) -> Result<()>
     Ok(())
 }
 
+async fn uninstall_dxvk_from_prefix(dirs: &CellarDirectories, prefix_name: &str) -> Result<()> {
+    let prefix_path = dirs.get_prefixes_path().join(prefix_name);
+
+    if !prefix_path.exists() {
+        return Err(anyhow!("Prefix '{}' not found", prefix_name));
+    }
+
+    let proton_runner = detect_prefix_proton(dirs, &prefix_path).await?;
+    if proton_runner.is_none() {
+        println!("No installed Proton version detected for prefix '{prefix_name}', restoring only backed-up DLLs");
+    }
+
+    let dxvk_manager = DxvkManager::new(dirs.get_runners_path());
+    let kept = dxvk_manager
+        .uninstall_dxvk_from_prefix(
+            &prefix_path,
+            proton_runner.as_ref().map(|r| r.path.as_path()),
+        )
+        .await?;
+
+    if !kept.is_empty() {
+        return Err(anyhow!(
+            "No backup or builtin replacement found for: {}. They were left in place; install the prefix's Proton version and try again",
+            kept.join(", ")
+        ));
+    }
+
+    println!("Removed DXVK from prefix '{prefix_name}'");
+
+    Ok(())
+}
+
 // Shortcut management functions
-pub async fn handle_shortcut_command(command: ShortcutCommands) -> Result<()> {
+pub async fn handle_shortcut_command(
+    dirs: &CellarDirectories,
+    command: ShortcutCommands,
+) -> Result<()> {
     match command {
-        ShortcutCommands::Create { name } => create_shortcut(&name).await,
-        ShortcutCommands::Remove { name } => remove_shortcut(&name).await,
-        ShortcutCommands::Sync => sync_shortcuts().await,
-        ShortcutCommands::List => list_shortcuts().await,
-        ShortcutCommands::ExtractIcon { name } => extract_icon(&name).await,
-        ShortcutCommands::ListIcons => list_icons().await,
+        ShortcutCommands::Create { name } => {
+            create_shortcut(dirs, &resolve_game_name(dirs, &name)?).await
+        }
+        ShortcutCommands::Remove { name } => {
+            remove_shortcut(dirs, &resolve_game_name(dirs, &name)?).await
+        }
+        ShortcutCommands::Sync { force_icons } => sync_shortcuts(dirs, force_icons).await,
+        ShortcutCommands::List => list_shortcuts(dirs).await,
+        ShortcutCommands::ExtractIcon { name } => {
+            extract_icon(dirs, &resolve_game_name(dirs, &name)?).await
+        }
+        ShortcutCommands::SetIcon { name, path } => {
+            set_icon(dirs, &resolve_game_name(dirs, &name)?, &path).await
+        }
+        ShortcutCommands::ListIcons => list_icons(dirs).await,
+    }
+}
+
+async fn set_icon(dirs: &CellarDirectories, game_name: &str, image_path: &Path) -> Result<()> {
+    let mut config = load_game_config(dirs, game_name)?;
+
+    if !image_path.is_file() {
+        return Err(anyhow!("Image not found: {}", image_path.display()));
     }
+
+    let icon_path = desktop::import_custom_icon(dirs, image_path, &config.game.name).await?;
+    println!("Converted icon to {}", icon_path.display());
+
+    config.desktop.icon_path = Some(icon_path);
+    save_game_config(dirs, &config)?;
+
+    let config_name = dirs.get_game_file_stem(game_name);
+    desktop::create_desktop_shortcut(dirs, &config, &config_name).await?;
+
+    Ok(())
 }
 
-async fn create_shortcut(game_name: &str) -> Result<()> {
-    let dirs = CellarDirectories::new()?;
-    let config = load_game_config(&dirs, game_name)?;
+async fn create_shortcut(dirs: &CellarDirectories, game_name: &str) -> Result<()> {
+    let config = load_game_config(dirs, game_name)?;
 
-    // Use the sanitized game name (config filename) for the exec command
-    let config_name = sanitize_filename(game_name);
-    desktop::create_desktop_shortcut(&config, &config_name).await?;
+    // Use the config name (config filename) for the exec command
+    let config_name = dirs.get_game_file_stem(game_name);
+    desktop::create_desktop_shortcut(dirs, &config, &config_name).await?;
 
     Ok(())
 }
 
-async fn remove_shortcut(game_name: &str) -> Result<()> {
-    desktop::remove_desktop_shortcut(game_name)?;
+async fn remove_shortcut(dirs: &CellarDirectories, game_name: &str) -> Result<()> {
+    desktop::remove_desktop_shortcut(dirs, game_name)?;
     Ok(())
 }
 
-async fn sync_shortcuts() -> Result<()> {
-    desktop::sync_desktop_shortcuts().await?;
+async fn sync_shortcuts(dirs: &CellarDirectories, force_icons: bool) -> Result<()> {
+    desktop::sync_desktop_shortcuts(dirs, force_icons).await?;
     Ok(())
 }
 
-async fn list_shortcuts() -> Result<()> {
-    let shortcuts = desktop::list_desktop_shortcuts()?;
+async fn list_shortcuts(dirs: &CellarDirectories) -> Result<()> {
+    let shortcuts = desktop::list_desktop_shortcuts(dirs)?;
 
     if shortcuts.is_empty() {
         println!("No desktop shortcuts found.");
@@ -1414,11 +4776,10 @@ async fn list_shortcuts() -> Result<()> {
     Ok(())
 }
 
-async fn extract_icon(game_name: &str) -> Result<()> {
-    let dirs = CellarDirectories::new()?;
-    let config = load_game_config(&dirs, game_name)?;
+async fn extract_icon(dirs: &CellarDirectories, game_name: &str) -> Result<()> {
+    let config = load_game_config(dirs, game_name)?;
 
-    match desktop::get_or_extract_icon(&config.game.executable, &config.game.name).await {
+    match desktop::get_or_extract_icon(dirs, &config.game.executable, &config.game.name).await {
         Ok(Some(icon_path)) => {
             println!(
                 "Successfully extracted icon for {} to {}",
@@ -1440,8 +4801,8 @@ async fn extract_icon(game_name: &str) -> Result<()> {
     Ok(())
 }
 
-async fn list_icons() -> Result<()> {
-    let icons = desktop::list_game_icons()?;
+async fn list_icons(dirs: &CellarDirectories) -> Result<()> {
+    let icons = desktop::list_game_icons(dirs)?;
 
     if icons.is_empty() {
         println!("No extracted icons found.");
@@ -1464,18 +4825,23 @@ mod tests {
     fn test_basic_config_loading() {
         // Test that we can create game configs
         let config = GameConfig {
+            version: CURRENT_CONFIG_VERSION,
             game: GameInfo {
                 name: "Test Game".to_string(),
                 executable: PathBuf::from("/tmp/test.exe"),
                 wine_prefix: PathBuf::from("/tmp/prefix"),
                 proton_version: "GE-Proton8-32".to_string(),
                 dxvk_version: None,
+                umu_game_id: None,
+                native: false,
             },
             launch: LaunchConfig::default(),
             wine_config: WineConfig::default(),
             dxvk: crate::config::game::DxvkConfig::default(),
+            mangohud: crate::config::game::MangohudConfig::default(),
             gamescope: GamescopeConfig::default(),
             desktop: DesktopConfig::default(),
+            stats: GameStats::default(),
             installation: None,
         };
 
@@ -1485,14 +4851,293 @@ mod tests {
     }
 
     #[test]
-    fn test_version_extraction() {
-        // Test the extract_version_number function for proper version comparison
-        assert_eq!(extract_version_number("GE-Proton9-1"), 9.01);
-        assert_eq!(extract_version_number("GE-Proton10-10"), 10.10);
-        assert_eq!(extract_version_number("GE-Proton8-32"), 8.32);
-
-        // Test fallback for non-standard versions
-        assert_eq!(extract_version_number("some-version-5"), 5.0);
-        assert_eq!(extract_version_number("no-numbers"), 0.0);
+    fn test_games_csv_quotes_names() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dirs = CellarDirectories::new(Some(temp_dir.path())).unwrap();
+        dirs.ensure_all_exist().unwrap();
+
+        let config = new_game_config(
+            "Game, The Sequel",
+            PathBuf::from("/tmp/game.exe"),
+            PathBuf::from("/tmp/prefix"),
+            "GE-Proton9-1".to_string(),
+        );
+        save_game_config(&dirs, &config).unwrap();
+
+        let mut out = Vec::new();
+        write_games_csv(&dirs, None, ListSort::Name, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = out.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            "name,proton_version,dxvk_version,prefix,executable,gamescope,last_played,playtime"
+        );
+        assert_eq!(
+            lines[1],
+            "\"Game, The Sequel\",GE-Proton9-1,,/tmp/prefix,/tmp/game.exe,false,,0"
+        );
+    }
+
+    #[test]
+    fn test_parse_env_vars() {
+        let entries = vec!["WINEDEBUG=+seh".to_string(), "DXVK_HUD=fps=1".to_string()];
+        assert_eq!(
+            parse_env_vars(&entries).unwrap(),
+            vec![
+                ("WINEDEBUG".to_string(), "+seh".to_string()),
+                ("DXVK_HUD".to_string(), "fps=1".to_string()),
+            ]
+        );
+
+        let err = parse_env_vars(&["WINEDEBUG".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("expected KEY=VALUE"));
+        assert!(parse_env_vars(&["=1".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_flag_override() {
+        assert_eq!(flag_override(false, false), None);
+        assert_eq!(flag_override(true, false), Some(true));
+        assert_eq!(flag_override(false, true), Some(false));
+    }
+
+    #[test]
+    fn test_resolve_game_name() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dirs = CellarDirectories::new(Some(temp_dir.path())).unwrap();
+        dirs.ensure_all_exist().unwrap();
+
+        for name in ["Portal 2", "Half-Life 2", "Half-Life: Alyx"] {
+            let config = new_game_config(
+                name,
+                PathBuf::from("/tmp/game.exe"),
+                PathBuf::from("/tmp/prefix"),
+                "GE-Proton9-1".to_string(),
+            );
+            save_game_config(&dirs, &config).unwrap();
+        }
+
+        assert_eq!(resolve_game_name(&dirs, "Portal 2").unwrap(), "Portal 2");
+        assert_eq!(resolve_game_name(&dirs, "portal").unwrap(), "portal_2");
+        assert_eq!(resolve_game_name(&dirs, "ALYX").unwrap(), "half-life_alyx");
+
+        let err = resolve_game_name(&dirs, "half-life").unwrap_err();
+        assert!(err.to_string().contains("Half-Life 2"));
+        assert!(err.to_string().contains("Half-Life: Alyx"));
+
+        assert_eq!(resolve_game_name(&dirs, "quake").unwrap(), "quake");
+    }
+
+    #[test]
+    fn test_colliding_config_names_get_suffix() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dirs = CellarDirectories::new(Some(temp_dir.path())).unwrap();
+        dirs.ensure_all_exist().unwrap();
+
+        for (name, exe) in [
+            ("Game: One", "/tmp/one.exe"),
+            ("Game? One", "/tmp/other.exe"),
+        ] {
+            let config = new_game_config(
+                name,
+                PathBuf::from(exe),
+                PathBuf::from("/tmp/prefix"),
+                "GE-Proton9-1".to_string(),
+            );
+            save_game_config(&dirs, &config).unwrap();
+        }
+
+        assert_eq!(
+            dirs.list_game_configs().unwrap(),
+            vec!["game_one".to_string(), "game_one-2".to_string()]
+        );
+        assert_eq!(dirs.get_game_file_stem("Game? One"), "game_one-2");
+
+        let first = load_game_config(&dirs, "Game: One").unwrap();
+        let second = load_game_config(&dirs, "Game? One").unwrap();
+        assert_eq!(first.game.executable, PathBuf::from("/tmp/one.exe"));
+        assert_eq!(second.game.executable, PathBuf::from("/tmp/other.exe"));
+        assert_eq!(
+            load_game_config(&dirs, "game_one-2").unwrap().game.name,
+            "Game? One"
+        );
+
+        // Saving again updates the existing file instead of taking a new suffix
+        save_game_config(&dirs, &second).unwrap();
+        assert_eq!(dirs.list_game_configs().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_games_csv_filters_by_proton() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dirs = CellarDirectories::new(Some(temp_dir.path())).unwrap();
+        dirs.ensure_all_exist().unwrap();
+
+        for (name, proton) in [("Old", "GE-Proton8-32"), ("New", "GE-Proton9-1")] {
+            let config = new_game_config(
+                name,
+                PathBuf::from("/tmp/game.exe"),
+                PathBuf::from("/tmp/prefix"),
+                proton.to_string(),
+            );
+            save_game_config(&dirs, &config).unwrap();
+        }
+
+        let mut out = Vec::new();
+        write_games_csv(&dirs, Some("Proton8"), ListSort::Name, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let names: Vec<_> = out.lines().skip(1).map(|l| l.split(',').next()).collect();
+
+        assert_eq!(names, vec![Some("Old")]);
+    }
+
+    #[test]
+    fn test_sort_games_by_last_played() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dirs = CellarDirectories::new(Some(temp_dir.path())).unwrap();
+        dirs.ensure_all_exist().unwrap();
+
+        let now = chrono::Utc::now();
+        for (name, last_played) in [
+            ("a", None),
+            ("b", Some(now - chrono::Duration::days(2))),
+            ("c", Some(now)),
+        ] {
+            let mut config = new_game_config(
+                name,
+                PathBuf::from("/tmp/game.exe"),
+                PathBuf::from("/tmp/prefix"),
+                "GE-Proton9-1".to_string(),
+            );
+            config.stats.last_played = last_played;
+            save_game_config(&dirs, &config).unwrap();
+        }
+
+        let names = |sort| -> Vec<String> {
+            load_sorted_game_configs(&dirs, sort)
+                .unwrap()
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect()
+        };
+        assert_eq!(names(ListSort::Name), vec!["a", "b", "c"]);
+        assert_eq!(names(ListSort::Recent), vec!["c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_format_playtime() {
+        assert_eq!(format_playtime(0), "0m");
+        assert_eq!(format_playtime(59 * 60 + 59), "59m");
+        assert_eq!(format_playtime(3 * 3600 + 24 * 60 + 5), "3h 24m");
+    }
+
+    #[test]
+    fn test_parse_open_file_limit() {
+        let limits = "Limit                     Soft Limit           Hard Limit           Units     \n\
+                      Max open files            1024                 524288               files     \n";
+        assert_eq!(parse_open_file_limit(limits), Some(524288));
+        assert_eq!(
+            parse_open_file_limit(
+                "Max open files            unlimited            unlimited            files"
+            ),
+            Some(u64::MAX)
+        );
+        assert_eq!(parse_open_file_limit("Max processes 10 10 processes"), None);
+    }
+
+    #[test]
+    fn test_parse_kernel_version() {
+        assert_eq!(parse_kernel_version("6.8.0-45-generic\n"), Some((6, 8)));
+        assert_eq!(parse_kernel_version("5.15.167.4-microsoft"), Some((5, 15)));
+        assert_eq!(parse_kernel_version("6"), None);
+    }
+
+    #[test]
+    fn test_remove_all_keeps_pinned_runners() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dirs = CellarDirectories::new(Some(temp_dir.path())).unwrap();
+        dirs.ensure_all_exist().unwrap();
+
+        let runner = |name: &str| Runner {
+            name: name.to_string(),
+            version: name.to_string(),
+            path: dirs.get_runners_path().join("proton").join(name),
+            runner_type: RunnerType::Proton,
+            installed: true,
+            pinned: false,
+            other_paths: Vec::new(),
+        };
+        let mut pins = RunnerPins::default();
+        pins.set_pinned(&runner("GE-Proton9-20"), true);
+        fs::write(dirs.get_runner_pins_path(), toml::to_string(&pins).unwrap()).unwrap();
+
+        let runners = vec![runner("GE-Proton9-19"), runner("GE-Proton9-20")];
+        let removed: Vec<String> = without_pinned(runners, &load_runner_pins(&dirs))
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        assert_eq!(removed, vec!["GE-Proton9-19".to_string()]);
+    }
+
+    #[test]
+    fn test_recreating_prefix_stays_in_prefixes_dir() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dirs = CellarDirectories::new(Some(temp_dir.path())).unwrap();
+        dirs.ensure_all_exist().unwrap();
+        let outside = temp_dir.path().join("outside");
+        fs::create_dir_all(&outside).unwrap();
+
+        assert!(delete_prefix_for_recreation(&dirs, "../outside").is_err());
+        assert!(delete_prefix_for_recreation(&dirs, ".").is_err());
+        assert!(outside.exists());
+        assert!(dirs.get_prefixes_path().exists());
+    }
+
+    #[test]
+    fn test_find_install_leftovers() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let tmp = temp_dir.path().join("tmp");
+        let downloads = temp_dir.path().join("downloads");
+        let runners = temp_dir.path().join("runners");
+        fs::create_dir_all(runners.join("proton/.GE-Proton9-20.partial")).unwrap();
+        fs::create_dir_all(downloads.join(format!("installer-{}", std::process::id()))).unwrap();
+        fs::write(downloads.join("GE-Proton9-21.tar.gz"), "").unwrap();
+        fs::create_dir_all(runners.join("proton/GE-Proton9-19")).unwrap();
+        fs::create_dir_all(tmp.join(format!("cellar-installer-{}", std::process::id()))).unwrap();
+        fs::create_dir_all(tmp.join(format!("cellar-installer-{}", u32::MAX))).unwrap();
+        for name in [
+            "GE-Proton9-20.tar.gz.part",
+            "dxvk-2.6.tar.gz",
+            "wine-lutris-GE-Proton8-26-x86_64.tar.xz",
+            "backup.tar.gz",
+            "GE-Proton-notes.txt",
+        ] {
+            fs::write(tmp.join(name), "").unwrap();
+        }
+
+        let names: Vec<String> =
+            find_install_leftovers(&tmp, &downloads, &runners, std::time::Duration::ZERO)
+                .iter()
+                .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+                .collect();
+        assert_eq!(
+            names,
+            vec![
+                "GE-Proton9-21.tar.gz".to_string(),
+                ".GE-Proton9-20.partial".to_string(),
+                "GE-Proton9-20.tar.gz.part".to_string(),
+                format!("cellar-installer-{}", u32::MAX),
+                "dxvk-2.6.tar.gz".to_string(),
+                "wine-lutris-GE-Proton8-26-x86_64.tar.xz".to_string(),
+            ]
+        );
+
+        // Everything was just written, as if an install were still running
+        let active_window = std::time::Duration::from_secs(3600);
+        assert_eq!(
+            find_install_leftovers(&tmp, &downloads, &runners, active_window),
+            vec![tmp.join(format!("cellar-installer-{}", u32::MAX))]
+        );
     }
 }
@@ -0,0 +1,120 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::game::{GameConfig, LaunchConfig, WineConfig};
+
+/// Global defaults for newly added games, stored in Cellar's `config.toml`
+///
+/// The sections mirror a game config, so `launch` and `wine_config` accept the
+/// same keys; anything left out keeps Cellar's built-in default. The `paths`
+/// section holds Cellar-wide directory settings rather than game defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CellarDefaults {
+    #[serde(default)]
+    pub game: GameDefaults,
+    #[serde(default)]
+    pub launch: LaunchConfig,
+    #[serde(default)]
+    pub wine_config: WineConfig,
+    #[serde(default, skip_serializing_if = "PathSettings::is_empty")]
+    pub paths: PathSettings,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PathSettings {
+    /// Where runners are installed instead of `<data dir>/runners`;
+    /// `CELLAR_RUNNERS_DIR` takes precedence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runners_dir: Option<PathBuf>,
+}
+
+impl PathSettings {
+    fn is_empty(&self) -> bool {
+        self.runners_dir.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GameDefaults {
+    /// Proton version used when `add` is not given `--proton`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proton_version: Option<String>,
+}
+
+impl CellarDefaults {
+    /// Read the defaults file, falling back to built-in defaults when it doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        toml::from_str(&content).map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| anyhow!("Failed to serialize defaults: {}", e))?;
+        fs::write(path, content)
+            .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?;
+        Ok(())
+    }
+
+    /// Seed a new game's launch and Wine settings from these defaults
+    pub fn apply(&self, config: &mut GameConfig) {
+        config.launch = self.launch.clone();
+        config.wine_config = self.wine_config.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_defaults_keep_builtin_values() {
+        let defaults: CellarDefaults = toml::from_str(
+            r#"
+[game]
+proton_version = "GE-Proton9-20"
+
+[launch]
+gamemode = true
+
+[wine_config]
+fsync = false
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            defaults.game.proton_version.as_deref(),
+            Some("GE-Proton9-20")
+        );
+        assert!(defaults.launch.gamemode);
+        assert!(!defaults.launch.mangohud);
+        assert!(!defaults.wine_config.fsync);
+        assert!(defaults.wine_config.esync);
+        assert_eq!(defaults.wine_config.wineserver_kill_timeout, 5);
+    }
+
+    #[test]
+    fn test_missing_defaults_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+
+        let defaults = CellarDefaults::load(&path).unwrap();
+        assert!(defaults.game.proton_version.is_none());
+        assert!(defaults.wine_config.dxvk);
+
+        defaults.save(&path).unwrap();
+        assert!(CellarDefaults::load(&path).is_ok());
+    }
+}
@@ -1,17 +1,33 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+/// Schema version written to game configs; older files are upgraded on load by
+/// `config::migrate::migrate_config`
+pub const CURRENT_CONFIG_VERSION: u32 = 2;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameConfig {
+    /// Files written before the field existed are version 1
+    #[serde(default = "legacy_config_version")]
+    pub version: u32,
     pub game: GameInfo,
     pub launch: LaunchConfig,
     pub wine_config: WineConfig,
     #[serde(default)]
     pub dxvk: DxvkConfig,
     #[serde(default)]
+    pub mangohud: MangohudConfig,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_gamescope",
+        serialize_with = "serialize_gamescope"
+    )]
     pub gamescope: GamescopeConfig,
     #[serde(default)]
     pub desktop: DesktopConfig,
+    #[serde(default)]
+    pub stats: GameStats,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub installation: Option<InstallationInfo>,
@@ -25,6 +41,13 @@ pub struct GameInfo {
     pub proton_version: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dxvk_version: Option<String>,
+    /// umu `GAMEID`, e.g. "umu-1091500", so protonfixes applies this game's fixes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub umu_game_id: Option<String>,
+    /// Native Linux program, run directly rather than through Proton or Wine;
+    /// `wine_prefix` and `proton_version` are not used
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub native: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -37,6 +60,26 @@ pub struct LaunchConfig {
     pub gamemode: bool,
     #[serde(default)]
     pub mangohud: bool,
+    /// Extra environment variables for the game; these take precedence over
+    /// every variable Cellar sets itself (Wine, Proton and DXVK defaults)
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Shell commands run before the game starts; a failure aborts the launch
+    #[serde(default)]
+    pub pre_launch: Vec<String>,
+    /// Shell commands run after the game exits, whatever its exit status
+    #[serde(default)]
+    pub post_launch: Vec<String>,
+    /// Directory the game is started in; by default the prefix, or the executable's
+    /// directory for native games
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<PathBuf>,
+    /// Allow any `-`/`--` option in `launch_options`, not just Cellar's allowlist,
+    /// for games that need e.g. `-dx12`. Shell metacharacters are still rejected,
+    /// but an option can make the game or a wrapper do anything it supports.
+    /// Deliberately only settable by editing the config file.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unsafe_launch_options: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,18 +96,48 @@ pub struct WineConfig {
     pub large_address_aware: bool,
     #[serde(default = "default_wineserver_timeout")]
     pub wineserver_kill_timeout: u32,
+    /// Replaces the computed `WINEDLLOVERRIDES` value when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dll_overrides: Option<String>,
+    /// Proton/Wine-GE fullscreen FSR, used when the game runs below the display
+    /// resolution; independent of gamescope's upscaling
+    #[serde(default)]
+    pub proton_fsr: bool,
+    /// FSR sharpening strength, 0 (sharpest) to 5; Proton defaults to 2
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proton_fsr_strength: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct DxvkConfig {
     #[serde(default)]
     pub hud: String,
+    /// Keep the DXVK state cache in Cellar's cache directory instead of the prefix,
+    /// so it survives prefix resets and reinstalls
+    #[serde(default)]
+    pub shared_shader_cache: bool,
+}
+
+/// MangoHud overlay settings, used when `launch.mangohud` is enabled
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MangohudConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fps_limit: Option<u32>,
+    /// Overlay position, e.g. "top-left" or "bottom-right"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<String>,
+    /// Extra comma-separated MangoHud options appended verbatim, e.g. "cpu_temp,gpu_temp"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_string: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GamescopeConfig {
     #[serde(default)]
     pub enabled: bool,
+    /// Named preset (see `config::presets`) supplying the fields not set explicitly
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
     #[serde(default = "default_width")]
     pub width: u32,
     #[serde(default = "default_height")]
@@ -89,6 +162,10 @@ pub struct GamescopeConfig {
     pub adaptive_sync: bool,
     #[serde(default)]
     pub immediate_flips: bool,
+    /// Preset fields that were set explicitly; only these are saved with the
+    /// preset, so the others keep following it
+    #[serde(skip)]
+    pub preset_overrides: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -105,6 +182,17 @@ pub struct DesktopConfig {
     pub comment: String,
 }
 
+/// Play statistics recorded by Cellar each time the game is launched
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GameStats {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_played: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub play_count: u32,
+    #[serde(default)]
+    pub total_playtime_secs: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstallationInfo {
     pub installer_path: PathBuf,
@@ -112,7 +200,65 @@ pub struct InstallationInfo {
     pub install_location: String,
 }
 
+/// Deserialize `[gamescope]`, layering the fields that are set over the chosen preset
+fn deserialize_gamescope<'de, D>(deserializer: D) -> Result<GamescopeConfig, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    let table = toml::Table::deserialize(deserializer)?;
+    let Some(preset_name) = table.get("preset") else {
+        return table.try_into().map_err(D::Error::custom);
+    };
+
+    let preset_name = preset_name
+        .as_str()
+        .ok_or_else(|| D::Error::custom("gamescope.preset must be a string"))?;
+    let preset = super::presets::gamescope_preset(preset_name).ok_or_else(|| {
+        D::Error::custom(format!(
+            "Unknown gamescope preset '{}'. Must be one of: {}",
+            preset_name,
+            super::presets::GAMESCOPE_PRESETS.join(", ")
+        ))
+    })?;
+
+    let preset_overrides = super::presets::PRESET_FIELDS
+        .iter()
+        .filter(|field| table.contains_key(**field))
+        .map(|field| field.to_string())
+        .collect();
+
+    let mut merged = toml::Table::try_from(preset).map_err(D::Error::custom)?;
+    merged.extend(table);
+    let mut config: GamescopeConfig = merged.try_into().map_err(D::Error::custom)?;
+    config.preset_overrides = preset_overrides;
+    Ok(config)
+}
+
+/// Serialize `[gamescope]` without the fields its preset fills in, unless they
+/// were set explicitly, so changing the preset later still takes effect
+fn serialize_gamescope<S>(config: &GamescopeConfig, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    use serde::ser::Error;
+
+    let mut table = toml::Table::try_from(config).map_err(S::Error::custom)?;
+    if config.preset.is_some() {
+        table.retain(|key, _| {
+            !super::presets::PRESET_FIELDS.contains(&key)
+                || config.preset_overrides.iter().any(|field| field == key)
+        });
+    }
+    table.serialize(serializer)
+}
+
 // Default value functions
+fn legacy_config_version() -> u32 {
+    1
+}
+
 fn default_true() -> bool {
     true
 }
@@ -166,6 +312,9 @@ impl Default for WineConfig {
             dxvk_async: true,
             large_address_aware: false,
             wineserver_kill_timeout: 5,
+            dll_overrides: None,
+            proton_fsr: false,
+            proton_fsr_strength: None,
         }
     }
 }
@@ -174,6 +323,7 @@ impl Default for GamescopeConfig {
     fn default() -> Self {
         Self {
             enabled: false,
+            preset: None,
             width: 1920,
             height: 1080,
             output_width: 1920,
@@ -186,6 +336,7 @@ impl Default for GamescopeConfig {
             hdr: false,
             adaptive_sync: false,
             immediate_flips: false,
+            preset_overrides: Vec::new(),
         }
     }
 }
@@ -209,18 +360,23 @@ mod tests {
     #[test]
     fn test_game_config_basic_functionality() {
         let config = GameConfig {
+            version: CURRENT_CONFIG_VERSION,
             game: GameInfo {
                 name: "Test Game".to_string(),
                 executable: std::path::PathBuf::from("/path/to/game.exe"),
                 wine_prefix: std::path::PathBuf::from("/path/to/prefix"),
                 proton_version: "GE-Proton8-32".to_string(),
                 dxvk_version: None,
+                umu_game_id: None,
+                native: false,
             },
             launch: LaunchConfig::default(),
             wine_config: WineConfig::default(),
             dxvk: DxvkConfig::default(),
+            mangohud: MangohudConfig::default(),
             gamescope: GamescopeConfig::default(),
             desktop: DesktopConfig::default(),
+            stats: GameStats::default(),
             installation: None,
         };
 
@@ -234,18 +390,23 @@ mod tests {
     #[test]
     fn test_game_config_serialization() {
         let config = GameConfig {
+            version: CURRENT_CONFIG_VERSION,
             game: GameInfo {
                 name: "Test Game".to_string(),
                 executable: std::path::PathBuf::from("/path/to/game.exe"),
                 wine_prefix: std::path::PathBuf::from("/path/to/prefix"),
                 proton_version: "GE-Proton8-32".to_string(),
                 dxvk_version: None,
+                umu_game_id: None,
+                native: false,
             },
             launch: LaunchConfig::default(),
             wine_config: WineConfig::default(),
             dxvk: DxvkConfig::default(),
+            mangohud: MangohudConfig::default(),
             gamescope: GamescopeConfig::default(),
             desktop: DesktopConfig::default(),
+            stats: GameStats::default(),
             installation: None,
         };
 
@@ -285,6 +446,96 @@ wineserver_kill_timeout = 5
         assert_eq!(config.game.name, "Test Game");
         assert_eq!(config.game.proton_version, "GE-Proton8-32");
         assert_eq!(config.launch.game_args, vec!["--windowed"]);
+        assert!(config.launch.env.is_empty());
+        assert!(config.launch.pre_launch.is_empty());
         assert!(config.wine_config.esync);
     }
+
+    #[test]
+    fn test_launch_hooks() {
+        let toml_string = r#"
+[game]
+name = "Test Game"
+executable = "/path/to/game.exe"
+wine_prefix = "/path/to/prefix"
+proton_version = "GE-Proton8-32"
+
+[launch]
+pre_launch = ["mount-ramdisk", "echo starting"]
+post_launch = ["umount-ramdisk"]
+
+[wine_config]
+"#;
+
+        let config: GameConfig = toml::from_str(toml_string).unwrap();
+        assert_eq!(
+            config.launch.pre_launch,
+            vec!["mount-ramdisk", "echo starting"]
+        );
+        assert_eq!(config.launch.post_launch, vec!["umount-ramdisk"]);
+    }
+
+    #[test]
+    fn test_launch_env_round_trip() {
+        let toml_string = r#"
+[game]
+name = "Test Game"
+executable = "/path/to/game.exe"
+wine_prefix = "/path/to/prefix"
+proton_version = "GE-Proton8-32"
+
+[launch.env]
+PROTON_NO_ESYNC = "1"
+VKD3D_CONFIG = "dxr"
+
+[wine_config]
+"#;
+
+        let config: GameConfig = toml::from_str(toml_string).unwrap();
+        assert_eq!(config.launch.env.get("PROTON_NO_ESYNC").unwrap(), "1");
+        assert_eq!(config.launch.env.get("VKD3D_CONFIG").unwrap(), "dxr");
+
+        let serialized = toml::to_string(&config).unwrap();
+        let reparsed: GameConfig = toml::from_str(&serialized).unwrap();
+        assert_eq!(reparsed.launch.env, config.launch.env);
+    }
+
+    #[test]
+    fn test_gamescope_preset_with_overrides() {
+        let toml_string = r#"
+[game]
+name = "Test Game"
+executable = "/path/to/game.exe"
+wine_prefix = "/path/to/prefix"
+proton_version = "GE-Proton8-32"
+
+[launch]
+
+[wine_config]
+
+[gamescope]
+enabled = true
+preset = "steamdeck"
+refresh_rate = 90
+"#;
+
+        let config: GameConfig = toml::from_str(toml_string).unwrap();
+        assert!(config.gamescope.enabled);
+        assert_eq!(config.gamescope.preset.as_deref(), Some("steamdeck"));
+        assert_eq!(config.gamescope.width, 1280);
+        assert_eq!(config.gamescope.output_height, 800);
+        assert_eq!(config.gamescope.refresh_rate, 90);
+
+        // Only the explicit refresh rate is saved, so editing the preset applies it
+        let saved = toml::to_string(&config).unwrap();
+        assert!(saved.contains("refresh_rate = 90"));
+        assert!(!saved.contains("width"));
+        let edited: GameConfig = toml::from_str(&saved.replace("steamdeck", "1080p60")).unwrap();
+        assert_eq!(edited.gamescope.width, 1920);
+        assert_eq!(edited.gamescope.refresh_rate, 90);
+
+        let unknown = toml_string.replace("steamdeck", "8k");
+        let err = toml::from_str::<GameConfig>(&unknown).unwrap_err();
+        assert!(err.to_string().contains("Unknown gamescope preset '8k'"));
+    }
 }
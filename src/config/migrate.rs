@@ -0,0 +1,272 @@
+use anyhow::Result;
+use std::borrow::Cow;
+use std::fs;
+use toml::{Table, Value};
+
+use super::game::{GameConfig, GamescopeConfig, CURRENT_CONFIG_VERSION};
+use crate::desktop::create_desktop_shortcut;
+use crate::utils::fs::{sanitize_filename, CellarDirectories};
+
+/// Parse a game config file's contents, upgrading older schema versions first
+///
+/// The file itself is left as it is; the upgraded shape is written the next time
+/// the config is saved.
+pub fn parse_game_config(content: &str) -> Result<GameConfig> {
+    Ok(parse_game_config_with_unknown_keys(content)?.0)
+}
+
+/// Like `parse_game_config`, also returning the dotted paths of keys Cellar doesn't
+/// know, such as a misspelled `wine_config.esyncc`, which are otherwise ignored
+pub fn parse_game_config_with_unknown_keys(content: &str) -> Result<(GameConfig, Vec<String>)> {
+    let mut table: Table = toml::from_str(content)?;
+    // Parse the original text when nothing changed, so errors point at the right line
+    let content = if migrate_config(&mut table) {
+        Cow::Owned(toml::to_string(&table)?)
+    } else {
+        Cow::Borrowed(content)
+    };
+
+    let mut unknown_keys = Vec::new();
+    let config = serde_ignored::deserialize(toml::Deserializer::parse(&content)?, |path| {
+        unknown_keys.push(path.to_string())
+    })?;
+    unknown_keys.extend(unknown_gamescope_keys(&table));
+
+    Ok((config, unknown_keys))
+}
+
+/// `[gamescope]` is read as a whole table so it can be layered over a preset,
+/// which hides its unknown keys from `serde_ignored`
+fn unknown_gamescope_keys(table: &Table) -> Vec<String> {
+    let Some(Value::Table(gamescope)) = table.get("gamescope") else {
+        return Vec::new();
+    };
+    let Ok(known) = Table::try_from(GamescopeConfig::default()) else {
+        return Vec::new();
+    };
+
+    gamescope
+        .keys()
+        .filter(|key| *key != "preset" && !known.contains_key(*key))
+        .map(|key| format!("gamescope.{key}"))
+        .collect()
+}
+
+/// Upgrade a game config table to `CURRENT_CONFIG_VERSION`, returning whether
+/// anything was changed
+///
+/// Configs from a newer Cellar are parsed as they are, after a warning.
+pub fn migrate_config(table: &mut Table) -> bool {
+    let version = match table.get("version") {
+        None => 1,
+        Some(value) => match value.as_integer().and_then(|v| u32::try_from(v).ok()) {
+            Some(version) => version,
+            // Left for deserialization to report
+            None => return false,
+        },
+    };
+
+    if version > CURRENT_CONFIG_VERSION {
+        let name = table
+            .get("game")
+            .and_then(|game| game.get("name"))
+            .and_then(Value::as_str)
+            .unwrap_or("unknown");
+        eprintln!(
+            "Warning: config for '{name}' is version {version}, newer than this Cellar supports ({CURRENT_CONFIG_VERSION}); some settings may be ignored"
+        );
+        return false;
+    }
+    if version == CURRENT_CONFIG_VERSION {
+        return false;
+    }
+
+    if version < 2 {
+        migrate_v1_to_v2(table);
+    }
+    table.insert(
+        "version".to_string(),
+        Value::Integer(CURRENT_CONFIG_VERSION.into()),
+    );
+    true
+}
+
+/// Version 1 configs could leave out the `launch` and `wine_config` tables, kept
+/// `mangohud` as a top-level switch, and carried `game.status`, `game.template`
+/// and `dependencies`, which are no longer used
+fn migrate_v1_to_v2(table: &mut Table) {
+    for section in ["launch", "wine_config"] {
+        table
+            .entry(section)
+            .or_insert_with(|| Value::Table(Table::new()));
+    }
+
+    if let Some(Value::Boolean(enabled)) = table.get("mangohud").cloned() {
+        table.remove("mangohud");
+        if let Some(Value::Table(launch)) = table.get_mut("launch") {
+            launch.entry("mangohud").or_insert(Value::Boolean(enabled));
+        }
+    }
+
+    if let Some(Value::Table(game)) = table.get_mut("game") {
+        game.remove("status");
+        game.remove("template");
+    }
+    table.remove("dependencies");
+}
+
+/// `sanitize_filename` as it was before runs of underscores were collapsed
+fn legacy_sanitize_filename(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>()
+        .trim()
+        .to_lowercase()
+        .replace(' ', "_")
+}
+
+/// Rename game configs, and recreate their desktop shortcuts, that are still
+/// named with the old `sanitize_filename` rules
+///
+/// Only files whose name is exactly what the old rules produce for the game's
+/// name are touched, and nothing is overwritten.
+pub async fn migrate_legacy_filenames(dirs: &CellarDirectories) -> Result<()> {
+    for stem in dirs.list_game_configs()? {
+        // Only the old rules could leave these behind
+        if !(stem.contains("__") || stem.starts_with('_') || stem.ends_with('_')) {
+            continue;
+        }
+
+        let old_path = dirs.configs_dir.join(format!("{stem}.toml"));
+        let Ok(content) = fs::read_to_string(&old_path) else {
+            continue;
+        };
+        let Ok(config) = parse_game_config(&content) else {
+            continue;
+        };
+
+        let new_stem = sanitize_filename(&config.game.name);
+        if stem != legacy_sanitize_filename(&config.game.name) || stem == new_stem {
+            continue;
+        }
+
+        // Another game may already own the new name, in which case this one gets a suffix
+        let new_path = dirs.game_config_path_for_save(&config.game.name);
+        fs::rename(&old_path, &new_path)?;
+        println!(
+            "Renamed config {} to {}",
+            old_path.display(),
+            new_path.display()
+        );
+
+        let old_shortcut = dirs.applications_dir.join(format!("cellar-{stem}.desktop"));
+        if old_shortcut.exists() {
+            fs::remove_file(&old_shortcut)?;
+            create_desktop_shortcut(dirs, &config, &dirs.get_game_file_stem(&config.game.name))
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_migrate_legacy_config_names() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dirs = CellarDirectories::new(Some(temp_dir.path())).unwrap();
+        dirs.ensure_all_exist().unwrap();
+
+        let config = |name: &str| {
+            format!(
+                "[game]\nname = \"{name}\"\nexecutable = \"/tmp/game.exe\"\n\
+                 wine_prefix = \"/tmp/prefix\"\nproton_version = \"9-1\"\n\n\
+                 [launch]\n\n[wine_config]\n\n[desktop]\ncreate_shortcut = false\n"
+            )
+        };
+        fs::write(
+            dirs.configs_dir.join("game__the_sequel.toml"),
+            config("Game: The Sequel"),
+        )
+        .unwrap();
+        // Not what the old rules produce for its name, so it's left alone
+        fs::write(dirs.configs_dir.join("custom__name.toml"), config("Other")).unwrap();
+
+        migrate_legacy_filenames(&dirs).await.unwrap();
+
+        assert_eq!(
+            dirs.list_game_configs().unwrap(),
+            vec!["custom__name", "game_the_sequel"]
+        );
+    }
+
+    #[test]
+    fn test_migrate_v1_config() {
+        let content = r#"
+mangohud = true
+dependencies = ["vcrun2019"]
+
+[game]
+name = "Old Game"
+executable = "/tmp/game.exe"
+wine_prefix = "/tmp/prefix"
+proton_version = "GE-Proton8-32"
+status = "installed"
+template = "unity"
+"#;
+
+        // Without migration the top-level mangohud switch and missing tables fail to parse
+        assert!(toml::from_str::<GameConfig>(content).is_err());
+
+        let config = parse_game_config(content).unwrap();
+        assert_eq!(config.version, CURRENT_CONFIG_VERSION);
+        assert_eq!(config.game.name, "Old Game");
+        assert!(config.launch.mangohud);
+        assert!(config.wine_config.esync);
+
+        let saved = toml::to_string_pretty(&config).unwrap();
+        assert!(saved.starts_with(&format!("version = {CURRENT_CONFIG_VERSION}")));
+        assert!(!saved.contains("status") && !saved.contains("dependencies"));
+
+        let mut table: Table = toml::from_str(&saved).unwrap();
+        assert!(!migrate_config(&mut table));
+    }
+
+    #[test]
+    fn test_unknown_config_keys() {
+        let content = r#"
+version = 2
+
+[game]
+name = "Game"
+executable = "/tmp/game.exe"
+wine_prefix = "/tmp/prefix"
+proton_version = "GE-Proton8-32"
+
+[launch]
+gamemode = true
+
+[launch.env]
+DXVK_HUD = "fps"
+
+[wine_config]
+esyncc = false
+
+[gamescope]
+preset = "steamdeck"
+widht = 1920
+"#;
+
+        let (config, unknown_keys) = parse_game_config_with_unknown_keys(content).unwrap();
+        assert!(config.launch.gamemode);
+        assert!(config.wine_config.esync);
+        assert_eq!(unknown_keys, vec!["wine_config.esyncc", "gamescope.widht"]);
+    }
+}
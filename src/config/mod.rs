@@ -1,2 +1,6 @@
+pub mod defaults;
 pub mod game;
+pub mod migrate;
+pub mod presets;
+pub mod settings;
 pub mod validation;
@@ -0,0 +1,53 @@
+use super::game::GamescopeConfig;
+
+/// Names accepted by `gamescope.preset`
+pub const GAMESCOPE_PRESETS: &[&str] = &["steamdeck", "1080p60", "1440p144", "4k-fsr"];
+
+/// Gamescope fields a preset fills in
+pub const PRESET_FIELDS: &[&str] = &[
+    "width",
+    "height",
+    "output_width",
+    "output_height",
+    "refresh_rate",
+    "upscaling",
+];
+
+/// Gamescope settings for a named preset
+///
+/// Presets only fill in resolution, refresh rate and scaling; `enabled` and
+/// the other switches keep their defaults.
+pub fn gamescope_preset(name: &str) -> Option<GamescopeConfig> {
+    let (width, height, output_width, output_height, refresh_rate, upscaling) = match name {
+        "steamdeck" => (1280, 800, 1280, 800, 60, "fsr"),
+        "1080p60" => (1920, 1080, 1920, 1080, 60, "off"),
+        "1440p144" => (2560, 1440, 2560, 1440, 144, "off"),
+        "4k-fsr" => (2560, 1440, 3840, 2160, 60, "fsr"),
+        _ => return None,
+    };
+
+    Some(GamescopeConfig {
+        preset: Some(name.to_string()),
+        width,
+        height,
+        output_width,
+        output_height,
+        refresh_rate,
+        upscaling: upscaling.to_string(),
+        ..GamescopeConfig::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_listed_preset_exists() {
+        for name in GAMESCOPE_PRESETS {
+            let preset = gamescope_preset(name).unwrap();
+            assert_eq!(preset.preset.as_deref(), Some(*name));
+        }
+        assert!(gamescope_preset("8k").is_none());
+    }
+}
@@ -0,0 +1,287 @@
+use anyhow::{anyhow, Result};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::defaults::CellarDefaults;
+use super::game::GameConfig;
+use super::presets::PRESET_FIELDS;
+
+/// Value type of a config field that can be set from the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SettingKind {
+    Bool,
+    Number,
+    Text,
+    /// Optional text; an empty value removes the key
+    OptionalText,
+    /// Optional number; an empty value removes the key
+    OptionalNumber,
+}
+
+/// Dotted keys accepted by `cellar games set`
+const SETTINGS: &[(&str, SettingKind)] = &[
+    ("game.executable", SettingKind::Text),
+    ("game.wine_prefix", SettingKind::Text),
+    ("game.proton_version", SettingKind::Text),
+    ("game.dxvk_version", SettingKind::OptionalText),
+    ("game.umu_game_id", SettingKind::OptionalText),
+    ("game.native", SettingKind::Bool),
+    ("launch.launch_options", SettingKind::Text),
+    ("launch.gamemode", SettingKind::Bool),
+    ("launch.mangohud", SettingKind::Bool),
+    ("launch.working_dir", SettingKind::OptionalText),
+    ("wine_config.esync", SettingKind::Bool),
+    ("wine_config.fsync", SettingKind::Bool),
+    ("wine_config.dxvk", SettingKind::Bool),
+    ("wine_config.dxvk_async", SettingKind::Bool),
+    ("wine_config.large_address_aware", SettingKind::Bool),
+    ("wine_config.wineserver_kill_timeout", SettingKind::Number),
+    ("wine_config.dll_overrides", SettingKind::OptionalText),
+    ("wine_config.proton_fsr", SettingKind::Bool),
+    (
+        "wine_config.proton_fsr_strength",
+        SettingKind::OptionalNumber,
+    ),
+    ("dxvk.hud", SettingKind::Text),
+    ("dxvk.shared_shader_cache", SettingKind::Bool),
+    ("mangohud.fps_limit", SettingKind::OptionalNumber),
+    ("mangohud.position", SettingKind::OptionalText),
+    ("mangohud.config_string", SettingKind::OptionalText),
+    ("gamescope.enabled", SettingKind::Bool),
+    ("gamescope.preset", SettingKind::OptionalText),
+    ("gamescope.width", SettingKind::Number),
+    ("gamescope.height", SettingKind::Number),
+    ("gamescope.output_width", SettingKind::Number),
+    ("gamescope.output_height", SettingKind::Number),
+    ("gamescope.refresh_rate", SettingKind::Number),
+    ("gamescope.upscaling", SettingKind::Text),
+    ("gamescope.fullscreen", SettingKind::Bool),
+    ("gamescope.force_grab_cursor", SettingKind::Bool),
+    ("gamescope.expose_wayland", SettingKind::Bool),
+    ("gamescope.hdr", SettingKind::Bool),
+    ("gamescope.adaptive_sync", SettingKind::Bool),
+    ("gamescope.immediate_flips", SettingKind::Bool),
+    ("desktop.create_shortcut", SettingKind::Bool),
+    ("desktop.comment", SettingKind::Text),
+];
+
+/// Return a copy of `config` with the dotted `key` set to `value`
+///
+/// The value is parsed according to the field's type. The result is not
+/// validated; callers should run `validate_game_config` before saving it.
+pub fn set_config_value(config: &GameConfig, key: &str, value: &str) -> Result<GameConfig> {
+    set_value(config, SETTINGS, key, value)
+}
+
+/// Keys accepted by `cellar defaults set`: the launch and Wine settings, the Proton
+/// version and the runners directory
+fn defaults_settings() -> Vec<(&'static str, SettingKind)> {
+    SETTINGS
+        .iter()
+        .filter_map(|&(name, kind)| match name {
+            // Unlike a game's, the default Proton version may be unset
+            "game.proton_version" => Some((name, SettingKind::OptionalText)),
+            // Only meaningful for a single game's install
+            "launch.working_dir" => None,
+            _ if name.starts_with("launch.") || name.starts_with("wine_config.") => {
+                Some((name, kind))
+            }
+            _ => None,
+        })
+        .chain([("paths.runners_dir", SettingKind::OptionalText)])
+        .collect()
+}
+
+/// Return a copy of `defaults` with the dotted `key` set to `value`
+pub fn set_defaults_value(
+    defaults: &CellarDefaults,
+    key: &str,
+    value: &str,
+) -> Result<CellarDefaults> {
+    set_value(defaults, &defaults_settings(), key, value)
+}
+
+fn set_value<T: Serialize + DeserializeOwned>(
+    current: &T,
+    settings: &[(&str, SettingKind)],
+    key: &str,
+    value: &str,
+) -> Result<T> {
+    let kind = settings
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, kind)| *kind)
+        .ok_or_else(|| {
+            let valid_keys: Vec<&str> = settings.iter().map(|(name, _)| *name).collect();
+            anyhow!(
+                "Unknown key '{}'. Valid keys:\n  {}",
+                key,
+                valid_keys.join("\n  ")
+            )
+        })?;
+
+    let (section, field) = key
+        .split_once('.')
+        .expect("settings keys are section.field");
+
+    let parsed = parse_setting_value(kind, value).ok_or_else(|| {
+        anyhow!(
+            "Invalid value '{}' for {}: expected {}",
+            value,
+            key,
+            describe(kind)
+        )
+    })?;
+
+    let mut table = toml::Table::try_from(current)?;
+    let section_table = table
+        .entry(section)
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        .as_table_mut()
+        .ok_or_else(|| anyhow!("Config section '{}' is not a table", section))?;
+
+    match parsed {
+        Some(parsed) => {
+            section_table.insert(field.to_string(), parsed);
+        }
+        None => {
+            section_table.remove(field);
+        }
+    }
+
+    // Setting a preset drops the fields it fills in, so the preset applies
+    if key == "gamescope.preset" {
+        for preset_field in PRESET_FIELDS {
+            section_table.remove(*preset_field);
+        }
+    }
+
+    table
+        .try_into()
+        .map_err(|e| anyhow!("Failed to apply {}: {}", key, e))
+}
+
+/// `None` if the value doesn't parse; `Some(None)` to remove an optional key
+fn parse_setting_value(kind: SettingKind, value: &str) -> Option<Option<toml::Value>> {
+    let value = value.trim();
+    match kind {
+        SettingKind::Bool => parse_bool(value).map(|b| Some(toml::Value::Boolean(b))),
+        SettingKind::Number => parse_number(value).map(Some),
+        SettingKind::Text => Some(Some(toml::Value::String(value.to_string()))),
+        SettingKind::OptionalText if value.is_empty() => Some(None),
+        SettingKind::OptionalText => Some(Some(toml::Value::String(value.to_string()))),
+        SettingKind::OptionalNumber if value.is_empty() => Some(None),
+        SettingKind::OptionalNumber => parse_number(value).map(Some),
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_number(value: &str) -> Option<toml::Value> {
+    value
+        .parse::<u32>()
+        .ok()
+        .map(|n| toml::Value::Integer(n.into()))
+}
+
+fn describe(kind: SettingKind) -> &'static str {
+    match kind {
+        SettingKind::Bool => "true or false",
+        SettingKind::Number => "a whole number",
+        SettingKind::Text | SettingKind::OptionalText => "text",
+        SettingKind::OptionalNumber => "a whole number, or an empty value to unset it",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> GameConfig {
+        toml::from_str(
+            r#"
+[game]
+name = "Test Game"
+executable = "/path/to/game.exe"
+wine_prefix = "/path/to/prefix"
+proton_version = "GE-Proton8-32"
+
+[launch]
+
+[wine_config]
+
+[gamescope]
+width = 2560
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_set_config_value_types() {
+        let config = test_config();
+
+        let config = set_config_value(&config, "wine_config.esync", "off").unwrap();
+        assert!(!config.wine_config.esync);
+
+        let config = set_config_value(&config, "gamescope.width", "1280").unwrap();
+        assert_eq!(config.gamescope.width, 1280);
+
+        let config = set_config_value(&config, "game.proton_version", "GE-Proton9-20").unwrap();
+        assert_eq!(config.game.proton_version, "GE-Proton9-20");
+
+        let config = set_config_value(&config, "mangohud.fps_limit", "144").unwrap();
+        assert_eq!(config.mangohud.fps_limit, Some(144));
+        let config = set_config_value(&config, "mangohud.fps_limit", "").unwrap();
+        assert_eq!(config.mangohud.fps_limit, None);
+    }
+
+    #[test]
+    fn test_set_config_value_errors() {
+        let config = test_config();
+
+        let err = set_config_value(&config, "gamescope.widht", "1280").unwrap_err();
+        assert!(err.to_string().contains("Valid keys"));
+        assert!(err.to_string().contains("gamescope.width"));
+
+        let err = set_config_value(&config, "gamescope.width", "wide").unwrap_err();
+        assert!(err.to_string().contains("expected a whole number"));
+
+        let err = set_config_value(&config, "launch.mangohud", "maybe").unwrap_err();
+        assert!(err.to_string().contains("expected true or false"));
+    }
+
+    #[test]
+    fn test_set_defaults_value() {
+        let defaults = CellarDefaults::default();
+
+        let defaults = set_defaults_value(&defaults, "launch.gamemode", "on").unwrap();
+        assert!(defaults.launch.gamemode);
+
+        let defaults =
+            set_defaults_value(&defaults, "game.proton_version", "GE-Proton9-20").unwrap();
+        assert_eq!(
+            defaults.game.proton_version.as_deref(),
+            Some("GE-Proton9-20")
+        );
+        let defaults = set_defaults_value(&defaults, "game.proton_version", "").unwrap();
+        assert!(defaults.game.proton_version.is_none());
+
+        assert!(set_defaults_value(&defaults, "gamescope.width", "1280").is_err());
+        assert!(set_defaults_value(&defaults, "game.executable", "/tmp/game.exe").is_err());
+    }
+
+    #[test]
+    fn test_set_gamescope_preset_replaces_fields() {
+        let config = set_config_value(&test_config(), "gamescope.preset", "steamdeck").unwrap();
+        assert_eq!(config.gamescope.preset.as_deref(), Some("steamdeck"));
+        assert_eq!(config.gamescope.width, 1280);
+    }
+}
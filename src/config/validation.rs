@@ -2,6 +2,10 @@ use anyhow::{anyhow, Result};
 use std::path::Path;
 
 use super::game::GameConfig;
+use crate::launch::command::validate_launch_options;
+
+/// Highest `WINE_FULLSCREEN_FSR_STRENGTH` Proton accepts
+const MAX_PROTON_FSR_STRENGTH: u32 = 5;
 
 pub fn validate_game_config(config: &GameConfig) -> Result<()> {
     // Validate game name
@@ -17,6 +21,41 @@ pub fn validate_game_config(config: &GameConfig) -> Result<()> {
         ));
     }
 
+    if !config.game.native {
+        validate_wine_settings(config)?;
+    }
+
+    // Catch bad launch options now instead of the next time the game is launched
+    validate_launch_options(
+        &config.launch.launch_options,
+        config.launch.unsafe_launch_options,
+    )
+    .map_err(|e| anyhow!("Invalid launch_options: {}", e))?;
+
+    if let Some(working_dir) = &config.launch.working_dir {
+        if !working_dir.is_dir() {
+            return Err(anyhow!(
+                "Working directory does not exist: {}",
+                working_dir.display()
+            ));
+        }
+    }
+
+    // Validate gamescope configuration
+    if config.gamescope.enabled {
+        validate_gamescope_config(&config.gamescope)?;
+    }
+
+    validate_mangohud_config(&config.mangohud)?;
+
+    // Validate desktop configuration
+    validate_desktop_config(&config.desktop)?;
+
+    Ok(())
+}
+
+/// Prefix, Proton and Wine settings, which native games don't use
+fn validate_wine_settings(config: &GameConfig) -> Result<()> {
     // Validate wine prefix path
     let prefix_parent = config
         .game
@@ -36,13 +75,19 @@ pub fn validate_game_config(config: &GameConfig) -> Result<()> {
         return Err(anyhow!("Proton version cannot be empty"));
     }
 
-    // Validate gamescope configuration
-    if config.gamescope.enabled {
-        validate_gamescope_config(&config.gamescope)?;
+    if let Some(dll_overrides) = &config.wine_config.dll_overrides {
+        validate_dll_overrides(dll_overrides)?;
     }
 
-    // Validate desktop configuration
-    validate_desktop_config(&config.desktop)?;
+    if let Some(strength) = config.wine_config.proton_fsr_strength {
+        if strength > MAX_PROTON_FSR_STRENGTH {
+            return Err(anyhow!(
+                "proton_fsr_strength must be between 0 and {}, got {}",
+                MAX_PROTON_FSR_STRENGTH,
+                strength
+            ));
+        }
+    }
 
     Ok(())
 }
@@ -73,6 +118,60 @@ fn validate_gamescope_config(config: &super::game::GamescopeConfig) -> Result<()
         ));
     }
 
+    // Upscalers can only scale up; other modes may also shrink the image to fit
+    let upscaling = config.upscaling.as_str();
+    if matches!(upscaling, "fsr" | "nis")
+        && (config.output_width < config.width || config.output_height < config.height)
+    {
+        return Err(anyhow!(
+            "Gamescope output resolution {}x{} is smaller than the game resolution {}x{}, which {} upscaling can't handle. Raise output_width/output_height or lower width/height",
+            config.output_width,
+            config.output_height,
+            config.width,
+            config.height,
+            upscaling.to_uppercase()
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_mangohud_config(config: &super::game::MangohudConfig) -> Result<()> {
+    if let Some(position) = &config.position {
+        let valid_positions = [
+            "top-left",
+            "top-center",
+            "top-right",
+            "middle-left",
+            "middle-right",
+            "bottom-left",
+            "bottom-center",
+            "bottom-right",
+        ];
+        if !valid_positions.contains(&position.as_str()) {
+            return Err(anyhow!(
+                "Invalid MangoHud position '{}'. Must be one of: {}",
+                position,
+                valid_positions.join(", ")
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn validate_dll_overrides(dll_overrides: &str) -> Result<()> {
+    // DLL names like d3dcompiler_47 need underscores; anything else could inject into the env
+    if let Some(c) = dll_overrides
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, ',' | '=' | ';' | '_')))
+    {
+        return Err(anyhow!(
+            "Invalid character '{}' in dll_overrides. Only letters, digits, '_', ',', '=' and ';' are allowed",
+            c
+        ));
+    }
+
     Ok(())
 }
 
@@ -90,6 +189,45 @@ fn validate_desktop_config(config: &super::game::DesktopConfig) -> Result<()> {
     Ok(())
 }
 
+/// Check whether a DXVK build honours `DXVK_ASYNC`
+///
+/// Async shader compilation was never part of upstream DXVK; only the
+/// `dxvk-async` and `dxvk-gplasync` forks read the variable.
+pub fn dxvk_supports_async(dxvk_name: &str) -> bool {
+    dxvk_name.to_lowercase().contains("async")
+}
+
+/// Warn when `dxvk_async` is enabled but the DXVK in use will ignore it
+///
+/// Uses the game's `dxvk_version` when set, otherwise the DXVK bundled with
+/// the Proton build (GE-Proton ships gplasync, Valve's Proton does not).
+pub fn dxvk_async_warning(config: &GameConfig, proton_name: &str) -> Option<String> {
+    if !config.wine_config.dxvk || !config.wine_config.dxvk_async {
+        return None;
+    }
+
+    let (dxvk_source, supported) = match &config.game.dxvk_version {
+        Some(dxvk_version) => (
+            format!("DXVK '{dxvk_version}'"),
+            dxvk_supports_async(dxvk_version),
+        ),
+        None => (
+            format!("the DXVK bundled with '{proton_name}'"),
+            proton_name.contains("GE-Proton") || dxvk_supports_async(proton_name),
+        ),
+    };
+
+    if supported {
+        return None;
+    }
+
+    Some(format!(
+        "dxvk_async is enabled but {dxvk_source} does not support async shader compilation, \
+         so DXVK_ASYNC will be ignored. Use a dxvk-gplasync build \
+         (https://gitlab.com/Ph42oN/dxvk-gplasync) or set dxvk_async = false."
+    ))
+}
+
 #[allow(dead_code)]
 pub fn validate_file_path(path: &Path, description: &str) -> Result<()> {
     if !path.exists() {
@@ -127,3 +265,83 @@ pub fn validate_directory_path(path: &Path, description: &str) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_dll_overrides() {
+        assert!(validate_dll_overrides("d3d11,dxgi=n,b").is_ok());
+        assert!(validate_dll_overrides("d3d9=d;d3dcompiler_47=n").is_ok());
+        assert!(validate_dll_overrides("d3d11=n,b $(rm -rf ~)").is_err());
+        assert!(validate_dll_overrides("dxgi=n\nLD_PRELOAD=x").is_err());
+    }
+
+    #[test]
+    fn test_gamescope_upscaling_needs_larger_output() {
+        let mut config = crate::config::game::GamescopeConfig {
+            width: 1920,
+            height: 1080,
+            output_width: 1280,
+            output_height: 720,
+            ..Default::default()
+        };
+
+        let err = validate_gamescope_config(&config).unwrap_err();
+        assert!(err.to_string().contains("smaller than the game resolution"));
+
+        config.upscaling = "nis".to_string();
+        assert!(validate_gamescope_config(&config).is_err());
+
+        // Scaling down is fine when nothing is upscaled
+        config.upscaling = "integer".to_string();
+        assert!(validate_gamescope_config(&config).is_ok());
+        config.upscaling = "stretch".to_string();
+        assert!(validate_gamescope_config(&config).is_ok());
+
+        config.upscaling = "fsr".to_string();
+        config.output_width = 2560;
+        config.output_height = 1440;
+        assert!(validate_gamescope_config(&config).is_ok());
+    }
+
+    #[test]
+    fn test_dxvk_supports_async() {
+        assert!(dxvk_supports_async("dxvk-gplasync-v2.3-1"));
+        assert!(dxvk_supports_async("DXVK-Async-1.10.3"));
+        assert!(!dxvk_supports_async("dxvk-2.3.1"));
+        assert!(!dxvk_supports_async("2.3"));
+    }
+
+    #[test]
+    fn test_dxvk_async_warning() {
+        let toml_string = r#"
+[game]
+name = "Test Game"
+executable = "/path/to/game.exe"
+wine_prefix = "/path/to/prefix"
+proton_version = "GE-Proton9-1"
+
+[launch]
+
+[wine_config]
+dxvk = true
+dxvk_async = true
+"#;
+        let mut config: GameConfig = toml::from_str(toml_string).unwrap();
+
+        assert!(dxvk_async_warning(&config, "GE-Proton9-1").is_none());
+        assert!(dxvk_async_warning(&config, "Proton 9.0").is_some());
+
+        config.game.dxvk_version = Some("dxvk-2.3.1".to_string());
+        assert!(dxvk_async_warning(&config, "GE-Proton9-1").is_some());
+
+        config.game.dxvk_version = Some("dxvk-gplasync-v2.3-1".to_string());
+        assert!(dxvk_async_warning(&config, "Proton 9.0").is_none());
+
+        config.wine_config.dxvk_async = false;
+        config.game.dxvk_version = Some("dxvk-2.3.1".to_string());
+        assert!(dxvk_async_warning(&config, "Proton 9.0").is_none());
+    }
+}
@@ -3,9 +3,15 @@ use std::path::{Path, PathBuf};
 
 use crate::utils::fs::CellarDirectories;
 
-/// Extract icon from executable using wrestool and convert to PNG using ImageMagick
-pub async fn extract_and_convert_icon(exe_path: &Path, game_name: &str) -> Result<PathBuf> {
-    let dirs = CellarDirectories::new()?;
+/// Extract icon from executable and convert to PNG using ImageMagick
+///
+/// wrestool is tried first; when it is missing, fails or finds nothing, the
+/// executable's resources are read directly.
+pub async fn extract_and_convert_icon(
+    dirs: &CellarDirectories,
+    exe_path: &Path,
+    game_name: &str,
+) -> Result<PathBuf> {
     dirs.ensure_all_exist()?;
 
     // Check if tools are available
@@ -15,12 +21,29 @@ pub async fn extract_and_convert_icon(exe_path: &Path, game_name: &str) -> Resul
     let ico_path = dirs.get_game_icon_path(game_name, "ico");
     let png_path = dirs.get_game_icon_path(game_name, "png");
 
-    // Step 1: Extract icon using wrestool
-    extract_icon_with_wrestool(exe_path, &ico_path).await?;
+    // Step 1: Extract icon using wrestool, falling back to reading the PE resources ourselves
+    if let Err(wrestool_error) = extract_icon_with_wrestool(exe_path, &ico_path).await {
+        let ico = std::fs::read(exe_path)
+            .ok()
+            .and_then(|data| super::pe::extract_ico(&data))
+            .ok_or(wrestool_error)?;
+        std::fs::write(&ico_path, ico)?;
+    }
 
     // Step 2: Convert ICO to PNG using ImageMagick
     convert_ico_to_png(&ico_path, &png_path).await?;
 
+    // Step 3: Install every size into the icon theme so launchers can pick the sharpest one
+    match install_themed_icons(dirs, &ico_path, game_name).await {
+        Ok(sizes) if sizes.len() > 1 => println!(
+            "Installed {} icon sizes into {}",
+            sizes.len(),
+            dirs.icon_theme_dir.display()
+        ),
+        Ok(_) => {}
+        Err(e) => eprintln!("Warning: Failed to install themed icons for {game_name}: {e}"),
+    }
+
     // Clean up intermediate ICO file
     if ico_path.exists() {
         std::fs::remove_file(&ico_path)?;
@@ -29,20 +52,8 @@ pub async fn extract_and_convert_icon(exe_path: &Path, game_name: &str) -> Resul
     Ok(png_path)
 }
 
-/// Check if wrestool and magick commands are available
+/// Check if the magick command is available (wrestool is optional)
 async fn check_required_tools() -> Result<()> {
-    // Check for wrestool
-    let wrestool_check = tokio::process::Command::new("which")
-        .arg("wrestool")
-        .output()
-        .await?;
-
-    if !wrestool_check.status.success() {
-        return Err(anyhow!(
-            "wrestool not found. Please install icoutils package (e.g., 'sudo apt install icoutils' on Ubuntu)"
-        ));
-    }
-
     // Check for magick (ImageMagick)
     let magick_check = tokio::process::Command::new("which")
         .arg("magick")
@@ -113,9 +124,164 @@ async fn convert_ico_to_png(ico_path: &Path, png_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Sizes the hicolor theme has directories for; frames of other sizes are skipped
+const THEME_ICON_SIZES: &[u32] = &[16, 22, 24, 32, 48, 64, 96, 128, 256];
+
+/// Image entry in an ICO file's directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IcoFrame {
+    index: usize,
+    width: u32,
+    height: u32,
+    bit_count: u16,
+}
+
+/// Read the image directory at the start of an ICO file
+fn parse_ico_frames(data: &[u8]) -> Vec<IcoFrame> {
+    let read_u16 = |offset: usize| -> Option<u16> {
+        Some(u16::from_le_bytes([
+            *data.get(offset)?,
+            *data.get(offset + 1)?,
+        ]))
+    };
+
+    // Header: reserved (0), type (1 = icon), image count
+    if read_u16(0) != Some(0) || read_u16(2) != Some(1) {
+        return Vec::new();
+    }
+    let count = read_u16(4).unwrap_or(0) as usize;
+
+    (0..count)
+        .map_while(|index| {
+            let entry = 6 + index * 16;
+            // A stored dimension of 0 means 256
+            let dimension = |byte: u8| if byte == 0 { 256 } else { u32::from(byte) };
+            Some(IcoFrame {
+                index,
+                width: dimension(*data.get(entry)?),
+                height: dimension(*data.get(entry + 1)?),
+                bit_count: read_u16(entry + 6)?,
+            })
+        })
+        .collect()
+}
+
+/// Pick the deepest-colour frame for each square size the theme supports
+fn select_theme_frames(frames: &[IcoFrame]) -> Vec<IcoFrame> {
+    THEME_ICON_SIZES
+        .iter()
+        .filter_map(|size| {
+            frames
+                .iter()
+                .filter(|f| f.width == *size && f.height == *size)
+                .max_by_key(|f| f.bit_count)
+                .copied()
+        })
+        .collect()
+}
+
+/// Convert each usable frame of the ICO into `hicolor/<size>x<size>/apps/cellar-<game>.png`
+async fn install_themed_icons(
+    dirs: &CellarDirectories,
+    ico_path: &Path,
+    game_name: &str,
+) -> Result<Vec<u32>> {
+    let frames = select_theme_frames(&parse_ico_frames(&std::fs::read(ico_path)?));
+
+    let mut sizes = Vec::new();
+    for frame in frames {
+        let icon_path = dirs.get_themed_icon_path(game_name, frame.width);
+        if let Some(parent) = icon_path.parent() {
+            dirs.ensure_dir_exists(parent)?;
+        }
+
+        let output = tokio::process::Command::new("magick")
+            .arg(format!("{}[{}]", ico_path.display(), frame.index))
+            .arg(&icon_path)
+            .output()
+            .await?;
+        if output.status.success() {
+            sizes.push(frame.width);
+        }
+    }
+
+    Ok(sizes)
+}
+
+/// Sizes of the game's icons installed in the icon theme
+pub fn themed_icon_sizes(dirs: &CellarDirectories, game_name: &str) -> Vec<u32> {
+    THEME_ICON_SIZES
+        .iter()
+        .copied()
+        .filter(|size| dirs.get_themed_icon_path(game_name, *size).exists())
+        .collect()
+}
+
+/// Convert a PNG, JPG or ICO into the game's custom icon, shrinking it to at most 256x256
+pub async fn import_custom_icon(
+    dirs: &CellarDirectories,
+    image_path: &Path,
+    game_name: &str,
+) -> Result<PathBuf> {
+    let extension = image_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+
+    // ICOs hold several images; use the largest rather than whichever comes first
+    let input = match extension.as_str() {
+        "png" | "jpg" | "jpeg" => image_path.display().to_string(),
+        "ico" => {
+            let frames = parse_ico_frames(&std::fs::read(image_path)?);
+            let index = frames
+                .iter()
+                .max_by_key(|f| (f.width * f.height, f.bit_count))
+                .map_or(0, |f| f.index);
+            format!("{}[{}]", image_path.display(), index)
+        }
+        _ => {
+            return Err(anyhow!(
+                "Unsupported icon format '{}'. Use a PNG, JPG or ICO file",
+                image_path.display()
+            ))
+        }
+    };
+
+    dirs.ensure_dir_exists(&dirs.icons_dir)?;
+    let icon_path = dirs.get_custom_icon_path(game_name);
+
+    let output = tokio::process::Command::new("magick")
+        .arg(&input)
+        .arg("-resize")
+        .arg("256x256>")
+        .arg(&icon_path)
+        .output()
+        .await
+        .map_err(|e| {
+            anyhow!(
+                "Failed to run magick: {}. Please install ImageMagick (e.g., 'sudo apt install imagemagick' on Ubuntu)",
+                e
+            )
+        })?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to convert {} to PNG: {}",
+            image_path.display(),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(icon_path)
+}
+
 /// Get icon path for a game, extracting if necessary
-pub async fn get_or_extract_icon(exe_path: &Path, game_name: &str) -> Result<Option<PathBuf>> {
-    let dirs = CellarDirectories::new()?;
+pub async fn get_or_extract_icon(
+    dirs: &CellarDirectories,
+    exe_path: &Path,
+    game_name: &str,
+) -> Result<Option<PathBuf>> {
     let png_path = dirs.get_game_icon_path(game_name, "png");
 
     // If PNG icon already exists, return it
@@ -124,7 +290,7 @@ pub async fn get_or_extract_icon(exe_path: &Path, game_name: &str) -> Result<Opt
     }
 
     // Try to extract and convert icon
-    match extract_and_convert_icon(exe_path, game_name).await {
+    match extract_and_convert_icon(dirs, exe_path, game_name).await {
         Ok(icon_path) => {
             println!(
                 "Extracted icon for {} to {}",
@@ -142,9 +308,7 @@ pub async fn get_or_extract_icon(exe_path: &Path, game_name: &str) -> Result<Opt
 }
 
 /// Remove icon files for a game
-pub fn remove_game_icons(game_name: &str) -> Result<()> {
-    let dirs = CellarDirectories::new()?;
-
+pub fn remove_game_icons(dirs: &CellarDirectories, game_name: &str) -> Result<()> {
     // Remove both ICO and PNG versions if they exist
     let ico_path = dirs.get_game_icon_path(game_name, "ico");
     let png_path = dirs.get_game_icon_path(game_name, "png");
@@ -158,12 +322,15 @@ pub fn remove_game_icons(game_name: &str) -> Result<()> {
         println!("Removed icon: {}", png_path.display());
     }
 
+    for size in themed_icon_sizes(dirs, game_name) {
+        std::fs::remove_file(dirs.get_themed_icon_path(game_name, size))?;
+    }
+
     Ok(())
 }
 
 /// List all extracted game icons
-pub fn list_game_icons() -> Result<Vec<String>> {
-    let dirs = CellarDirectories::new()?;
+pub fn list_game_icons(dirs: &CellarDirectories) -> Result<Vec<String>> {
     let mut icons = Vec::new();
 
     if dirs.icons_dir.exists() {
@@ -185,3 +352,56 @@ pub fn list_game_icons() -> Result<Vec<String>> {
     icons.sort();
     Ok(icons)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ico_with_frames(frames: &[(u8, u8, u16)]) -> Vec<u8> {
+        let mut data = vec![0, 0, 1, 0];
+        data.extend_from_slice(&(frames.len() as u16).to_le_bytes());
+        for (width, height, bit_count) in frames {
+            data.extend_from_slice(&[*width, *height, 0, 0, 1, 0]);
+            data.extend_from_slice(&bit_count.to_le_bytes());
+            data.extend_from_slice(&[0; 8]);
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_ico_frames() {
+        let data = ico_with_frames(&[(32, 32, 8), (0, 0, 32)]);
+        let frames = parse_ico_frames(&data);
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!((frames[0].width, frames[0].bit_count), (32, 8));
+        assert_eq!(
+            (frames[1].index, frames[1].width, frames[1].height),
+            (1, 256, 256)
+        );
+
+        assert!(parse_ico_frames(b"\x89PNG").is_empty());
+        // Truncated directories yield the frames that are complete
+        assert_eq!(parse_ico_frames(&data[..6 + 16]).len(), 1);
+    }
+
+    #[test]
+    fn test_select_theme_frames() {
+        let frames = parse_ico_frames(&ico_with_frames(&[
+            (32, 32, 4),
+            (32, 32, 32),
+            (48, 48, 32),
+            (20, 20, 32),
+            (64, 32, 32),
+        ]));
+        let selected = select_theme_frames(&frames);
+
+        assert_eq!(
+            selected
+                .iter()
+                .map(|f| (f.width, f.index))
+                .collect::<Vec<_>>(),
+            vec![(32, 1), (48, 2)]
+        );
+    }
+}
@@ -1,4 +1,5 @@
 pub mod icon;
+pub mod pe;
 pub mod shortcut;
 
 pub use icon::*;
@@ -0,0 +1,300 @@
+//! Minimal PE resource reader for pulling icons out of Windows executables
+//!
+//! Used when `wrestool` can't read an executable. Only the resource directory
+//! is parsed: the first icon group (`RT_GROUP_ICON`) is reassembled into an
+//! ICO file from the `RT_ICON` images it references.
+
+use std::collections::HashMap;
+
+const RT_ICON: u32 = 3;
+const RT_GROUP_ICON: u32 = 14;
+
+/// Resource directory index in the optional header's data directories
+const RESOURCE_DIRECTORY: usize = 2;
+
+struct Section {
+    virtual_address: u32,
+    virtual_size: u32,
+    raw_offset: u32,
+    raw_size: u32,
+}
+
+struct PeFile<'a> {
+    data: &'a [u8],
+    sections: Vec<Section>,
+    resource_offset: usize,
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+impl<'a> PeFile<'a> {
+    fn parse(data: &'a [u8]) -> Option<Self> {
+        if data.get(0..2)? != b"MZ" {
+            return None;
+        }
+        let pe_offset = read_u32(data, 0x3c)? as usize;
+        if data.get(pe_offset..pe_offset + 4)? != b"PE\0\0" {
+            return None;
+        }
+
+        let coff = pe_offset + 4;
+        let section_count = read_u16(data, coff + 2)? as usize;
+        let optional_header_size = read_u16(data, coff + 16)? as usize;
+        let optional_header = coff + 20;
+
+        // PE32 and PE32+ differ in where the data directories start
+        let data_directories = match read_u16(data, optional_header)? {
+            0x10b => optional_header + 96,
+            0x20b => optional_header + 112,
+            _ => return None,
+        };
+        let directory_count = read_u32(data, data_directories - 4)? as usize;
+        if directory_count <= RESOURCE_DIRECTORY {
+            return None;
+        }
+        let resource_rva = read_u32(data, data_directories + RESOURCE_DIRECTORY * 8)?;
+        if resource_rva == 0 {
+            return None;
+        }
+
+        let section_table = optional_header + optional_header_size;
+        let sections = (0..section_count)
+            .map(|i| {
+                let header = section_table + i * 40;
+                Some(Section {
+                    virtual_size: read_u32(data, header + 8)?,
+                    virtual_address: read_u32(data, header + 12)?,
+                    raw_size: read_u32(data, header + 16)?,
+                    raw_offset: read_u32(data, header + 20)?,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut pe = Self {
+            data,
+            sections,
+            resource_offset: 0,
+        };
+        pe.resource_offset = pe.rva_to_offset(resource_rva)?;
+        Some(pe)
+    }
+
+    fn rva_to_offset(&self, rva: u32) -> Option<usize> {
+        self.sections.iter().find_map(|s| {
+            let size = s.virtual_size.max(s.raw_size);
+            let delta = rva.checked_sub(s.virtual_address).filter(|d| *d < size)?;
+            delta
+                .checked_add(s.raw_offset)
+                .map(|offset| offset as usize)
+        })
+    }
+
+    /// (id, offset to data or subdirectory, is subdirectory) for each ID entry of a directory
+    fn directory_entries(&self, offset: usize) -> Option<Vec<(u32, usize, bool)>> {
+        let named = read_u16(self.data, offset + 12)? as usize;
+        let ids = read_u16(self.data, offset + 14)? as usize;
+
+        (0..named + ids)
+            .map(|i| {
+                let entry = offset + 16 + i * 8;
+                let name = read_u32(self.data, entry)?;
+                let target = read_u32(self.data, entry + 4)?;
+                Some((
+                    name,
+                    self.resource_offset + (target & 0x7fff_ffff) as usize,
+                    target & 0x8000_0000 != 0,
+                ))
+            })
+            .collect::<Option<Vec<_>>>()
+            // Named entries (high bit set) aren't numeric IDs
+            .map(|entries| {
+                entries
+                    .into_iter()
+                    .filter(|(name, _, _)| name & 0x8000_0000 == 0)
+                    .collect()
+            })
+    }
+
+    /// Resources of one type as (id, bytes), taking the first language of each
+    fn resources(&self, resource_type: u32) -> Option<Vec<(u32, &'a [u8])>> {
+        let (_, type_dir, _) = self
+            .directory_entries(self.resource_offset)?
+            .into_iter()
+            .find(|(id, _, is_dir)| *id == resource_type && *is_dir)?;
+
+        let mut resources = Vec::new();
+        for (id, name_dir, is_dir) in self.directory_entries(type_dir)? {
+            if !is_dir {
+                continue;
+            }
+            let Some(&(_, data_entry, false)) = self.directory_entries(name_dir)?.first() else {
+                continue;
+            };
+            let rva = read_u32(self.data, data_entry)?;
+            let size = read_u32(self.data, data_entry + 4)? as usize;
+            let offset = self.rva_to_offset(rva)?;
+            resources.push((id, self.data.get(offset..offset + size)?));
+        }
+        Some(resources)
+    }
+}
+
+/// Build an ICO file from the first icon group in a PE executable
+pub fn extract_ico(data: &[u8]) -> Option<Vec<u8>> {
+    let pe = PeFile::parse(data)?;
+    let icons: HashMap<u32, &[u8]> = pe.resources(RT_ICON)?.into_iter().collect();
+    let (_, group) = pe.resources(RT_GROUP_ICON)?.into_iter().next()?;
+
+    // GRPICONDIR: reserved, type, count, then 14-byte entries ending in the RT_ICON id
+    let count = read_u16(group, 4)? as usize;
+    let mut images = Vec::new();
+    for i in 0..count {
+        let entry = group.get(6 + i * 14..6 + (i + 1) * 14)?;
+        let id = u32::from(read_u16(entry, 12)?);
+        if let Some(image) = icons.get(&id) {
+            images.push((&entry[..8], *image));
+        }
+    }
+    if images.is_empty() {
+        return None;
+    }
+
+    // ICONDIR entries are the same, but end in a file offset instead of an id
+    let mut ico = vec![0, 0, 1, 0];
+    ico.extend_from_slice(&(images.len() as u16).to_le_bytes());
+    let mut image_offset = 6 + images.len() * 16;
+    for (header, image) in &images {
+        ico.extend_from_slice(header);
+        ico.extend_from_slice(&(image.len() as u32).to_le_bytes());
+        ico.extend_from_slice(&(image_offset as u32).to_le_bytes());
+        image_offset += image.len();
+    }
+    for (_, image) in &images {
+        ico.extend_from_slice(image);
+    }
+
+    Some(ico)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECTION_RVA: u32 = 0x1000;
+    const SECTION_OFFSET: usize = 0x200;
+
+    /// Resource directory with one entry per (id, target) pair
+    fn directory(entries: &[(u32, u32)]) -> Vec<u8> {
+        let mut dir = vec![0; 12];
+        dir.extend_from_slice(&0u16.to_le_bytes());
+        dir.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        for (id, target) in entries {
+            dir.extend_from_slice(&id.to_le_bytes());
+            dir.extend_from_slice(&target.to_le_bytes());
+        }
+        dir
+    }
+
+    fn data_entry(rva: u32, size: u32) -> Vec<u8> {
+        let mut entry = rva.to_le_bytes().to_vec();
+        entry.extend_from_slice(&size.to_le_bytes());
+        entry.extend_from_slice(&[0; 8]);
+        entry
+    }
+
+    /// A PE32 file whose only resources are one 32x32 icon and its group
+    fn build_pe(icon: &[u8]) -> Vec<u8> {
+        const SUBDIR: u32 = 0x8000_0000;
+
+        let mut group = vec![0, 0, 1, 0, 1, 0];
+        group.extend_from_slice(&[32, 32, 0, 0, 1, 0, 32, 0]);
+        group.extend_from_slice(&(icon.len() as u32).to_le_bytes());
+        group.extend_from_slice(&7u16.to_le_bytes());
+
+        // Layout: root, RT_ICON dirs, RT_GROUP_ICON dirs, data entries, payloads
+        let mut rsrc = directory(&[(RT_ICON, SUBDIR | 32), (RT_GROUP_ICON, SUBDIR | 80)]);
+        rsrc.extend(directory(&[(7, SUBDIR | 56)]));
+        rsrc.extend(directory(&[(1033, 128)]));
+        rsrc.extend(directory(&[(1, SUBDIR | 104)]));
+        rsrc.extend(directory(&[(1033, 144)]));
+        rsrc.extend(data_entry(SECTION_RVA + 160, icon.len() as u32));
+        rsrc.extend(data_entry(
+            SECTION_RVA + 160 + icon.len() as u32,
+            group.len() as u32,
+        ));
+        assert_eq!(rsrc.len(), 160);
+        rsrc.extend_from_slice(icon);
+        rsrc.extend(group);
+
+        let mut pe = vec![0; SECTION_OFFSET];
+        pe[0..2].copy_from_slice(b"MZ");
+        pe[0x3c..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        pe[0x40..0x44].copy_from_slice(b"PE\0\0");
+        let coff = 0x44;
+        pe[coff + 2..coff + 4].copy_from_slice(&1u16.to_le_bytes());
+        let optional_header_size: u16 = 96 + 3 * 8;
+        pe[coff + 16..coff + 18].copy_from_slice(&optional_header_size.to_le_bytes());
+        let optional = coff + 20;
+        pe[optional..optional + 2].copy_from_slice(&0x10bu16.to_le_bytes());
+        pe[optional + 92..optional + 96].copy_from_slice(&3u32.to_le_bytes());
+        let resource_dir = optional + 96 + 2 * 8;
+        pe[resource_dir..resource_dir + 4].copy_from_slice(&SECTION_RVA.to_le_bytes());
+        pe[resource_dir + 4..resource_dir + 8].copy_from_slice(&(rsrc.len() as u32).to_le_bytes());
+
+        let section = optional + optional_header_size as usize;
+        pe[section..section + 5].copy_from_slice(b".rsrc");
+        pe[section + 8..section + 12].copy_from_slice(&(rsrc.len() as u32).to_le_bytes());
+        pe[section + 12..section + 16].copy_from_slice(&SECTION_RVA.to_le_bytes());
+        pe[section + 16..section + 20].copy_from_slice(&(rsrc.len() as u32).to_le_bytes());
+        pe[section + 20..section + 24].copy_from_slice(&(SECTION_OFFSET as u32).to_le_bytes());
+
+        pe.extend(rsrc);
+        pe
+    }
+
+    #[test]
+    fn test_extract_ico_from_pe() {
+        let icon = b"\x89PNG\r\n\x1a\nfake image";
+        let ico = extract_ico(&build_pe(icon)).unwrap();
+
+        assert_eq!(&ico[0..6], &[0, 0, 1, 0, 1, 0]);
+        assert_eq!(&ico[6..8], &[32, 32]);
+        assert_eq!(read_u32(&ico, 6 + 8), Some(icon.len() as u32));
+        assert_eq!(read_u32(&ico, 6 + 12), Some(22));
+        assert_eq!(&ico[22..], icon);
+    }
+
+    #[test]
+    fn test_extract_ico_rejects_non_pe() {
+        assert!(extract_ico(b"not an executable").is_none());
+        let mut pe = build_pe(b"icon");
+        pe[0x40] = b'X';
+        assert!(extract_ico(&pe).is_none());
+    }
+
+    #[test]
+    fn test_rva_to_offset_overflow() {
+        let pe = PeFile {
+            data: &[],
+            sections: vec![Section {
+                virtual_address: 0,
+                virtual_size: u32::MAX,
+                raw_offset: 0x200,
+                raw_size: 0,
+            }],
+            resource_offset: 0,
+        };
+        assert_eq!(pe.rva_to_offset(0x100), Some(0x300));
+        assert_eq!(pe.rva_to_offset(0xffff_ff00), None);
+    }
+}
@@ -2,7 +2,8 @@ use anyhow::{anyhow, Result};
 use std::fs;
 
 use crate::config::game::GameConfig;
-use crate::desktop::icon::{get_or_extract_icon, remove_game_icons};
+use crate::config::migrate::parse_game_config;
+use crate::desktop::icon::{get_or_extract_icon, remove_game_icons, themed_icon_sizes};
 use crate::utils::fs::CellarDirectories;
 
 /// Get the full path to the cellar binary using 'which cellar'
@@ -26,17 +27,40 @@ async fn get_cellar_binary_path() -> Result<String> {
 }
 
 /// Generate a .desktop file for a game
-pub async fn generate_desktop_file(config: &GameConfig, config_name: &str) -> Result<String> {
+pub async fn generate_desktop_file(
+    dirs: &CellarDirectories,
+    config: &GameConfig,
+    config_name: &str,
+) -> Result<String> {
     let cellar_path = get_cellar_binary_path().await?;
-    let exec_command = format!("{} launch {}", cellar_path, config_name);
+    // Shortcuts of an isolated instance have to launch through that instance
+    let exec_command = match &dirs.base_override {
+        Some(base) => {
+            let base = std::path::absolute(base).unwrap_or_else(|_| base.clone());
+            format!(
+                "{} --config-dir {} launch {}",
+                cellar_path,
+                desktop_exec_arg(&base.to_string_lossy()),
+                config_name
+            )
+        }
+        None => format!("{} launch {}", cellar_path, config_name),
+    };
 
     // Determine icon path - try to extract from executable if not explicitly set
     let icon = if let Some(icon_path) = &config.desktop.icon_path {
         // Use explicitly set icon path
         icon_path.to_string_lossy().to_string()
+    } else if config.game.native {
+        // Icons are only extracted from Windows executables
+        "application-x-executable".to_string()
     } else {
         // Try to extract icon from executable
-        match get_or_extract_icon(&config.game.executable, &config.game.name).await {
+        match get_or_extract_icon(dirs, &config.game.executable, &config.game.name).await {
+            // With several sizes installed the theme picks the sharpest one
+            Ok(Some(_)) if themed_icon_sizes(dirs, &config.game.name).len() > 1 => {
+                dirs.get_themed_icon_name(&config.game.name)
+            }
             Ok(Some(extracted_icon)) => extracted_icon.to_string_lossy().to_string(),
             Ok(None) | Err(_) => "application-x-ms-dos-executable".to_string(),
         }
@@ -60,16 +84,45 @@ pub async fn generate_desktop_file(config: &GameConfig, config_name: &str) -> Re
     ))
 }
 
+/// Quote an argument for a desktop entry's `Exec=` key when it needs it
+///
+/// Quoted arguments escape `"`, `` ` ``, `$` and `\` with a backslash, and every
+/// backslash is then escaped again as the key is a string value.
+fn desktop_exec_arg(arg: &str) -> String {
+    let arg = arg.replace('%', "%%");
+    let reserved = |c: char| " \t\n\"'\\><~|&;$*?#()`".contains(c);
+    if !arg.contains(reserved) {
+        return arg;
+    }
+
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        match c {
+            '"' | '`' | '$' => {
+                quoted.push_str(r"\\");
+                quoted.push(c);
+            }
+            '\\' => quoted.push_str(r"\\\\"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
 /// Create a desktop shortcut for a game
-pub async fn create_desktop_shortcut(config: &GameConfig, config_name: &str) -> Result<()> {
+pub async fn create_desktop_shortcut(
+    dirs: &CellarDirectories,
+    config: &GameConfig,
+    config_name: &str,
+) -> Result<()> {
     if !config.desktop.create_shortcut {
         return Ok(());
     }
 
-    let dirs = CellarDirectories::new()?;
     dirs.ensure_all_exist()?;
 
-    let desktop_content = generate_desktop_file(config, config_name).await?;
+    let desktop_content = generate_desktop_file(dirs, config, config_name).await?;
     let shortcut_path = dirs.get_game_shortcut_path(&config.game.name);
 
     fs::write(&shortcut_path, desktop_content)
@@ -80,8 +133,7 @@ pub async fn create_desktop_shortcut(config: &GameConfig, config_name: &str) ->
 }
 
 /// Remove a desktop shortcut for a game
-pub fn remove_desktop_shortcut(game_name: &str) -> Result<()> {
-    let dirs = CellarDirectories::new()?;
+pub fn remove_desktop_shortcut(dirs: &CellarDirectories, game_name: &str) -> Result<()> {
     let shortcut_path = dirs.get_game_shortcut_path(game_name);
 
     if shortcut_path.exists() {
@@ -93,7 +145,7 @@ pub fn remove_desktop_shortcut(game_name: &str) -> Result<()> {
     }
 
     // Also remove extracted icons
-    if let Err(e) = remove_game_icons(game_name) {
+    if let Err(e) = remove_game_icons(dirs, game_name) {
         eprintln!("Warning: Failed to remove icons for {}: {}", game_name, e);
     }
 
@@ -101,8 +153,10 @@ pub fn remove_desktop_shortcut(game_name: &str) -> Result<()> {
 }
 
 /// Sync all desktop shortcuts (recreate all shortcuts for configured games)
-pub async fn sync_desktop_shortcuts() -> Result<()> {
-    let dirs = CellarDirectories::new()?;
+///
+/// With `force_icons`, extracted icons are deleted first so they're extracted
+/// again from the current executables; custom icons are kept.
+pub async fn sync_desktop_shortcuts(dirs: &CellarDirectories, force_icons: bool) -> Result<()> {
     dirs.ensure_all_exist()?;
 
     let games = dirs.list_game_configs()?;
@@ -113,10 +167,18 @@ pub async fn sync_desktop_shortcuts() -> Result<()> {
         let config_path = dirs.get_game_config_path(&game_config_name);
 
         match fs::read_to_string(&config_path) {
-            Ok(content) => match toml::from_str::<GameConfig>(&content) {
+            Ok(content) => match parse_game_config(&content) {
                 Ok(config) => {
                     if config.desktop.create_shortcut {
-                        match create_desktop_shortcut(&config, &game_config_name).await {
+                        if force_icons {
+                            if let Err(e) = remove_game_icons(dirs, &config.game.name) {
+                                eprintln!(
+                                    "Warning: Failed to remove icons for {}: {}",
+                                    config.game.name, e
+                                );
+                            }
+                        }
+                        match create_desktop_shortcut(dirs, &config, &game_config_name).await {
                             Ok(()) => created_count += 1,
                             Err(e) => {
                                 eprintln!(
@@ -147,15 +209,14 @@ pub async fn sync_desktop_shortcuts() -> Result<()> {
 }
 
 /// Check if a desktop shortcut exists for a game
-pub fn shortcut_exists(game_name: &str) -> Result<bool> {
-    let dirs = CellarDirectories::new()?;
+#[allow(dead_code)]
+pub fn shortcut_exists(dirs: &CellarDirectories, game_name: &str) -> Result<bool> {
     let shortcut_path = dirs.get_game_shortcut_path(game_name);
     Ok(shortcut_path.exists())
 }
 
 /// List all existing desktop shortcuts
-pub fn list_desktop_shortcuts() -> Result<Vec<String>> {
-    let dirs = CellarDirectories::new()?;
+pub fn list_desktop_shortcuts(dirs: &CellarDirectories) -> Result<Vec<String>> {
     let mut shortcuts = Vec::new();
 
     if dirs.applications_dir.exists() {
@@ -185,22 +246,34 @@ pub fn list_desktop_shortcuts() -> Result<Vec<String>> {
 mod tests {
     use super::*;
     use tempfile::TempDir;
-    use std::fs;
+
+    #[test]
+    fn test_desktop_exec_arg() {
+        assert_eq!(desktop_exec_arg("/home/me/cellar"), "/home/me/cellar");
+        assert_eq!(
+            desktop_exec_arg("/home/me/My Games"),
+            r#""/home/me/My Games""#
+        );
+        assert_eq!(
+            desktop_exec_arg(r#"/tmp/a"b$c\d%"#),
+            r#""/tmp/a\\"b\\$c\\\\d%%""#
+        );
+    }
 
     #[test]
     fn test_shortcut_exists() {
         let temp_dir = TempDir::new().expect("Failed to create temp dir");
         let game_name = "test-game";
-        
+
         // Mock CellarDirectories for testing
         let applications_dir = temp_dir.path().join("applications");
         fs::create_dir_all(&applications_dir).expect("Failed to create applications dir");
-        
+
         let shortcut_path = applications_dir.join(format!("cellar-{}.desktop", game_name));
-        
+
         // Test when shortcut doesn't exist
         assert!(!shortcut_path.exists());
-        
+
         // Test when shortcut exists
         fs::write(&shortcut_path, "[Desktop Entry]\nType=Application\n")
             .expect("Failed to create test shortcut");
@@ -212,16 +285,16 @@ mod tests {
         let temp_dir = TempDir::new().expect("Failed to create temp dir");
         let applications_dir = temp_dir.path().join("applications");
         fs::create_dir_all(&applications_dir).expect("Failed to create applications dir");
-        
+
         // Create test shortcuts
         let shortcut1 = applications_dir.join("cellar-game1.desktop");
         let shortcut2 = applications_dir.join("cellar-game2.desktop");
         let non_cellar_file = applications_dir.join("other-app.desktop");
-        
+
         fs::write(&shortcut1, "[Desktop Entry]\n").expect("Failed to create shortcut1");
         fs::write(&shortcut2, "[Desktop Entry]\n").expect("Failed to create shortcut2");
         fs::write(&non_cellar_file, "[Desktop Entry]\n").expect("Failed to create non-cellar file");
-        
+
         // Note: This test would need to mock CellarDirectories to work properly
         // For now, it's a basic structure test
     }
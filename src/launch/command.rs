@@ -3,11 +3,19 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 
 use crate::config::game::GameConfig;
+use crate::utils::fs::sanitize_filename;
+
+/// umu `GAMEID` used when a game has no `umu_game_id`; protonfixes applies no fixes for it
+pub const DEFAULT_UMU_GAME_ID: &str = "umu-default";
 
 /// Builds launch commands for games with proper environment variable management
 pub struct CommandBuilder {
     config: GameConfig,
     proton_path: Option<PathBuf>,
+    /// Wine runner to run the game with directly, instead of Proton through umu-run
+    wine_path: Option<PathBuf>,
+    steam_game_id: Option<String>,
+    cache_dir: Option<PathBuf>,
 }
 
 impl CommandBuilder {
@@ -15,43 +23,96 @@ impl CommandBuilder {
         Self {
             config,
             proton_path: None,
+            wine_path: None,
+            steam_game_id: None,
+            cache_dir: None,
         }
     }
 
+    /// Cellar's cache directory, used for the shared DXVK state cache
+    pub fn with_cache_dir(mut self, cache_dir: PathBuf) -> Self {
+        self.cache_dir = Some(cache_dir);
+        self
+    }
+
     pub fn with_proton_path(mut self, proton_path: PathBuf) -> Self {
         self.proton_path = Some(proton_path);
         self
     }
 
+    pub fn with_wine_path(mut self, wine_path: PathBuf) -> Self {
+        self.wine_path = Some(wine_path);
+        self
+    }
+
+    /// Hook the Steam overlay into the game using the ID Steam assigned to the shortcut
+    pub fn with_steam_game_id(mut self, steam_game_id: String) -> Self {
+        self.steam_game_id = Some(steam_game_id);
+        self
+    }
+
     /// Build the complete launch command with all components
     pub fn build(&self) -> Result<LaunchCommand> {
         // First, build the base umu-run command
         let base_command = self.build_base_command()?;
 
-        // Apply Wine environment variables
-        let mut env_vars = self.build_wine_environment()?;
+        // Native games get no Wine or DXVK environment
+        let mut env_vars = HashMap::new();
+        if !self.config.game.native {
+            env_vars.extend(self.build_wine_environment()?);
+            env_vars.extend(self.build_dxvk_environment()?);
+        }
+
+        if let Some(mangohud_config) = self.mangohud_config_string() {
+            env_vars.insert("MANGOHUD_CONFIG".to_string(), mangohud_config);
+        }
+
+        // DXVK won't create the state cache directory itself
+        if !self.config.game.native
+            && self.config.wine_config.dxvk
+            && self.config.dxvk.shared_shader_cache
+        {
+            std::fs::create_dir_all(self.dxvk_state_cache_path())?;
+        }
 
-        // Apply DXVK environment variables
-        env_vars.extend(self.build_dxvk_environment()?);
+        // Steam only sets SteamGameId for non-Steam shortcuts; Proton needs the rest
+        if let Some(steam_game_id) = &self.steam_game_id {
+            for key in ["SteamGameId", "SteamAppId", "SteamOverlayGameId"] {
+                env_vars.insert(key.to_string(), steam_game_id.clone());
+            }
+        }
+
+        // Per-game variables from the config override everything Cellar set above
+        env_vars.extend(self.config.launch.env.clone());
 
         // Process Steam-style launch options with %command% placeholder
         let final_command = self.process_launch_options(base_command, &env_vars)?;
 
+        // Native games run from their own directory, since they have no prefix
+        let working_directory = match (
+            &self.config.launch.working_dir,
+            self.config.game.executable.parent(),
+        ) {
+            (Some(dir), _) => dir.clone(),
+            (None, Some(dir)) if self.config.game.native => dir.to_path_buf(),
+            _ => self.config.game.wine_prefix.clone(),
+        };
+
         Ok(LaunchCommand {
             command: final_command,
             environment: env_vars,
-            working_directory: self.config.game.wine_prefix.clone(),
+            working_directory,
         })
     }
 
-    /// Build the base umu-run command that will replace %command%
+    /// Build the base umu-run (or Wine runner, or native) command that will replace %command%
     fn build_base_command(&self) -> Result<Vec<String>> {
-        let _proton_path = self
-            .proton_path
-            .as_ref()
-            .ok_or_else(|| anyhow!("Proton path is required for game launching"))?;
-
-        let mut cmd = vec!["umu-run".to_string()];
+        let mut cmd = match (&self.wine_path, &self.proton_path) {
+            _ if self.config.game.native => Vec::new(),
+            (Some(wine_path), _) => vec![wine_path.join("bin/wine").to_string_lossy().to_string()],
+            (None, Some(_)) => vec!["umu-run".to_string()],
+            (None, None) => return Err(anyhow!("Proton path is required for game launching")),
+        };
 
         // Add the game executable
         cmd.push(self.config.game.executable.to_string_lossy().to_string());
@@ -82,9 +143,17 @@ impl CommandBuilder {
         }
 
         // Essential Proton environment variables
-        env.insert("PROTON_VERB".to_string(), "waitforexitandrun".to_string());
-        env.insert("GAMEID".to_string(), "umu-default".to_string());
-        env.insert("HOST_LC_ALL".to_string(), "en_US.UTF-8".to_string());
+        if self.wine_path.is_none() {
+            env.insert("PROTON_VERB".to_string(), "waitforexitandrun".to_string());
+            let game_id = self
+                .config
+                .game
+                .umu_game_id
+                .as_deref()
+                .unwrap_or(DEFAULT_UMU_GAME_ID);
+            env.insert("GAMEID".to_string(), game_id.to_string());
+            env.insert("HOST_LC_ALL".to_string(), "en_US.UTF-8".to_string());
+        }
 
         // Wine-specific configurations
         if wine_config.esync {
@@ -99,8 +168,20 @@ impl CommandBuilder {
             env.insert("WINE_LARGE_ADDRESS_AWARE".to_string(), "1".to_string());
         }
 
-        // DXVK DLL overrides if DXVK is enabled
-        if wine_config.dxvk {
+        if wine_config.proton_fsr {
+            env.insert("WINE_FULLSCREEN_FSR".to_string(), "1".to_string());
+            if let Some(strength) = wine_config.proton_fsr_strength {
+                env.insert(
+                    "WINE_FULLSCREEN_FSR_STRENGTH".to_string(),
+                    strength.to_string(),
+                );
+            }
+        }
+
+        // DXVK DLL overrides if DXVK is enabled, unless the game provides its own
+        if let Some(dll_overrides) = &wine_config.dll_overrides {
+            env.insert("WINEDLLOVERRIDES".to_string(), dll_overrides.clone());
+        } else if wine_config.dxvk {
             let dll_overrides = "d3d10core,d3d11,d3d9,dxgi=n,b";
             env.insert("WINEDLLOVERRIDES".to_string(), dll_overrides.to_string());
         } else {
@@ -128,7 +209,7 @@ impl CommandBuilder {
             }
 
             // DXVK state cache path (managed automatically by Cellar)
-            let cache_path = self.config.game.wine_prefix.join("dxvk_cache");
+            let cache_path = self.dxvk_state_cache_path();
             env.insert(
                 "DXVK_STATE_CACHE_PATH".to_string(),
                 cache_path.to_string_lossy().to_string(),
@@ -138,6 +219,41 @@ impl CommandBuilder {
         Ok(env)
     }
 
+    /// Assemble `MANGOHUD_CONFIG` from the `[mangohud]` section, if MangoHud is on and configured
+    fn mangohud_config_string(&self) -> Option<String> {
+        if !self.config.launch.mangohud {
+            return None;
+        }
+
+        let mangohud = &self.config.mangohud;
+        let mut options = Vec::new();
+        if let Some(fps_limit) = mangohud.fps_limit {
+            options.push(format!("fps_limit={fps_limit}"));
+        }
+        if let Some(position) = &mangohud.position {
+            options.push(format!("position={position}"));
+        }
+        if let Some(config_string) = &mangohud.config_string {
+            options.push(config_string.trim_matches(',').to_string());
+        }
+        options.retain(|option| !option.is_empty());
+
+        if options.is_empty() {
+            None
+        } else {
+            Some(options.join(","))
+        }
+    }
+
+    fn dxvk_state_cache_path(&self) -> PathBuf {
+        match &self.cache_dir {
+            Some(cache_dir) if self.config.dxvk.shared_shader_cache => cache_dir
+                .join("dxvk_cache")
+                .join(sanitize_filename(&self.config.game.name)),
+            _ => self.config.game.wine_prefix.join("dxvk_cache"),
+        }
+    }
+
     /// Process Steam-style launch options with %command% placeholder
     fn process_launch_options(
         &self,
@@ -155,7 +271,8 @@ impl CommandBuilder {
         }
 
         // Parse launch options into tokens
-        let tokens = self.parse_launch_options(launch_options)?;
+        let tokens =
+            parse_launch_options(launch_options, self.config.launch.unsafe_launch_options)?;
 
         // Find and replace %command% placeholder
         let mut final_command = Vec::with_capacity(tokens.len() + base_command.len());
@@ -187,130 +304,6 @@ impl CommandBuilder {
         Ok(gamemode_wrapped)
     }
 
-    /// Parse launch options string into tokens, handling quotes and environment variables safely
-    fn parse_launch_options(&self, launch_options: &str) -> Result<Vec<String>> {
-        let mut tokens = Vec::new();
-        let mut current_token = String::new();
-        let mut in_quotes = false;
-        let chars = launch_options.chars().peekable();
-
-        for ch in chars {
-            match ch {
-                '"' if !in_quotes => {
-                    in_quotes = true;
-                }
-                '"' if in_quotes => {
-                    in_quotes = false;
-                }
-                ' ' if !in_quotes => {
-                    if !current_token.is_empty() {
-                        // Validate and sanitize token before adding
-                        let sanitized = self.sanitize_token(&current_token)?;
-                        tokens.push(sanitized);
-                        current_token.clear();
-                    }
-                }
-                _ => {
-                    current_token.push(ch);
-                }
-            }
-        }
-
-        if !current_token.is_empty() {
-            let sanitized = self.sanitize_token(&current_token)?;
-            tokens.push(sanitized);
-        }
-
-        if in_quotes {
-            return Err(anyhow!("Unclosed quote in launch options"));
-        }
-
-        Ok(tokens)
-    }
-
-    /// Sanitize a command token to prevent shell injection
-    fn sanitize_token(&self, token: &str) -> Result<String> {
-        // Check for dangerous characters and patterns
-        let dangerous_chars = [
-            '|', '&', ';', '`', '$', '(', ')', '{', '}', '[', ']', '*', '?', '~', '\n', '\r', '\t',
-            '\'', '"',
-        ];
-
-        for ch in dangerous_chars {
-            if token.contains(ch) {
-                return Err(anyhow!(
-                    "Dangerous character '{}' found in launch option: {}",
-                    ch,
-                    token
-                ));
-            }
-        }
-
-        // Check for dangerous patterns
-        let dangerous_patterns = ["../", "./", "//", "\\\\", "\n", "\r"];
-        for pattern in dangerous_patterns {
-            if token.contains(pattern) {
-                return Err(anyhow!(
-                    "Dangerous pattern '{}' found in launch option: {}",
-                    pattern,
-                    token
-                ));
-            }
-        }
-
-        // Ensure the token doesn't start with dangerous prefixes
-        let dangerous_prefixes = ["-", "--"];
-        for prefix in dangerous_prefixes {
-            if token.starts_with(prefix) && token != "%command%" {
-                // Allow well-known safe options only
-                if !self.is_safe_option(token) {
-                    return Err(anyhow!("Potentially dangerous option: {}", token));
-                }
-            }
-        }
-
-        Ok(token.to_string())
-    }
-
-    /// Check if an option is in the allowlist of safe options
-    fn is_safe_option(&self, option: &str) -> bool {
-        // Allowlist of safe command line options
-        let safe_options = [
-            // Common safe options
-            "--fullscreen",
-            "--windowed",
-            "--width",
-            "--height",
-            "--vsync",
-            "--no-vsync",
-            // Mangohud options
-            "--dlsym",
-            // Gamescope options
-            "-f",
-            "-w",
-            "-h",
-            "-W",
-            "-H",
-            "-r",
-            "-F",
-            "-S",
-            "-n",
-            "-b",
-            "--force-grab-cursor",
-            "--expose-wayland",
-            "--hdr-enabled",
-            "--adaptive-sync",
-            "--immediate-flips",
-            "--mangoapp",
-        ];
-
-        safe_options.contains(&option) ||
-        // Allow numeric values
-        option.parse::<i32>().is_ok() ||
-        // Allow resolution patterns like "1920x1080"
-        option.matches('x').count() == 1 && option.split('x').all(|s| s.parse::<u32>().is_ok())
-    }
-
     /// Wrap command with mangohud if enabled (but not when gamescope is enabled)
     fn wrap_with_mangohud(&self, command: Vec<String>) -> Result<Vec<String>> {
         if !self.config.launch.mangohud || self.config.gamescope.enabled {
@@ -433,6 +426,152 @@ pub struct LaunchCommand {
     pub working_directory: PathBuf,
 }
 
+/// Check launch options the way a launch would, without building a command
+///
+/// Catches unclosed quotes, unsafe tokens and repeated `%command%` placeholders
+/// so configs can be rejected when they're saved rather than at launch.
+pub fn validate_launch_options(launch_options: &str, unsafe_options: bool) -> Result<()> {
+    let tokens = parse_launch_options(launch_options, unsafe_options)?;
+    if tokens.iter().filter(|t| *t == "%command%").count() > 1 {
+        return Err(anyhow!("Multiple %command% placeholders found"));
+    }
+    Ok(())
+}
+
+/// Parse launch options string into tokens, handling quotes and environment variables safely
+///
+/// `unsafe_options` lets through options that aren't on the allowlist, as set by
+/// `launch.unsafe_launch_options`.
+pub fn parse_launch_options(launch_options: &str, unsafe_options: bool) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut current_token = String::new();
+    let mut in_quotes = false;
+    let chars = launch_options.chars().peekable();
+
+    for ch in chars {
+        match ch {
+            '"' if !in_quotes => {
+                in_quotes = true;
+            }
+            '"' if in_quotes => {
+                in_quotes = false;
+            }
+            ' ' if !in_quotes => {
+                if !current_token.is_empty() {
+                    // Validate and sanitize token before adding
+                    let sanitized = sanitize_token(&current_token, unsafe_options)?;
+                    tokens.push(sanitized);
+                    current_token.clear();
+                }
+            }
+            _ => {
+                current_token.push(ch);
+            }
+        }
+    }
+
+    if !current_token.is_empty() {
+        let sanitized = sanitize_token(&current_token, unsafe_options)?;
+        tokens.push(sanitized);
+    }
+
+    if in_quotes {
+        return Err(anyhow!("Unclosed quote in launch options"));
+    }
+
+    Ok(tokens)
+}
+
+/// Sanitize a command token to prevent shell injection
+fn sanitize_token(token: &str, unsafe_options: bool) -> Result<String> {
+    // Check for dangerous characters and patterns
+    let dangerous_chars = [
+        '|', '&', ';', '`', '$', '(', ')', '{', '}', '[', ']', '*', '?', '~', '\n', '\r', '\t',
+        '\'', '"',
+    ];
+
+    for ch in dangerous_chars {
+        if token.contains(ch) {
+            return Err(anyhow!(
+                "Dangerous character '{}' found in launch option: {}",
+                ch,
+                token
+            ));
+        }
+    }
+
+    // Check for dangerous patterns
+    let dangerous_patterns = ["../", "./", "//", "\\\\", "\n", "\r"];
+    for pattern in dangerous_patterns {
+        if token.contains(pattern) {
+            return Err(anyhow!(
+                "Dangerous pattern '{}' found in launch option: {}",
+                pattern,
+                token
+            ));
+        }
+    }
+
+    if unsafe_options {
+        return Ok(token.to_string());
+    }
+
+    // Ensure the token doesn't start with dangerous prefixes
+    let dangerous_prefixes = ["-", "--"];
+    for prefix in dangerous_prefixes {
+        if token.starts_with(prefix) && token != "%command%" {
+            // Allow well-known safe options only
+            if !is_safe_option(token) {
+                return Err(anyhow!(
+                    "Potentially dangerous option: {} (set launch.unsafe_launch_options = true in the game's config to allow it)",
+                    token
+                ));
+            }
+        }
+    }
+
+    Ok(token.to_string())
+}
+
+/// Check if an option is in the allowlist of safe options
+fn is_safe_option(option: &str) -> bool {
+    // Allowlist of safe command line options
+    let safe_options = [
+        // Common safe options
+        "--fullscreen",
+        "--windowed",
+        "--width",
+        "--height",
+        "--vsync",
+        "--no-vsync",
+        // Mangohud options
+        "--dlsym",
+        // Gamescope options
+        "-f",
+        "-w",
+        "-h",
+        "-W",
+        "-H",
+        "-r",
+        "-F",
+        "-S",
+        "-n",
+        "-b",
+        "--force-grab-cursor",
+        "--expose-wayland",
+        "--hdr-enabled",
+        "--adaptive-sync",
+        "--immediate-flips",
+        "--mangoapp",
+    ];
+
+    safe_options.contains(&option) ||
+    // Allow numeric values
+    option.parse::<i32>().is_ok() ||
+    // Allow resolution patterns like "1920x1080"
+    option.matches('x').count() == 1 && option.split('x').all(|s| s.parse::<u32>().is_ok())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -440,23 +579,33 @@ mod tests {
 
     fn create_test_config() -> GameConfig {
         GameConfig {
+            version: CURRENT_CONFIG_VERSION,
             game: GameInfo {
                 name: "Test Game".to_string(),
                 executable: PathBuf::from("/path/to/game.exe"),
                 wine_prefix: PathBuf::from("/path/to/prefix"),
                 proton_version: "GE-Proton8-32".to_string(),
                 dxvk_version: None,
+                umu_game_id: None,
+                native: false,
             },
             launch: LaunchConfig {
                 launch_options: "PROTON_ENABLE_WAYLAND=1 gamemoderun %command%".to_string(),
                 game_args: vec!["--windowed".to_string(), "--dx11".to_string()],
                 gamemode: false,
                 mangohud: false,
+                env: HashMap::new(),
+                pre_launch: Vec::new(),
+                post_launch: Vec::new(),
+                working_dir: None,
+                unsafe_launch_options: false,
             },
             wine_config: WineConfig::default(),
             dxvk: DxvkConfig::default(),
+            mangohud: MangohudConfig::default(),
             gamescope: GamescopeConfig::default(),
             desktop: DesktopConfig::default(),
+            stats: GameStats::default(),
             installation: None,
         }
     }
@@ -487,6 +636,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_wine_runner_launch_command() {
+        let config = create_test_config();
+        let launch_command = CommandBuilder::new(config)
+            .with_wine_path(PathBuf::from("/runners/wine/GE-Proton8-26"))
+            .build()
+            .unwrap();
+
+        let args = &launch_command.command;
+        assert!(args.contains(&"/runners/wine/GE-Proton8-26/bin/wine".to_string()));
+        assert!(!args.contains(&"umu-run".to_string()));
+        assert!(launch_command.environment.contains_key("WINEPREFIX"));
+        assert!(!launch_command.environment.contains_key("PROTONPATH"));
+        assert!(!launch_command.environment.contains_key("PROTON_VERB"));
+    }
+
+    #[test]
+    fn test_native_launch_command() {
+        let mut config = create_test_config();
+        config.game.native = true;
+        config.game.executable = PathBuf::from("/games/native/game.x86_64");
+        config.launch.launch_options = String::new();
+        config.launch.game_args = vec!["--windowed".to_string()];
+        config.launch.mangohud = true;
+        config.gamescope.enabled = true;
+
+        // No Proton path needed
+        let launch_command = CommandBuilder::new(config).build().unwrap();
+
+        let args = &launch_command.command;
+        assert_eq!(args[0], "gamescope");
+        assert!(args.contains(&"--mangoapp".to_string()));
+        assert_eq!(
+            args[args.len() - 2..],
+            ["/games/native/game.x86_64", "--windowed"]
+        );
+        assert!(!args.contains(&"umu-run".to_string()));
+        assert!(!launch_command.environment.contains_key("WINEPREFIX"));
+        assert!(!launch_command.environment.contains_key("WINEDLLOVERRIDES"));
+        assert_eq!(
+            launch_command.working_directory,
+            PathBuf::from("/games/native")
+        );
+    }
+
+    #[test]
+    fn test_working_dir() {
+        let mut config = create_test_config();
+        config.launch.working_dir = Some(PathBuf::from("/games/witcher"));
+
+        let launch_command = CommandBuilder::new(config)
+            .with_proton_path(PathBuf::from("/path/to/proton"))
+            .build()
+            .unwrap();
+        assert_eq!(
+            launch_command.working_directory,
+            PathBuf::from("/games/witcher")
+        );
+    }
+
     #[test]
     fn test_command_builder_creation() {
         let config = create_test_config();
@@ -502,7 +711,7 @@ mod tests {
     #[test]
     fn test_processed_command_types() {
         // Test direct command creation
-        let direct_cmd = vec!["umu-run".to_string(), "game.exe".to_string()];
+        let direct_cmd = ["umu-run".to_string(), "game.exe".to_string()];
 
         assert_eq!(direct_cmd.len(), 2);
         assert_eq!(direct_cmd[0], "umu-run");
@@ -568,6 +777,214 @@ mod tests {
         assert!(args.contains(&"umu-run".to_string()));
     }
 
+    #[test]
+    fn test_game_env_overrides_defaults() {
+        let mut config = create_test_config();
+        config
+            .launch
+            .env
+            .insert("VKD3D_CONFIG".to_string(), "dxr".to_string());
+        config
+            .launch
+            .env
+            .insert("GAMEID".to_string(), "umu-1091500".to_string());
+        config
+            .launch
+            .env
+            .insert("DXVK_HUD".to_string(), "fps".to_string());
+
+        let builder =
+            CommandBuilder::new(config).with_proton_path(PathBuf::from("/path/to/proton"));
+        let env = builder.build().unwrap().environment;
+
+        assert_eq!(env.get("VKD3D_CONFIG").unwrap(), "dxr");
+        assert_eq!(env.get("GAMEID").unwrap(), "umu-1091500");
+        assert_eq!(env.get("DXVK_HUD").unwrap(), "fps");
+        // Untouched defaults are still present
+        assert_eq!(env.get("WINEARCH").unwrap(), "win64");
+    }
+
+    #[test]
+    fn test_dll_overrides() {
+        let config = create_test_config();
+        let env = CommandBuilder::new(config.clone())
+            .with_proton_path(PathBuf::from("/path/to/proton"))
+            .build()
+            .unwrap()
+            .environment;
+        assert_eq!(
+            env.get("WINEDLLOVERRIDES").unwrap(),
+            "d3d10core,d3d11,d3d9,dxgi=n,b"
+        );
+
+        let mut config = config;
+        config.wine_config.dll_overrides = Some("d3d11,dxgi=n,b;d3d9=d".to_string());
+        let env = CommandBuilder::new(config)
+            .with_proton_path(PathBuf::from("/path/to/proton"))
+            .build()
+            .unwrap()
+            .environment;
+        assert_eq!(
+            env.get("WINEDLLOVERRIDES").unwrap(),
+            "d3d11,dxgi=n,b;d3d9=d"
+        );
+    }
+
+    #[test]
+    fn test_umu_game_id() {
+        let mut config = create_test_config();
+        let env = CommandBuilder::new(config.clone())
+            .with_proton_path(PathBuf::from("/path/to/proton"))
+            .build()
+            .unwrap()
+            .environment;
+        assert_eq!(env.get("GAMEID").unwrap(), DEFAULT_UMU_GAME_ID);
+
+        config.game.umu_game_id = Some("umu-1091500".to_string());
+        let env = CommandBuilder::new(config)
+            .with_proton_path(PathBuf::from("/path/to/proton"))
+            .build()
+            .unwrap()
+            .environment;
+        assert_eq!(env.get("GAMEID").unwrap(), "umu-1091500");
+    }
+
+    #[test]
+    fn test_proton_fsr_environment() {
+        let mut config = create_test_config();
+        config.wine_config.proton_fsr_strength = Some(4);
+        let env = CommandBuilder::new(config.clone())
+            .with_proton_path(PathBuf::from("/path/to/proton"))
+            .build()
+            .unwrap()
+            .environment;
+        assert!(!env.contains_key("WINE_FULLSCREEN_FSR"));
+        assert!(!env.contains_key("WINE_FULLSCREEN_FSR_STRENGTH"));
+
+        config.wine_config.proton_fsr = true;
+        let env = CommandBuilder::new(config)
+            .with_proton_path(PathBuf::from("/path/to/proton"))
+            .build()
+            .unwrap()
+            .environment;
+        assert_eq!(env.get("WINE_FULLSCREEN_FSR").unwrap(), "1");
+        assert_eq!(env.get("WINE_FULLSCREEN_FSR_STRENGTH").unwrap(), "4");
+    }
+
+    #[test]
+    fn test_steam_overlay_environment() {
+        let env = CommandBuilder::new(create_test_config())
+            .with_proton_path(PathBuf::from("/path/to/proton"))
+            .build()
+            .unwrap()
+            .environment;
+        assert!(!env.contains_key("SteamAppId"));
+
+        let env = CommandBuilder::new(create_test_config())
+            .with_proton_path(PathBuf::from("/path/to/proton"))
+            .with_steam_game_id("12345678901234567890".to_string())
+            .build()
+            .unwrap()
+            .environment;
+        assert_eq!(env.get("SteamGameId").unwrap(), "12345678901234567890");
+        assert_eq!(env.get("SteamAppId").unwrap(), "12345678901234567890");
+        assert_eq!(
+            env.get("SteamOverlayGameId").unwrap(),
+            "12345678901234567890"
+        );
+    }
+
+    #[test]
+    fn test_shared_shader_cache() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = create_test_config();
+
+        let env = CommandBuilder::new(config.clone())
+            .with_proton_path(PathBuf::from("/path/to/proton"))
+            .with_cache_dir(temp_dir.path().to_path_buf())
+            .build()
+            .unwrap()
+            .environment;
+        assert_eq!(
+            env.get("DXVK_STATE_CACHE_PATH").unwrap(),
+            "/path/to/prefix/dxvk_cache"
+        );
+
+        config.dxvk.shared_shader_cache = true;
+        let env = CommandBuilder::new(config)
+            .with_proton_path(PathBuf::from("/path/to/proton"))
+            .with_cache_dir(temp_dir.path().to_path_buf())
+            .build()
+            .unwrap()
+            .environment;
+        let expected = temp_dir.path().join("dxvk_cache").join("test_game");
+        assert_eq!(
+            env.get("DXVK_STATE_CACHE_PATH").unwrap(),
+            &expected.to_string_lossy().to_string()
+        );
+        assert!(expected.is_dir());
+    }
+
+    #[test]
+    fn test_mangohud_config() {
+        let mut config = create_test_config();
+        config.mangohud.fps_limit = Some(60);
+        config.mangohud.position = Some("top-right".to_string());
+        config.mangohud.config_string = Some("cpu_temp,gpu_temp,".to_string());
+
+        // Ignored while MangoHud itself is off
+        let builder =
+            CommandBuilder::new(config.clone()).with_proton_path(PathBuf::from("/path/to/proton"));
+        let launch_command = builder.build().unwrap();
+        assert!(!launch_command.environment.contains_key("MANGOHUD_CONFIG"));
+
+        config.launch.mangohud = true;
+        let builder =
+            CommandBuilder::new(config).with_proton_path(PathBuf::from("/path/to/proton"));
+        let launch_command = builder.build().unwrap();
+        assert_eq!(
+            launch_command.environment.get("MANGOHUD_CONFIG"),
+            Some(&"fps_limit=60,position=top-right,cpu_temp,gpu_temp".to_string())
+        );
+    }
+
+    #[test]
+    fn test_validate_launch_options() {
+        assert!(validate_launch_options("", false).is_ok());
+        assert!(
+            validate_launch_options("PROTON_ENABLE_WAYLAND=1 gamemoderun %command%", false).is_ok()
+        );
+
+        let err = validate_launch_options("\"%command% --windowed", false).unwrap_err();
+        assert!(err.to_string().contains("Unclosed quote"));
+
+        let err = validate_launch_options("%command% %command%", false).unwrap_err();
+        assert!(err.to_string().contains("Multiple %command%"));
+
+        assert!(validate_launch_options("%command%; rm -rf ~", false).is_err());
+    }
+
+    #[test]
+    fn test_unsafe_launch_options() {
+        let err = validate_launch_options("%command% -dx12", false).unwrap_err();
+        assert!(err.to_string().contains("unsafe_launch_options"));
+        assert!(validate_launch_options("%command% -dx12 +connect", true).is_ok());
+        assert!(validate_launch_options("%command% -dx12; rm -rf ~", true).is_err());
+
+        let mut config = create_test_config();
+        config.launch.launch_options = "%command% -dx12".to_string();
+        config.launch.game_args.clear();
+        config.launch.unsafe_launch_options = true;
+        let launch_command = CommandBuilder::new(config)
+            .with_proton_path(PathBuf::from("/path/to/proton"))
+            .build()
+            .unwrap();
+        assert_eq!(
+            launch_command.command,
+            vec!["umu-run", "/path/to/game.exe", "-dx12"]
+        );
+    }
+
     #[test]
     fn test_gamemode_disabled() {
         let mut config = create_test_config();
@@ -1,14 +1,59 @@
 use anyhow::{anyhow, Result};
-use std::path::PathBuf;
-use std::process::Stdio;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+use std::time::{Duration, Instant};
 use tokio::process::Command;
+use tokio::signal::unix::{signal, SignalKind};
 
 use crate::config::game::GameConfig;
+use crate::config::migrate::parse_game_config;
+use crate::config::validation::{dxvk_async_warning, validate_dll_overrides};
 use crate::runners::proton::ProtonManager;
-use crate::runners::RunnerManager;
-use crate::utils::fs::CellarDirectories;
+use crate::runners::{find_game_runner, Runner, RunnerManager, RunnerType};
+use crate::utils::fs::{write_file_atomic, CellarDirectories};
+use crate::utils::output::{filter_wine_errors, is_verbose, print_verbose_stderr};
+use crate::utils::prefix::{detect_prefix_arch, PrefixArch};
+use crate::utils::tools::{command_available, require_umu_run, tool_version, EXTERNAL_TOOLS};
 
 use super::command::{CommandBuilder, LaunchCommand};
+use super::lock::LaunchLock;
+use super::process::signal_group;
+
+/// Sessions shorter than this are treated as a failed start and don't count as playtime
+const MIN_PLAY_SESSION: Duration = Duration::from_secs(10);
+
+/// Per-invocation launch behaviour that isn't stored in the game's config
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LaunchOptions {
+    /// Build and print the launch command without executing it
+    pub dry_run: bool,
+    /// Write a diagnostics report to the logs directory if the launch fails
+    pub capture_crash: bool,
+    /// Pass Steam's game ID through so the overlay works from a non-Steam shortcut
+    pub steam_overlay: bool,
+    /// Turn gamescope on or off for this launch only
+    pub gamescope: Option<bool>,
+    /// Turn gamemode on or off for this launch only
+    pub gamemode: Option<bool>,
+    /// Turn the MangoHud overlay on or off for this launch only
+    pub mangohud: Option<bool>,
+}
+
+impl LaunchOptions {
+    /// Apply the one-off toggles to a copy of the game's config; the saved config is untouched
+    fn apply_overrides(&self, config: &mut GameConfig) {
+        if let Some(gamescope) = self.gamescope {
+            config.gamescope.enabled = gamescope;
+        }
+        if let Some(gamemode) = self.gamemode {
+            config.launch.gamemode = gamemode;
+        }
+        if let Some(mangohud) = self.mangohud {
+            config.launch.mangohud = mangohud;
+        }
+    }
+}
 
 /// Handles the execution of games with proper Proton integration
 pub struct GameLauncher {
@@ -16,39 +61,318 @@ pub struct GameLauncher {
 }
 
 impl GameLauncher {
-    pub fn new() -> Result<Self> {
-        let dirs = CellarDirectories::new()?;
-        Ok(Self { dirs })
+    pub fn new(dirs: CellarDirectories) -> Self {
+        Self { dirs }
     }
 
     /// Launch a game using its configuration
-    pub async fn launch_game(&self, game_config: &GameConfig) -> Result<()> {
+    #[tracing::instrument(skip_all, fields(game = %game_config.game.name))]
+    pub async fn launch_game(
+        &self,
+        game_config: &GameConfig,
+        options: LaunchOptions,
+    ) -> Result<()> {
+        let mut game_config = game_config.clone();
+        options.apply_overrides(&mut game_config);
+        tracing::debug!(?options, "Launch options");
+        let game_config = &game_config;
+        let native = game_config.game.native;
+
         println!("Launching game: {}", game_config.game.name);
         println!("  Executable: {}", game_config.game.executable.display());
-        println!("  Wine Prefix: {}", game_config.game.wine_prefix.display());
-        println!("  Proton Version: {}", game_config.game.proton_version);
+        if native {
+            println!("  Native Linux game");
+        } else {
+            println!("  Wine Prefix: {}", game_config.game.wine_prefix.display());
+            println!("  Proton Version: {}", game_config.game.proton_version);
+        }
 
         // Validate the configuration before launching
         self.validate_launch_config(game_config)?;
 
-        // Find the Proton installation
-        let proton_path = self
-            .find_proton_installation(&game_config.game.proton_version)
-            .await?;
-        println!("  Proton Path: {}", proton_path.display());
-
         // Build the launch command
-        let launch_command = CommandBuilder::new(game_config.clone())
-            .with_proton_path(proton_path)
-            .build()?;
+        let mut builder =
+            CommandBuilder::new(game_config.clone()).with_cache_dir(self.dirs.get_cache_path());
+
+        // Find the Proton installation, or a Wine runner to run directly
+        let mut needs_umu = false;
+        if !native {
+            let runner = self
+                .find_runner_installation(&game_config.game.proton_version)
+                .await?;
+
+            tracing::debug!(
+                runner = %runner.name,
+                path = %runner.path.display(),
+                "Found runner"
+            );
+            let runner_name = runner
+                .path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if let Some(warning) = dxvk_async_warning(game_config, &runner_name) {
+                eprintln!("Warning: {warning}");
+            }
+
+            if runner.runner_type == RunnerType::Wine {
+                println!("  Wine Path: {}", runner.path.display());
+                builder = builder.with_wine_path(runner.path);
+            } else {
+                println!("  Proton Path: {}", runner.path.display());
+                self.warn_if_not_proton_prefix(game_config);
+                builder = builder.with_proton_path(runner.path);
+                needs_umu = true;
+            }
+        }
+        if options.steam_overlay {
+            match std::env::var("SteamGameId") {
+                Ok(steam_game_id) => builder = builder.with_steam_game_id(steam_game_id),
+                Err(_) => eprintln!(
+                    "Warning: --steam-overlay only works when Cellar is launched by Steam (SteamGameId is not set)"
+                ),
+            }
+        }
+        let mut launch_command = builder.build()?;
+        tracing::debug!(
+            command = ?launch_command.command,
+            working_directory = %launch_command.working_directory.display(),
+            "Built launch command"
+        );
+        tracing::trace!(environment = ?launch_command.environment, "Launch environment");
+
+        if options.dry_run {
+            self.print_dry_run(&launch_command);
+            return Ok(());
+        }
+
+        if needs_umu {
+            require_umu_run().await?;
+        }
+
+        // Held until this function returns, so the lock is released on every exit path
+        let lock = LaunchLock::acquire(
+            &self.dirs.get_game_lock_path(&game_config.game.name),
+            &game_config.game.name,
+        )?;
+
+        // Have Proton write its own log so it can be bundled into the report
+        let proton_log_dir = self.proton_log_dir(&game_config.game.name);
+        if options.capture_crash && !native {
+            self.prepare_proton_log_dir(&proton_log_dir)?;
+            launch_command
+                .environment
+                .insert("PROTON_LOG".to_string(), "1".to_string());
+            launch_command.environment.insert(
+                "PROTON_LOG_DIR".to_string(),
+                proton_log_dir.to_string_lossy().to_string(),
+            );
+        }
+
+        for hook in &game_config.launch.pre_launch {
+            self.run_hook(hook)
+                .await
+                .map_err(|e| anyhow!("Pre-launch command failed, not launching: {}", e))?;
+        }
 
         // Execute the command
-        self.execute_launch_command(&launch_command).await?;
+        let started = Instant::now();
+        let output = self.execute_launch_command(&launch_command, &lock).await;
+        match &output {
+            Ok(output) => tracing::info!(
+                status = %output.status,
+                elapsed_secs = started.elapsed().as_secs(),
+                "Game exited"
+            ),
+            Err(e) => tracing::debug!(error = %e, "Game failed to start"),
+        }
+
+        if output.is_ok() {
+            if let Err(e) = self.record_play(&game_config.game.name, started.elapsed()) {
+                eprintln!("Warning: Failed to update play statistics: {e}");
+            }
+        }
+
+        for hook in &game_config.launch.post_launch {
+            if let Err(e) = self.run_hook(hook).await {
+                eprintln!("Warning: Post-launch command failed: {e}");
+            }
+        }
+
+        if options.capture_crash {
+            let failure = match &output {
+                Ok(output) if output.status.success() => None,
+                Ok(output) => Some((
+                    output.status.to_string(),
+                    String::from_utf8_lossy(&output.stderr).to_string(),
+                )),
+                Err(e) => Some(("failed to start".to_string(), e.to_string())),
+            };
+
+            if let Some((status, stderr)) = failure {
+                match self
+                    .write_crash_report(
+                        game_config,
+                        &launch_command,
+                        &status,
+                        &stderr,
+                        &proton_log_dir,
+                    )
+                    .await
+                {
+                    Ok(report_path) => {
+                        println!("Crash report written to: {}", report_path.display())
+                    }
+                    Err(e) => eprintln!("Warning: Failed to write crash report: {}", e),
+                }
+            }
+        }
+
+        self.handle_command_output(&game_config.game.name, &output?)?;
 
         println!("Game exited.");
         Ok(())
     }
 
+    /// Run a pre- or post-launch hook through the shell
+    async fn run_hook(&self, hook: &str) -> Result<()> {
+        println!("Running hook: {hook}");
+
+        let status = Command::new("sh").arg("-c").arg(hook).status().await?;
+        if !status.success() {
+            return Err(anyhow!("'{}' exited with {}", hook, status));
+        }
+
+        Ok(())
+    }
+
+    fn proton_log_dir(&self, game_name: &str) -> PathBuf {
+        self.dirs
+            .get_logs_path()
+            .join("proton")
+            .join(self.dirs.get_game_file_stem(game_name))
+    }
+
+    /// Create the Proton log directory, clearing logs left over from earlier launches
+    fn prepare_proton_log_dir(&self, proton_log_dir: &Path) -> Result<()> {
+        self.dirs.ensure_dir_exists(proton_log_dir)?;
+
+        for entry in std::fs::read_dir(proton_log_dir)?.flatten() {
+            if entry.path().extension().and_then(|s| s.to_str()) == Some("log") {
+                std::fs::remove_file(entry.path())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Bundle everything needed for a bug report into a single file in the logs directory
+    async fn write_crash_report(
+        &self,
+        game_config: &GameConfig,
+        launch_command: &LaunchCommand,
+        status: &str,
+        stderr: &str,
+        proton_log_dir: &Path,
+    ) -> Result<PathBuf> {
+        let logs_path = self.dirs.get_logs_path();
+        self.dirs.ensure_dir_exists(&logs_path)?;
+
+        let now = chrono::Local::now();
+        let report_path = logs_path.join(format!(
+            "{}-crash-{}.txt",
+            self.dirs.get_game_file_stem(&game_config.game.name),
+            now.format("%Y%m%d-%H%M%S")
+        ));
+
+        let mut report = String::new();
+        writeln!(report, "Cellar crash report")?;
+        writeln!(report, "Generated: {}", now.to_rfc3339())?;
+        writeln!(report, "Cellar version: {}", env!("CARGO_PKG_VERSION"))?;
+        writeln!(report, "Game: {}", game_config.game.name)?;
+        writeln!(report, "Exit status: {status}")?;
+
+        writeln!(report, "\n== Versions ==")?;
+        if let Ok(kernel) = std::fs::read_to_string("/proc/sys/kernel/osrelease") {
+            writeln!(report, "Kernel: {}", kernel.trim())?;
+        }
+        if let Some(os) = std::fs::read_to_string("/etc/os-release")
+            .ok()
+            .and_then(|release| {
+                release.lines().find_map(|line| {
+                    line.strip_prefix("PRETTY_NAME=")
+                        .map(|name| name.trim_matches('"').to_string())
+                })
+            })
+        {
+            writeln!(report, "OS: {os}")?;
+        }
+        for tool in EXTERNAL_TOOLS {
+            let version = if !command_available(tool.program).await {
+                "not found".to_string()
+            } else {
+                tool_version(tool.program)
+                    .await
+                    .unwrap_or_else(|| "installed (version unknown)".to_string())
+            };
+            writeln!(report, "{}: {version}", tool.program)?;
+        }
+
+        writeln!(report, "\n== Launch command ==")?;
+        writeln!(report, "Command: {:?}", launch_command.command)?;
+        writeln!(
+            report,
+            "Working directory: {}",
+            launch_command.working_directory.display()
+        )?;
+        let mut environment: Vec<_> = launch_command.environment.iter().collect();
+        environment.sort();
+        writeln!(report, "Environment:")?;
+        for (key, value) in environment {
+            writeln!(report, "  {key}={value}")?;
+        }
+
+        writeln!(report, "\n== Installed Proton runners ==")?;
+        let proton_manager = ProtonManager::new(self.dirs.get_runners_path());
+        match proton_manager.discover_local_runners().await {
+            Ok(runners) if runners.is_empty() => writeln!(report, "(none)")?,
+            Ok(runners) => {
+                for runner in runners {
+                    writeln!(
+                        report,
+                        "{} ({}) at {}",
+                        runner.name,
+                        runner.version,
+                        runner.path.display()
+                    )?;
+                }
+            }
+            Err(e) => writeln!(report, "Failed to discover runners: {e}")?,
+        }
+
+        writeln!(report, "\n== Game configuration ==")?;
+        match toml::to_string_pretty(game_config) {
+            Ok(config_toml) => report.push_str(&config_toml),
+            Err(e) => writeln!(report, "Failed to serialize config: {e}")?,
+        }
+
+        writeln!(report, "\n== stderr ==")?;
+        report.push_str(stderr);
+
+        if let Ok(entries) = std::fs::read_dir(proton_log_dir) {
+            for entry in entries.flatten() {
+                let log_path = entry.path();
+                if log_path.extension().and_then(|s| s.to_str()) == Some("log") {
+                    writeln!(report, "\n== Proton log ({}) ==", log_path.display())?;
+                    report.push_str(&String::from_utf8_lossy(&std::fs::read(&log_path)?));
+                }
+            }
+        }
+
+        std::fs::write(&report_path, report)?;
+        Ok(report_path)
+    }
+
     /// Validate that the game configuration is ready for launching
     fn validate_launch_config(&self, config: &GameConfig) -> Result<()> {
         // Check if executable exists
@@ -59,6 +383,20 @@ impl GameLauncher {
             ));
         }
 
+        if let Some(working_dir) = &config.launch.working_dir {
+            if !working_dir.is_dir() {
+                return Err(anyhow!(
+                    "Working directory not found: {}. Update launch.working_dir",
+                    working_dir.display()
+                ));
+            }
+        }
+
+        // Native games have no prefix or Wine settings to check
+        if config.game.native {
+            return Ok(());
+        }
+
         // Check if wine prefix exists
         if !config.game.wine_prefix.exists() {
             return Err(anyhow!(
@@ -67,6 +405,11 @@ impl GameLauncher {
             ));
         }
 
+        // dll_overrides is hand-edited and passed straight into the environment
+        if let Some(dll_overrides) = &config.wine_config.dll_overrides {
+            validate_dll_overrides(dll_overrides)?;
+        }
+
         // Validate wine prefix structure
         let system32_path = config.game.wine_prefix.join("drive_c/windows/system32");
         if !system32_path.exists() {
@@ -76,37 +419,45 @@ impl GameLauncher {
             ));
         }
 
-        // Check if this is a Proton prefix if we're using Proton
+        // Games are always launched with WINEARCH=win64; new-style WoW64 prefixes
+        // have no syswow64 but run 32-bit games just the same
+        if detect_prefix_arch(&config.game.wine_prefix) == Some(PrefixArch::Win32) {
+            return Err(anyhow!(
+                "Wine prefix is 32-bit, but Proton needs a 64-bit prefix: {}",
+                config.game.wine_prefix.display()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Check if this is a Proton prefix when launching with Proton
+    fn warn_if_not_proton_prefix(&self, config: &GameConfig) {
         let version_file = config.game.wine_prefix.join("version");
         if !version_file.exists() {
             println!("⚠ Warning: No Proton version file found in prefix. This may not be a Proton-compatible prefix.");
             println!("  Consider creating a new Proton prefix with: cellar prefix create <name> --proton {}", config.game.proton_version);
         }
-
-        Ok(())
     }
 
-    /// Find the Proton installation path
-    async fn find_proton_installation(&self, proton_version: &str) -> Result<PathBuf> {
-        let runners_path = self.dirs.get_runners_path();
-        let proton_manager = ProtonManager::new(runners_path);
-
-        let runners = proton_manager.discover_local_runners().await?;
-        let proton_runner = runners
-            .iter()
-            .find(|r| r.version == proton_version || r.name.contains(proton_version))
+    /// Find the Proton installation, or the Wine runner, a game is configured to use
+    async fn find_runner_installation(&self, proton_version: &str) -> Result<Runner> {
+        find_game_runner(&self.dirs.get_runners_path(), proton_version)
+            .await?
             .ok_or_else(|| {
                 anyhow!(
                     "Proton version '{}' not found. Install it first with 'cellar runners install proton {}'",
                     proton_version, proton_version
                 )
-            })?;
-
-        Ok(proton_runner.path.clone())
+            })
     }
 
-    /// Execute the launch command with proper environment and error handling
-    async fn execute_launch_command(&self, launch_command: &LaunchCommand) -> Result<()> {
+    /// Execute the launch command and wait for it to exit
+    async fn execute_launch_command(
+        &self,
+        launch_command: &LaunchCommand,
+        lock: &LaunchLock,
+    ) -> Result<Output> {
         let args = &launch_command.command;
 
         // Check if the first argument looks like an environment variable assignment
@@ -114,15 +465,19 @@ impl GameLauncher {
 
         if needs_shell {
             // Use shell execution for complex command lines with environment variables
-            self.execute_shell_command(launch_command).await
+            self.execute_shell_command(launch_command, lock).await
         } else {
             // Direct execution for simple commands
-            self.execute_direct_command(launch_command).await
+            self.execute_direct_command(launch_command, lock).await
         }
     }
 
     /// Execute command directly without shell
-    async fn execute_direct_command(&self, launch_command: &LaunchCommand) -> Result<()> {
+    async fn execute_direct_command(
+        &self,
+        launch_command: &LaunchCommand,
+        lock: &LaunchLock,
+    ) -> Result<Output> {
         let command = &launch_command.command;
         let program = &command[0];
         let cmd_args = &command[1..];
@@ -144,14 +499,19 @@ impl GameLauncher {
             .envs(&launch_command.environment)
             .current_dir(&launch_command.working_directory)
             .stdout(Stdio::inherit())
-            .stderr(Stdio::piped());
+            .stderr(Stdio::piped())
+            // Own process group, so `cellar kill` can stop the game and everything it started
+            .process_group(0);
 
-        let child = command.spawn()?;
-        self.handle_command_output(child).await
+        self.run_game_process(command, lock).await
     }
 
     /// Execute command through shell for complex command lines
-    async fn execute_shell_command(&self, launch_command: &LaunchCommand) -> Result<()> {
+    async fn execute_shell_command(
+        &self,
+        launch_command: &LaunchCommand,
+        lock: &LaunchLock,
+    ) -> Result<Output> {
         let args = &launch_command.command;
         let command_line = self.shell_quote_command(args);
 
@@ -163,22 +523,71 @@ impl GameLauncher {
 
         println!("\nStarting game...");
 
+        // exec replaces the shell, so the game's exit status (including signals) is
+        // reported the same way as with direct execution
         let mut command = Command::new("sh");
         command
             .arg("-c")
-            .arg(&command_line)
+            .arg(format!("exec env {command_line}"))
             .envs(&launch_command.environment)
             .current_dir(&launch_command.working_directory)
             .stdout(Stdio::inherit())
-            .stderr(Stdio::piped());
+            .stderr(Stdio::piped())
+            // Own process group, so `cellar kill` can stop the game and everything it started
+            .process_group(0);
+
+        self.run_game_process(command, lock).await
+    }
+
+    /// Spawn the game and wait for it, passing Ctrl+C and SIGTERM on to its process
+    /// group, which no longer receives the terminal's signals
+    async fn run_game_process(&self, mut command: Command, lock: &LaunchLock) -> Result<Output> {
+        let mut sigint = signal(SignalKind::interrupt())?;
+        let mut sigterm = signal(SignalKind::terminate())?;
 
         let child = command.spawn()?;
-        self.handle_command_output(child).await
+        self.record_child_pid(lock, &child);
+        let pgid = child.id();
+        let output = child.wait_with_output();
+        tokio::pin!(output);
+
+        loop {
+            let forwarded = tokio::select! {
+                output = &mut output => return Ok(output?),
+                _ = sigint.recv() => libc::SIGINT,
+                _ = sigterm.recv() => libc::SIGTERM,
+            };
+            if let Some(pgid) = pgid {
+                tracing::debug!(pgid, signal = forwarded, "Forwarding signal to the game");
+                if let Err(e) = signal_group(pgid, forwarded) {
+                    eprintln!("Warning: {e}");
+                }
+            }
+        }
+    }
+
+    fn record_child_pid(&self, lock: &LaunchLock, child: &tokio::process::Child) {
+        if let Some(pid) = child.id() {
+            if let Err(e) = lock.set_pid(pid) {
+                eprintln!("Warning: Failed to update launch lock: {e}");
+            }
+        }
+    }
+
+    /// Print the resolved launch command without running it
+    fn print_dry_run(&self, launch_command: &LaunchCommand) {
+        println!("\nDry run, the game will not be started.");
+        println!("  Command: {:?}", launch_command.command);
+        println!(
+            "  Working directory: {}",
+            launch_command.working_directory.display()
+        );
+        self.print_environment_variables(&launch_command.environment);
     }
 
     /// Print interesting environment variables
     fn print_environment_variables(&self, environment: &std::collections::HashMap<String, String>) {
-        let interesting_env_vars: Vec<_> = environment
+        let mut interesting_env_vars: Vec<_> = environment
             .iter()
             .filter(|(key, _)| {
                 key.starts_with("WINE")
@@ -188,6 +597,7 @@ impl GameLauncher {
                     || key.starts_with("HOST_LC_ALL")
             })
             .collect();
+        interesting_env_vars.sort();
 
         if !interesting_env_vars.is_empty() {
             println!("  Environment variables:");
@@ -217,43 +627,86 @@ impl GameLauncher {
     }
 
     /// Handle command output and error filtering
-    async fn handle_command_output(&self, child: tokio::process::Child) -> Result<()> {
-        let output = child.wait_with_output().await?;
+    ///
+    /// The captured stderr is also written to the game's log file, replacing the previous launch.
+    fn handle_command_output(&self, game_name: &str, output: &Output) -> Result<()> {
+        if let Err(e) = self.write_game_log(game_name, &output.stderr) {
+            eprintln!("Warning: Failed to write game log: {e}");
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        print_verbose_stderr(&stderr);
 
         if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-
-            // Filter out Wine debug noise but show critical errors
-            let critical_errors: Vec<&str> = stderr
-                .lines()
-                .filter(|line| {
-                    let line_lower = line.to_lowercase();
-                    (line_lower.contains("error") || line_lower.contains("failed"))
-                        && !line.contains("fixme:")
-                        && !line.contains("err:setupapi:create_dest_file")
-                        && !line.contains("wine-staging")
-                        && !line.contains("experimental patches")
-                        && !line.contains("winediag:")
-                        && !line_lower.contains("stub")
-                        && !line.trim().is_empty()
-                })
-                .collect();
+            let critical_errors = filter_wine_errors(&stderr, is_verbose());
 
             if !critical_errors.is_empty() {
                 return Err(anyhow!(
-                    "Game launch failed with errors:\n{}",
+                    "Game exited with {} and errors:\n{}",
+                    output.status,
                     critical_errors.join("\n")
                 ));
-            } else {
-                println!("Game exited with non-zero status but no critical errors detected.");
             }
+            return Err(anyhow!(
+                "Game exited with {}; no critical errors detected, see 'cellar logs \"{}\"'",
+                output.status,
+                game_name
+            ));
         }
 
         Ok(())
     }
 
+    /// Bump the play count and last-played time in the game's config file, and add
+    /// the session to the total playtime
+    ///
+    /// The config is re-read rather than taken from the launch, so edits made while the
+    /// game was running aren't overwritten. Only `[stats]` is touched; everything else,
+    /// including keys Cellar doesn't know, is written back as it was.
+    fn record_play(&self, game_name: &str, session: Duration) -> Result<()> {
+        let config_path = self.dirs.get_game_config_path(game_name);
+        let content = std::fs::read_to_string(&config_path)?;
+        let mut config = content.parse::<toml::Table>()?;
+
+        let stats = config
+            .entry("stats")
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .ok_or_else(|| anyhow!("[stats] in {} is not a table", config_path.display()))?;
+        let counter = |key: &str| {
+            stats
+                .get(key)
+                .and_then(toml::Value::as_integer)
+                .unwrap_or(0)
+        };
+
+        let play_count = counter("play_count") + 1;
+        let mut total_playtime_secs = counter("total_playtime_secs");
+        if session >= MIN_PLAY_SESSION {
+            total_playtime_secs += i64::try_from(session.as_secs()).unwrap_or(i64::MAX);
+        }
+
+        stats.insert(
+            "last_played".to_string(),
+            toml::Value::try_from(chrono::Utc::now())?,
+        );
+        stats.insert("play_count".to_string(), play_count.into());
+        stats.insert(
+            "total_playtime_secs".to_string(),
+            total_playtime_secs.into(),
+        );
+
+        write_file_atomic(&config_path, toml::to_string_pretty(&config)?)
+    }
+
+    fn write_game_log(&self, game_name: &str, stderr: &[u8]) -> Result<()> {
+        self.dirs.ensure_dir_exists(&self.dirs.get_logs_path())?;
+        std::fs::write(self.dirs.get_game_log_path(game_name), stderr)?;
+        Ok(())
+    }
+
     /// Launch a game by name (convenience method)
-    pub async fn launch_game_by_name(&self, game_name: &str) -> Result<()> {
+    pub async fn launch_game_by_name(&self, game_name: &str, options: LaunchOptions) -> Result<()> {
         let config_path = self.dirs.get_game_config_path(game_name);
 
         if !config_path.exists() {
@@ -263,15 +716,15 @@ impl GameLauncher {
         let content = std::fs::read_to_string(&config_path)
             .map_err(|e| anyhow!("Failed to read game config: {}", e))?;
 
-        let config: GameConfig =
-            toml::from_str(&content).map_err(|e| anyhow!("Failed to parse game config: {}", e))?;
+        let config = parse_game_config(&content)
+            .map_err(|e| anyhow!("Failed to parse game config: {}", e))?;
 
-        self.launch_game(&config).await
+        self.launch_game(&config, options).await
     }
 }
 
 impl Default for GameLauncher {
     fn default() -> Self {
-        Self::new().expect("Failed to create GameLauncher")
+        Self::new(CellarDirectories::new(None).expect("Failed to create GameLauncher"))
     }
 }
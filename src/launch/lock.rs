@@ -0,0 +1,132 @@
+use anyhow::{anyhow, Result};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use super::process::process_start_time;
+
+/// Lock file that stops the same game from being launched twice at once
+///
+/// The file holds the PID of the running game, which leads its own process group,
+/// and its start time, and is removed when the lock is dropped.
+#[derive(Debug)]
+pub struct LaunchLock {
+    path: PathBuf,
+}
+
+impl LaunchLock {
+    /// Take the lock, replacing it if the process that held it is gone
+    pub fn acquire(path: &Path, game_name: &str) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(path) {
+                Ok(mut file) => {
+                    write!(file, "{}", lock_contents(std::process::id()))?;
+                    return Ok(Self {
+                        path: path.to_path_buf(),
+                    });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    if let Some(pid) = running_pid(path) {
+                        return Err(anyhow!(
+                            "'{}' is already running (PID {}). If this is wrong, delete {}",
+                            game_name,
+                            pid,
+                            path.display()
+                        ));
+                    }
+
+                    // Stale lock from a crashed or killed launch
+                    fs::remove_file(path)?;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Record the PID of the spawned game process
+    pub fn set_pid(&self, pid: u32) -> Result<()> {
+        fs::write(&self.path, lock_contents(pid))?;
+        Ok(())
+    }
+}
+
+fn lock_contents(pid: u32) -> String {
+    match process_start_time(pid) {
+        Some(start_time) => format!("{pid} {start_time}"),
+        None => pid.to_string(),
+    }
+}
+
+/// PID recorded in a lock file, if the lock is held by a live process
+///
+/// A lock left behind by a killed launch or before a reboot may name a PID that now
+/// belongs to an unrelated process, so the recorded start time has to match too.
+/// Locks without one can't be verified and count as stale.
+pub fn running_pid(path: &Path) -> Option<u32> {
+    let contents = fs::read_to_string(path).ok()?;
+    let mut fields = contents.split_whitespace();
+    let pid = fields.next()?.parse::<u32>().ok()?;
+    let start_time = fields.next()?.parse::<u64>().ok()?;
+    (process_start_time(pid) == Some(start_time)).then_some(pid)
+}
+
+impl Drop for LaunchLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock_refuses_running_game() {
+        let temp_dir = TempDir::new().unwrap();
+        let lock_path = temp_dir.path().join("locks").join("game.lock");
+
+        let lock = LaunchLock::acquire(&lock_path, "Game").unwrap();
+        assert!(LaunchLock::acquire(&lock_path, "Game").is_err());
+
+        drop(lock);
+        assert!(!lock_path.exists());
+        assert!(LaunchLock::acquire(&lock_path, "Game").is_ok());
+    }
+
+    #[test]
+    fn test_stale_lock_is_replaced() {
+        let temp_dir = TempDir::new().unwrap();
+        let lock_path = temp_dir.path().join("game.lock");
+
+        // PIDs are capped well below u32::MAX, so this process can't exist
+        fs::write(&lock_path, u32::MAX.to_string()).unwrap();
+        let lock = LaunchLock::acquire(&lock_path, "Game").unwrap();
+        assert_eq!(running_pid(&lock_path), Some(std::process::id()));
+
+        lock.set_pid(u32::MAX).unwrap();
+        assert_eq!(
+            fs::read_to_string(&lock_path).unwrap(),
+            u32::MAX.to_string()
+        );
+    }
+
+    #[test]
+    fn test_lock_with_reused_pid_is_stale() {
+        let temp_dir = TempDir::new().unwrap();
+        let lock_path = temp_dir.path().join("game.lock");
+        let pid = std::process::id();
+        let start_time = process_start_time(pid).unwrap();
+
+        // A live PID whose start time differs belongs to another process now
+        fs::write(&lock_path, format!("{pid} {}", start_time + 1)).unwrap();
+        assert_eq!(running_pid(&lock_path), None);
+        fs::write(&lock_path, pid.to_string()).unwrap();
+        assert_eq!(running_pid(&lock_path), None);
+        assert!(LaunchLock::acquire(&lock_path, "Game").is_ok());
+    }
+}
@@ -1,5 +1,7 @@
 pub mod command;
 pub mod executor;
+pub mod lock;
+pub mod process;
 
 //pub use command::CommandBuilder;
-pub use executor::GameLauncher;
+pub use executor::{GameLauncher, LaunchOptions};
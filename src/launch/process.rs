@@ -0,0 +1,210 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+
+pub fn process_is_alive(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+/// PIDs of every live (non-zombie) process in the process group `pgid`
+pub fn process_group_members(pgid: u32) -> Vec<u32> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|pid| {
+            fs::read_to_string(Path::new("/proc").join(pid.to_string()).join("stat"))
+                .ok()
+                .and_then(|stat| parse_stat(&stat))
+                .is_some_and(|(state, pgrp)| state != 'Z' && pgrp == pgid)
+        })
+        .collect()
+}
+
+/// State and process group from /proc/<pid>/stat; the command name may contain spaces and ')'
+fn parse_stat(stat: &str) -> Option<(char, u32)> {
+    // Fields after the command: state, ppid, pgrp
+    let mut fields = stat[stat.rfind(')')? + 1..].split_whitespace();
+    let state = fields.next()?.chars().next()?;
+    let pgrp = fields.nth(1)?.parse().ok()?;
+    Some((state, pgrp))
+}
+
+/// Start time of `pid` in clock ticks since boot, which tells it apart from a
+/// later process that reuses the same PID
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(Path::new("/proc").join(pid.to_string()).join("stat")).ok()?;
+    parse_start_time(&stat)
+}
+
+/// Field 22 of /proc/<pid>/stat, the 20th after the command
+fn parse_start_time(stat: &str) -> Option<u64> {
+    stat[stat.rfind(')')? + 1..]
+        .split_whitespace()
+        .nth(19)?
+        .parse()
+        .ok()
+}
+
+/// `kill(2)`: a negative `target` signals that process group; one that has
+/// already exited is not an error
+fn send_signal(target: i32, signal: libc::c_int) -> Result<()> {
+    if unsafe { libc::kill(target, signal) } != 0 {
+        let err = std::io::Error::last_os_error();
+        if err.raw_os_error() != Some(libc::ESRCH) {
+            return Err(anyhow!("Failed to signal {}: {}", target, err));
+        }
+    }
+    Ok(())
+}
+
+/// Pass `signal` on to the process group led by `pgid`
+pub fn signal_group(pgid: u32, signal: libc::c_int) -> Result<()> {
+    send_signal(-(pgid as i32), signal)
+}
+
+/// Send SIGTERM to `pid`'s process group, then SIGKILL if it's still around after `timeout`
+///
+/// Falls back to signalling just `pid` when it doesn't lead a process group.
+/// Returns whether SIGKILL was needed.
+pub async fn terminate(pid: u32, timeout: Duration) -> Result<bool> {
+    let is_group = !process_group_members(pid).is_empty();
+    let target = if is_group { -(pid as i32) } else { pid as i32 };
+    let still_running = || {
+        if is_group {
+            !process_group_members(pid).is_empty()
+        } else {
+            process_is_alive(pid)
+        }
+    };
+
+    send_signal(target, libc::SIGTERM)?;
+
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if !still_running() {
+            return Ok(false);
+        }
+        sleep(Duration::from_millis(200)).await;
+    }
+
+    if still_running() {
+        send_signal(target, libc::SIGKILL)?;
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+/// PIDs of `umu-run` processes launched with `WINEPREFIX` set to `prefix`
+///
+/// Processes owned by other users can't be inspected and are skipped.
+pub fn find_umu_processes(prefix: &Path) -> Vec<u32> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+
+    let mut pids: Vec<u32> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|pid| {
+            let proc_dir = Path::new("/proc").join(pid.to_string());
+            let Ok(cmdline) = fs::read(proc_dir.join("cmdline")) else {
+                return false;
+            };
+            let Ok(environ) = fs::read(proc_dir.join("environ")) else {
+                return false;
+            };
+            is_umu_in_prefix(&cmdline, &environ, prefix)
+        })
+        .collect();
+    pids.sort_unstable();
+    pids
+}
+
+/// Whether a NUL-separated cmdline runs umu-run and its environment targets `prefix`
+fn is_umu_in_prefix(cmdline: &[u8], environ: &[u8], prefix: &Path) -> bool {
+    let runs_umu = cmdline.split(|b| *b == 0).any(|arg| {
+        Path::new(&*String::from_utf8_lossy(arg))
+            .file_name()
+            .is_some_and(|name| name == "umu-run")
+    });
+    if !runs_umu {
+        return false;
+    }
+
+    let wanted = format!("WINEPREFIX={}", prefix.display());
+    environ
+        .split(|b| *b == 0)
+        .any(|var| var == wanted.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_umu_in_prefix() {
+        let prefix = Path::new("/home/user/.local/share/cellar/prefixes/game");
+        let cmdline = b"/usr/bin/python3\0/usr/bin/umu-run\0/games/game.exe\0";
+        let environ = b"HOME=/home/user\0WINEPREFIX=/home/user/.local/share/cellar/prefixes/game\0";
+
+        assert!(is_umu_in_prefix(cmdline, environ, prefix));
+        assert!(!is_umu_in_prefix(
+            cmdline,
+            b"WINEPREFIX=/home/user/.local/share/cellar/prefixes/other\0",
+            prefix
+        ));
+        assert!(!is_umu_in_prefix(b"wine\0game.exe\0", environ, prefix));
+    }
+
+    #[test]
+    fn test_parse_stat() {
+        assert_eq!(
+            parse_stat("4242 (Game (x64).exe) S 4000 4100 4100 0 -1 4194560"),
+            Some(('S', 4100))
+        );
+        assert_eq!(parse_stat("garbage"), None);
+    }
+
+    #[test]
+    fn test_parse_start_time() {
+        assert_eq!(
+            parse_start_time(
+                "4242 (Game (x64).exe) S 4000 4100 4100 0 -1 4194560 \
+                 1 2 3 4 5 6 7 8 20 0 1 0 987654 1000 100"
+            ),
+            Some(987654)
+        );
+        assert_eq!(parse_start_time("4242 (game) S 4000"), None);
+        assert!(process_start_time(std::process::id()).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_terminate_process_group() {
+        use std::os::unix::process::CommandExt;
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+
+        assert_eq!(process_group_members(pid), vec![pid]);
+        let escalated = terminate(pid, Duration::from_secs(5)).await.unwrap();
+        assert!(!escalated);
+        assert!(!child.wait().unwrap().success());
+    }
+
+    #[test]
+    fn test_own_process_is_alive() {
+        assert!(process_is_alive(std::process::id()));
+        assert!(!process_is_alive(u32::MAX));
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use clap::Parser;
+use std::path::PathBuf;
 
 mod cli;
 mod config;
@@ -8,13 +9,34 @@ mod launch;
 mod runners;
 mod utils;
 
-use cli::commands::Commands;
+use cli::commands::{Commands, LogFormat, LogLevel};
+use utils::fs::CellarDirectories;
 
 #[derive(Parser)]
 #[command(name = "cellar")]
 #[command(about = "A wine prefix and game manager for Linux")]
 #[command(version)]
 struct Cli {
+    /// Use a separate Cellar data directory instead of ~/.local/share/cellar
+    #[arg(long, global = true, value_name = "PATH")]
+    config_dir: Option<PathBuf>,
+
+    /// Print Wine's full stderr, including fixme and stub messages
+    #[arg(long, global = true)]
+    verbose: bool,
+
+    /// Show diagnostics down to this level on stderr (default: warn, or RUST_LOG)
+    #[arg(long, global = true, value_enum, value_name = "LEVEL")]
+    log_level: Option<LogLevel>,
+
+    /// Format of the diagnostics on stderr
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    /// Answer yes to every confirmation prompt instead of reading stdin
+    #[arg(long = "yes", short = 'y', visible_alias = "no-confirm", global = true)]
+    assume_yes: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -22,38 +44,120 @@ struct Cli {
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    utils::logging::init(
+        cli.log_level.map(Into::into),
+        cli.log_format == LogFormat::Json,
+    );
+    let dirs = CellarDirectories::new(cli.config_dir.as_deref())?;
+    utils::output::set_verbose(cli.verbose);
+    utils::output::set_assume_yes(cli.assume_yes);
+
+    if let Err(e) = config::migrate::migrate_legacy_filenames(&dirs).await {
+        eprintln!("Warning: Failed to rename old-style config files: {e}");
+    }
 
     match cli.command {
         Commands::Add {
             name,
             exe,
             installer,
+            from_url,
             interactive,
             proton,
             prefix,
+            force,
+            no_shortcut,
+            native,
+            no_prefix,
+            layout,
         } => {
-            cli::commands::add_game(name, exe, installer, interactive, proton, prefix).await?;
+            cli::commands::add_game(
+                &dirs,
+                name,
+                exe,
+                installer,
+                from_url,
+                interactive,
+                proton,
+                prefix,
+                force,
+                no_shortcut,
+                native,
+                no_prefix,
+                layout,
+            )
+            .await?;
         }
-        Commands::Launch { name } => {
-            cli::commands::launch_game(name).await?;
+        Commands::Launch {
+            name,
+            dry_run,
+            capture_crash,
+            steam_overlay,
+            gamescope,
+            no_gamescope,
+            gamemode,
+            no_gamemode,
+            mangohud,
+            no_mangohud,
+        } => {
+            let options = launch::LaunchOptions {
+                dry_run,
+                capture_crash,
+                steam_overlay,
+                gamescope: cli::commands::flag_override(gamescope, no_gamescope),
+                gamemode: cli::commands::flag_override(gamemode, no_gamemode),
+                mangohud: cli::commands::flag_override(mangohud, no_mangohud),
+            };
+            cli::commands::launch_game(&dirs, name, options).await?;
         }
-        Commands::List => {
-            cli::commands::list_games(None)?;
+        Commands::List {
+            format,
+            proton,
+            sort,
+        } => {
+            cli::commands::list_games(&dirs, None, proton.as_deref(), sort, format)?;
         }
         Commands::Remove { name } => {
-            cli::commands::remove_game(name)?;
+            cli::commands::remove_game(&dirs, name)?;
+        }
+        Commands::Info { name, json } => {
+            cli::commands::show_game_info(&dirs, name, json)?;
+        }
+        Commands::Status { name } => {
+            cli::commands::show_status(&dirs, name)?;
+        }
+        Commands::Kill { name } => {
+            cli::commands::kill_game(&dirs, name).await?;
+        }
+        Commands::Logs { name } => {
+            cli::commands::show_game_logs(&dirs, name)?;
+        }
+        Commands::Doctor { prefix, game } => {
+            cli::commands::run_doctor(&dirs, prefix, game).await?;
+        }
+        Commands::Backup { output } => {
+            cli::commands::backup_cellar(&dirs, &output)?;
+        }
+        Commands::Restore { input, force } => {
+            cli::commands::restore_cellar(&dirs, &input, force)?;
+        }
+        Commands::Defaults { command } => {
+            cli::commands::handle_defaults_command(&dirs, command)?;
+        }
+        Commands::Config { command } => {
+            cli::commands::handle_config_command(&dirs, command)?;
         }
-        Commands::Info { name } => {
-            cli::commands::show_game_info(name)?;
+        Commands::Games { command } => {
+            cli::commands::handle_games_command(&dirs, command).await?;
         }
         Commands::Runners { command } => {
-            cli::commands::handle_runners_command(command).await?;
+            cli::commands::handle_runners_command(&dirs, command).await?;
         }
         Commands::Prefix { command } => {
-            cli::commands::handle_prefix_command(command).await?;
+            cli::commands::handle_prefix_command(&dirs, command).await?;
         }
         Commands::Shortcut { command } => {
-            cli::commands::handle_shortcut_command(command).await?;
+            cli::commands::handle_shortcut_command(&dirs, command).await?;
         }
     }
 
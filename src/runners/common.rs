@@ -1,9 +1,34 @@
-
 use anyhow::{anyhow, Result};
 use reqwest;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+use crate::utils::fs::ensure_free_space;
+
+/// Attempts per request unless overridden by `CELLAR_HTTP_RETRIES`
+const DEFAULT_HTTP_ATTEMPTS: u32 = 3;
+
+/// Seconds a download may go without receiving data unless overridden by `CELLAR_DOWNLOAD_TIMEOUT`
+const DEFAULT_DOWNLOAD_TIMEOUT_SECS: u64 = 1800;
+
+/// Connecting should be quick even on slow links, so give up on unreachable hosts early
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Largest page size the GitHub releases API allows
+const RELEASES_PER_PAGE: usize = 100;
+
+/// Stop paging through releases after this many, which covers every Proton-GE release
+const MAX_RELEASES: usize = 300;
+
+/// Name prefixes of the release assets Cellar downloads (Proton-GE, DXVK and
+/// Wine-GE), used to recognize leftovers that older versions left in the shared
+/// temp dir
+const DOWNLOAD_ASSET_PREFIXES: &[&str] = &["GE-Proton", "dxvk-", "wine-lutris-"];
 
 /// Type alias for asset filter function
 pub type AssetFilter = fn(&str) -> bool;
@@ -32,32 +57,186 @@ pub struct GitHubAsset {
     pub size: u64,
 }
 
+/// Download size limit in MiB from `CELLAR_MAX_DOWNLOAD_MB`, replacing each runner's default
+fn max_download_size_override() -> Option<u64> {
+    std::env::var("CELLAR_MAX_DOWNLOAD_MB")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .map(|mib| mib.saturating_mul(1024 * 1024))
+}
+
+fn http_attempts() -> u32 {
+    std::env::var("CELLAR_HTTP_RETRIES")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .map(|n| n.max(1))
+        .unwrap_or(DEFAULT_HTTP_ATTEMPTS)
+}
+
+/// How long a download may stall before it is abandoned; anything Cellar's
+/// downloads changed more recently than this may still be in use
+pub fn download_timeout() -> Duration {
+    std::env::var("CELLAR_DOWNLOAD_TIMEOUT")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(DEFAULT_DOWNLOAD_TIMEOUT_SECS))
+}
+
+/// HTTP client with a short connect timeout and a long read timeout, so an
+/// unreachable host fails fast and a stalled download is abandoned, while a
+/// slow download that keeps receiving data is allowed to finish however long it takes
+pub fn http_client(user_agent: &str) -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .user_agent(user_agent)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(download_timeout())
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .build()?)
+}
+
+/// Server errors and rate limiting are worth retrying; anything else (e.g. 404) is final
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Exponential backoff starting at one second
+fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_secs(1 << (attempt - 1).min(5))
+}
+
+/// Send a request, retrying connection failures and 5xx/429 responses with backoff
+pub async fn send_with_retry(request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+    let attempts = http_attempts();
+    let mut attempt = 1;
+
+    loop {
+        let this_attempt = request
+            .try_clone()
+            .ok_or_else(|| anyhow!("Request cannot be retried"))?;
+
+        let reason = match this_attempt.send().await {
+            Ok(response) if !is_retryable_status(response.status()) => return Ok(response),
+            Ok(response) if attempt >= attempts => return Ok(response),
+            Ok(response) => format!("HTTP {}", response.status()),
+            Err(e) if attempt < attempts && (e.is_connect() || e.is_timeout()) => e.to_string(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let delay = retry_delay(attempt);
+        tracing::warn!(
+            "Request failed ({reason}), retrying in {}s (attempt {}/{})",
+            delay.as_secs(),
+            attempt + 1,
+            attempts
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Stream a response body to `dest` without buffering it in memory
+///
+/// With a non-zero `resume_from` the body is appended to the `resume_from`
+/// bytes already in `dest`. Fails if the file grows past `max_size` or the body
+/// is shorter than the advertised content length, leaving what was received in
+/// place. Returns the total size of the file.
+pub async fn download_to_file(
+    mut response: reqwest::Response,
+    dest: &Path,
+    resume_from: u64,
+    max_size: u64,
+) -> Result<u64> {
+    let expected_size = response.content_length();
+    if let Some(content_length) = expected_size {
+        if resume_from + content_length > max_size {
+            return Err(anyhow!(
+                "Download too large: {} bytes (max {} bytes)",
+                content_length,
+                max_size
+            ));
+        }
+    }
+
+    let mut file = if resume_from > 0 {
+        fs::OpenOptions::new().append(true).open(dest).await?
+    } else {
+        fs::File::create(dest).await?
+    };
+    let mut written = resume_from;
+
+    while let Some(chunk) = response.chunk().await? {
+        written += chunk.len() as u64;
+        if written > max_size {
+            drop(file);
+            let _ = fs::remove_file(dest).await;
+            return Err(anyhow!(
+                "Download exceeded maximum size of {} bytes",
+                max_size
+            ));
+        }
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+
+    if let Some(content_length) = expected_size {
+        if written - resume_from != content_length {
+            return Err(anyhow!(
+                "Downloaded size mismatch: expected {}, got {}",
+                content_length,
+                written - resume_from
+            ));
+        }
+    }
+
+    Ok(written)
+}
+
 /// Base runner implementation for GitHub-based runners
 pub struct BaseGitHubRunner {
     pub config: GitHubRunnerConfig,
     pub cellar_runners_path: PathBuf,
+    /// Where archives are downloaded before extraction; the system temp dir
+    /// unless set with `set_download_dir`
+    pub download_dir: PathBuf,
 }
 
 impl BaseGitHubRunner {
-    pub fn new(config: GitHubRunnerConfig, cellar_runners_path: PathBuf) -> Self {
+    pub fn new(mut config: GitHubRunnerConfig, cellar_runners_path: PathBuf) -> Self {
+        if let Some(max_download_size) = max_download_size_override() {
+            config.max_download_size = max_download_size;
+        }
+
         Self {
             config,
             cellar_runners_path,
+            download_dir: std::env::temp_dir(),
         }
     }
 
+    /// Download archives into `dir`, which is created when needed
+    pub fn set_download_dir(&mut self, dir: PathBuf) {
+        self.download_dir = dir;
+    }
+
+    /// Lift the download size limit for this runner
+    pub fn allow_large_downloads(&mut self) {
+        self.config.max_download_size = u64::MAX;
+    }
+
     /// Download a runner from GitHub releases
+    #[tracing::instrument(skip(self), fields(repo = %self.config.repo_name))]
     pub async fn download_from_github(&self, version: &str, tag_prefix: &str) -> Result<PathBuf> {
-        let client = reqwest::Client::builder()
-            .user_agent(&self.config.user_agent)
-            .build()?;
+        let client = http_client(&self.config.user_agent)?;
 
         // Get release info from GitHub API
         let url = format!(
             "https://api.github.com/repos/{}/{}/releases/tags/{}{}",
             self.config.repo_owner, self.config.repo_name, tag_prefix, version
         );
-        let response = client.get(&url).send().await?;
+        tracing::debug!(%url, "Fetching release info");
+        let response = send_with_retry(client.get(&url)).await?;
 
         if !response.status().is_success() {
             return Err(anyhow!(
@@ -76,87 +255,167 @@ impl BaseGitHubRunner {
             .iter()
             .find(|a| (self.config.asset_filter)(&a.name))
             .ok_or_else(|| anyhow!("No suitable asset found for version {}", version))?;
+        tracing::debug!(
+            asset = %asset.name,
+            size = asset.size,
+            url = %asset.browser_download_url,
+            "Selected release asset"
+        );
 
         // Check asset size limit
         if asset.size > self.config.max_download_size {
             return Err(anyhow!(
-                "Asset too large: {} bytes (max {} bytes)",
+                "Asset too large: {} bytes (max {} bytes). Use --allow-large or set CELLAR_MAX_DOWNLOAD_MB to raise the limit",
                 asset.size,
                 self.config.max_download_size
             ));
         }
 
-        // Download the asset
-        let download_response = client.get(&asset.browser_download_url).send().await?;
+        // The archive, its staged extraction and the installed copy usually share
+        // one filesystem
+        fs::create_dir_all(&self.download_dir).await?;
+        let required_space = asset.size.saturating_mul(5) / 2;
+        ensure_free_space(&self.cellar_runners_path, required_space)?;
+        ensure_free_space(&self.download_dir, required_space)?;
 
-        if !download_response.status().is_success() {
-            return Err(anyhow!("Failed to download {}", asset.name));
+        // Download into a .part file that survives failures so a retry can resume it
+        let temp_file = self.download_dir.join(&asset.name);
+        let part_file = self.download_dir.join(format!("{}.part", asset.name));
+
+        let mut existing = match fs::metadata(&part_file).await {
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        };
+        if existing > asset.size {
+            tracing::debug!(
+                existing,
+                "Partial download is larger than the asset, starting over"
+            );
+            fs::remove_file(&part_file).await?;
+            existing = 0;
         }
 
-        // Verify content length matches expected size
-        if let Some(content_length) = download_response.content_length() {
-            if content_length != asset.size {
-                return Err(anyhow!(
-                    "Content length mismatch: expected {}, got {}",
-                    asset.size,
-                    content_length
-                ));
+        let mut written = existing;
+        if existing < asset.size {
+            let mut request = client.get(&asset.browser_download_url);
+            if existing > 0 {
+                println!(
+                    "Resuming download of {} at {} of {} bytes",
+                    asset.name, existing, asset.size
+                );
+                request = request.header(reqwest::header::RANGE, format!("bytes={existing}-"));
             }
-        }
 
-        // Save to temporary file with size verification
-        let temp_dir = std::env::temp_dir();
-        let temp_file = temp_dir.join(&asset.name);
+            let download_response = send_with_retry(request).await?;
 
-        let bytes = download_response.bytes().await?;
+            if !download_response.status().is_success() {
+                return Err(anyhow!("Failed to download {}", asset.name));
+            }
+
+            // A 200 means the server ignored the range, so start over
+            let resume_from = if download_response.status() == reqwest::StatusCode::PARTIAL_CONTENT
+            {
+                existing
+            } else {
+                0
+            };
+            tracing::debug!(
+                status = %download_response.status(),
+                resume_from,
+                part_file = %part_file.display(),
+                "Downloading"
+            );
+
+            // Verify content length matches expected size
+            if let Some(content_length) = download_response.content_length() {
+                if resume_from + content_length != asset.size {
+                    // A partial download of some other build of the asset can't be resumed
+                    let _ = fs::remove_file(&part_file).await;
+                    return Err(anyhow!(
+                        "Content length mismatch: expected {}, got {}",
+                        asset.size - resume_from,
+                        content_length
+                    ));
+                }
+            }
+
+            written =
+                download_to_file(download_response, &part_file, resume_from, asset.size).await?;
+        }
 
         // Verify downloaded size
-        if bytes.len() as u64 != asset.size {
+        if written != asset.size {
             return Err(anyhow!(
                 "Downloaded size mismatch: expected {}, got {}",
                 asset.size,
-                bytes.len()
+                written
             ));
         }
 
-        fs::write(&temp_file, bytes).await?;
+        fs::rename(&part_file, &temp_file).await?;
+        tracing::info!(path = %temp_file.display(), bytes = written, "Download complete");
 
         Ok(temp_file)
     }
 
     /// Get available versions from GitHub releases
     pub async fn get_github_versions(&self) -> Result<Vec<String>> {
-        let client = reqwest::Client::builder()
-            .user_agent(&self.config.user_agent)
-            .build()?;
+        let client = http_client(&self.config.user_agent)?;
 
-        let url = format!(
-            "https://api.github.com/repos/{}/{}/releases",
-            self.config.repo_owner, self.config.repo_name
-        );
+        let mut url = Some(format!(
+            "https://api.github.com/repos/{}/{}/releases?per_page={}",
+            self.config.repo_owner, self.config.repo_name, RELEASES_PER_PAGE
+        ));
+        let mut versions = Vec::new();
 
-        let response = client.get(&url).send().await?;
-        if !response.status().is_success() {
-            return Err(anyhow!(
-                "Failed to fetch available versions from {}/{}: HTTP {}",
-                self.config.repo_owner,
-                self.config.repo_name,
-                response.status()
-            ));
-        }
+        // Follow the `Link: rel="next"` header until the last page or the cap
+        while let Some(page_url) = url.take() {
+            let response = send_with_retry(client.get(&page_url)).await?;
+            if !response.status().is_success() {
+                return Err(anyhow!(
+                    "Failed to fetch available versions from {}/{}: HTTP {}",
+                    self.config.repo_owner,
+                    self.config.repo_name,
+                    response.status()
+                ));
+            }
 
-        let releases: Vec<GitHubRelease> = response
-            .json()
-            .await
-            .map_err(|e| anyhow!("Failed to parse GitHub API response: {}", e))?;
+            url = response
+                .headers()
+                .get(reqwest::header::LINK)
+                .and_then(|v| v.to_str().ok())
+                .and_then(next_page_url);
 
-        let versions = releases.into_iter().map(|r| r.tag_name).collect();
+            let releases: Vec<GitHubRelease> = response
+                .json()
+                .await
+                .map_err(|e| anyhow!("Failed to parse GitHub API response: {}", e))?;
+
+            versions.extend(releases.into_iter().map(|r| r.tag_name));
+            if versions.len() >= MAX_RELEASES {
+                versions.truncate(MAX_RELEASES);
+                break;
+            }
+        }
 
         Ok(versions)
     }
 
     /// Common runner deletion logic
+    ///
+    /// Only runners inside Cellar's runners directory are deleted; Proton found in
+    /// Steam's directories belongs to Steam.
     pub async fn delete_runner_common(&self, runner_path: &Path) -> Result<()> {
+        if !runner_path.starts_with(&self.cellar_runners_path)
+            || runner_path == self.cellar_runners_path
+        {
+            return Err(anyhow!(
+                "{} was not installed by Cellar, so it won't be removed. \
+                 Runners installed for Steam are managed through Steam",
+                runner_path.display()
+            ));
+        }
+
         if !runner_path.exists() {
             return Err(anyhow!(
                 "Runner path does not exist: {}",
@@ -182,3 +441,96 @@ impl BaseGitHubRunner {
         Ok(())
     }
 }
+
+/// Whether a file in the temp dir is a runner download Cellar left behind: an
+/// interrupted `.part` file, or an archive whose extraction failed
+pub fn is_leftover_download(file_name: &str) -> bool {
+    let name = file_name.strip_suffix(".part").unwrap_or(file_name);
+    DOWNLOAD_ASSET_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+        && (name.ends_with(".tar.gz") || name.ends_with(".tar.xz"))
+}
+
+/// URL of the next page from a GitHub `Link` header, e.g. `<https://...&page=2>; rel="next"`
+fn next_page_url(link_header: &str) -> Option<String> {
+    link_header.split(',').find_map(|link| {
+        let (url, params) = link.split_once(';')?;
+        params
+            .split(';')
+            .any(|param| param.trim() == r#"rel="next""#)
+            .then(|| {
+                url.trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string()
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_delete_runner_common_stays_in_runners_dir() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let runners = temp_dir.path().join("runners");
+        let cellar_proton = runners.join("proton/GE-Proton9-19");
+        let steam_proton = temp_dir.path().join("compatibilitytools.d/GE-Proton9-20");
+        std::fs::create_dir_all(&cellar_proton).unwrap();
+        std::fs::create_dir_all(&steam_proton).unwrap();
+
+        let config = GitHubRunnerConfig {
+            repo_owner: "owner".to_string(),
+            repo_name: "repo".to_string(),
+            user_agent: "cellar-test".to_string(),
+            max_download_size: 1024,
+            asset_filter: |_| true,
+        };
+        let base_runner = BaseGitHubRunner::new(config, runners.clone());
+
+        assert!(base_runner
+            .delete_runner_common(&steam_proton)
+            .await
+            .is_err());
+        assert!(steam_proton.exists());
+        assert!(base_runner.delete_runner_common(&runners).await.is_err());
+
+        base_runner
+            .delete_runner_common(&cellar_proton)
+            .await
+            .unwrap();
+        assert!(!cellar_proton.exists());
+    }
+
+    #[test]
+    fn test_next_page_url() {
+        let header = r#"<https://api.github.com/repositories/1/releases?per_page=100&page=2>; rel="next", <https://api.github.com/repositories/1/releases?per_page=100&page=4>; rel="last""#;
+        assert_eq!(
+            next_page_url(header).as_deref(),
+            Some("https://api.github.com/repositories/1/releases?per_page=100&page=2")
+        );
+
+        let last_page = r#"<https://api.github.com/repositories/1/releases?per_page=100&page=3>; rel="prev", <https://api.github.com/repositories/1/releases?per_page=100&page=1>; rel="first""#;
+        assert_eq!(next_page_url(last_page), None);
+    }
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(is_retryable_status(reqwest::StatusCode::BAD_GATEWAY));
+        assert!(is_retryable_status(
+            reqwest::StatusCode::SERVICE_UNAVAILABLE
+        ));
+        assert!(is_retryable_status(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable_status(reqwest::StatusCode::NOT_FOUND));
+        assert!(!is_retryable_status(reqwest::StatusCode::OK));
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off() {
+        assert_eq!(retry_delay(1), Duration::from_secs(1));
+        assert_eq!(retry_delay(2), Duration::from_secs(2));
+        assert_eq!(retry_delay(3), Duration::from_secs(4));
+    }
+}
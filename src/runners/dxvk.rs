@@ -1,10 +1,55 @@
 use super::common::{AssetFilter, BaseGitHubRunner, GitHubRunnerConfig};
 use super::{Runner, RunnerManager, RunnerType};
+use crate::utils::archive::extract_tar_gz_into;
+use crate::utils::prefix::{detect_prefix_arch, PrefixArch};
 use anyhow::{anyhow, Result};
 use regex::Regex;
 use std::path::{Path, PathBuf};
 use tokio::fs;
 
+/// File in a prefix recording the DXVK version Cellar installed into it
+pub const DXVK_VERSION_MARKER: &str = ".cellar-dxvk-version";
+
+/// Directory in a prefix holding the DLLs DXVK replaced, mirroring their paths
+const DXVK_BACKUP_DIR: &str = ".cellar-dxvk-backup";
+
+/// DLLs DXVK replaces in a prefix
+const DXVK_DLLS: [&str; 4] = ["d3d9.dll", "d3d10core.dll", "d3d11.dll", "dxgi.dll"];
+
+/// Architecture directories of a DXVK release, each holding all of `DXVK_DLLS`
+const DXVK_ARCH_DIRS: [&str; 2] = ["x64", "x32"];
+
+/// DLLs missing from a DXVK install, as paths relative to it (e.g. `x32/d3d11.dll`)
+pub fn missing_dxvk_files(runner_path: &Path) -> Vec<String> {
+    DXVK_ARCH_DIRS
+        .iter()
+        .flat_map(|arch| DXVK_DLLS.iter().map(move |dll| format!("{arch}/{dll}")))
+        .filter(|file| !runner_path.join(file).is_file())
+        .collect()
+}
+
+/// Where Proton keeps Wine's builtin DLLs, for newer and older Proton layouts
+const BUILTIN_DLL64_DIRS: &[&str] = &[
+    "files/lib/wine/x86_64-windows",
+    "files/lib64/wine/x86_64-windows",
+];
+const BUILTIN_DLL32_DIRS: &[&str] = &["files/lib/wine/i386-windows"];
+
+/// DXVK builds embed their name; Wine's builtin DLLs don't
+pub fn is_dxvk_dll(path: &Path) -> bool {
+    std::fs::read(path)
+        .map(|bytes| bytes.windows(4).any(|w| w.eq_ignore_ascii_case(b"dxvk")))
+        .unwrap_or(false)
+}
+
+/// DXVK version recorded in a prefix by `install_dxvk_to_prefix`
+pub fn installed_dxvk_version(prefix_path: &Path) -> Option<String> {
+    std::fs::read_to_string(prefix_path.join(DXVK_VERSION_MARKER))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
 pub struct DxvkManager {
     pub base_runner: BaseGitHubRunner,
 }
@@ -54,6 +99,8 @@ impl DxvkManager {
                             path: path.clone(),
                             runner_type: RunnerType::Dxvk,
                             installed: true,
+                            pinned: false,
+                            other_paths: Vec::new(),
                         });
                     }
                 }
@@ -86,90 +133,162 @@ impl DxvkManager {
         fs::create_dir_all(&dxvk_dir).await?;
 
         let extract_path = dxvk_dir.join(format!("v{version}"));
-        fs::create_dir_all(&extract_path).await?;
-
-        // Extract tar.gz file
-        let file = std::fs::File::open(archive_path)?;
-        let decoder = flate2::read::GzDecoder::new(file);
-        let mut archive = tar::Archive::new(decoder);
-
-        // Extract to temporary directory first
-        let temp_extract = std::env::temp_dir().join(format!("dxvk-extract-{version}"));
-        std::fs::create_dir_all(&temp_extract)?;
-        archive.unpack(&temp_extract)?;
-
-        // Find the extracted directory (usually the first subdirectory)
-        let mut entries = std::fs::read_dir(&temp_extract)?;
-        if let Some(entry) = entries.next() {
-            let extracted_dir = entry?.path();
-            if extracted_dir.is_dir() {
-                // Move contents to final destination
-                self.move_directory_contents(&extracted_dir, &extract_path)
-                    .await?;
-            }
-        }
-
-        // Clean up
-        std::fs::remove_dir_all(&temp_extract)?;
+        extract_tar_gz_into(archive_path, &extract_path, true)?;
         std::fs::remove_file(archive_path)?;
 
         Ok(extract_path)
     }
 
-    async fn move_directory_contents(&self, src: &Path, dest: &Path) -> Result<()> {
-        use std::collections::VecDeque;
+    pub async fn install_dxvk_to_prefix(&self, dxvk_path: &Path, prefix_path: &Path) -> Result<()> {
+        // Prefixes too fresh to tell are treated like the classic 64-bit layout
+        let arch = detect_prefix_arch(prefix_path).unwrap_or(PrefixArch::Win64);
 
-        let mut queue = VecDeque::new();
-        queue.push_back((src.to_path_buf(), dest.to_path_buf()));
+        // Only the first install backs DLLs up, so reinstalling or upgrading DXVK
+        // keeps the prefix's original DLLs rather than an older DXVK's
+        let backup = installed_dxvk_version(prefix_path).is_none();
 
-        while let Some((src_dir, dest_dir)) = queue.pop_front() {
-            let mut entries = fs::read_dir(&src_dir).await?;
-            while let Some(entry) = entries.next_entry().await? {
-                let src_path = entry.path();
-                let dest_path = dest_dir.join(entry.file_name());
+        // Copy x64 DLLs to system32
+        if let Some(dll64_dir) = arch.dll64_dir() {
+            self.copy_dlls(&dxvk_path.join("x64"), prefix_path, dll64_dir, backup)
+                .await?;
+        }
 
-                if src_path.is_dir() {
-                    fs::create_dir_all(&dest_path).await?;
-                    queue.push_back((src_path, dest_path));
-                } else {
-                    fs::copy(&src_path, &dest_path).await?;
-                }
+        // Copy x32 DLLs to syswow64, or system32 in a 32-bit prefix
+        match arch.dll32_dir() {
+            Some(dll32_dir) => {
+                self.copy_dlls(&dxvk_path.join("x32"), prefix_path, dll32_dir, backup)
+                    .await?
             }
+            None => println!(
+                "Skipping 32-bit DXVK DLLs: {} is a WoW64 prefix without syswow64",
+                prefix_path.display()
+            ),
         }
+
+        let name = dxvk_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        fs::write(
+            prefix_path.join(DXVK_VERSION_MARKER),
+            self.extract_version_from_name(name),
+        )
+        .await?;
+
         Ok(())
     }
 
-    pub async fn install_dxvk_to_prefix(&self, dxvk_path: &Path, prefix_path: &Path) -> Result<()> {
-        let system32_path = prefix_path.join("drive_c/windows/system32");
-        let syswow64_path = prefix_path.join("drive_c/windows/syswow64");
+    /// Remove DXVK's DLLs from a prefix, put Wine's back and forget the installed version
+    ///
+    /// Each DXVK DLL is replaced by the backup taken by `install_dxvk_to_prefix`,
+    /// or failing that by the builtin DLL of the Proton runner at `proton_path`.
+    /// DLLs with neither are left in place and returned; the version marker and
+    /// backups are then kept, so the uninstall can be retried with a runner.
+    pub async fn uninstall_dxvk_from_prefix(
+        &self,
+        prefix_path: &Path,
+        proton_path: Option<&Path>,
+    ) -> Result<Vec<String>> {
+        let backup_root = prefix_path.join(DXVK_BACKUP_DIR);
+        if installed_dxvk_version(prefix_path).is_none() && !backup_root.exists() {
+            return Err(anyhow!(
+                "No DXVK installed by Cellar found in {}",
+                prefix_path.display()
+            ));
+        }
 
-        // Ensure directories exist
-        fs::create_dir_all(&system32_path).await?;
-        fs::create_dir_all(&syswow64_path).await?;
+        let arch = detect_prefix_arch(prefix_path).unwrap_or(PrefixArch::Win64);
+        let mut kept = Vec::new();
+
+        let layouts = [
+            (arch.dll64_dir(), BUILTIN_DLL64_DIRS),
+            (arch.dll32_dir(), BUILTIN_DLL32_DIRS),
+        ];
+        for (dll_dir, builtin_dirs) in layouts {
+            let Some(dll_dir) = dll_dir else {
+                continue;
+            };
+
+            // Anything backed up was replaced by DXVK, even if it's not a usual DXVK DLL
+            let mut dlls: Vec<String> = DXVK_DLLS.iter().map(|dll| dll.to_string()).collect();
+            if let Ok(entries) = std::fs::read_dir(backup_root.join(dll_dir)) {
+                for entry in entries.flatten() {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    if !dlls.contains(&name) {
+                        dlls.push(name);
+                    }
+                }
+            }
 
-        // Copy x64 DLLs to system32
-        let x64_path = dxvk_path.join("x64");
-        if x64_path.exists() {
-            let mut entries = fs::read_dir(&x64_path).await?;
-            while let Some(entry) = entries.next_entry().await? {
-                let src = entry.path();
-                if src.extension().and_then(|s| s.to_str()) == Some("dll") {
-                    let dest = system32_path.join(entry.file_name());
-                    fs::copy(&src, &dest).await?;
+            for dll in dlls {
+                // Anything else, e.g. a DLL Wine already recreated, is left alone
+                let dest = prefix_path.join(dll_dir).join(&dll);
+                if !is_dxvk_dll(&dest) {
+                    continue;
                 }
+
+                let backup = backup_root.join(dll_dir).join(&dll);
+                let builtin = proton_path.and_then(|proton_path| {
+                    builtin_dirs
+                        .iter()
+                        .map(|dir| proton_path.join(dir).join(&dll))
+                        .find(|path| path.is_file())
+                });
+                let source = if backup.is_file() {
+                    Some(backup)
+                } else {
+                    builtin
+                };
+                let Some(source) = source else {
+                    kept.push(format!("{dll_dir}/{dll}"));
+                    continue;
+                };
+                fs::remove_file(&dest).await?;
+                fs::copy(&source, &dest).await?;
             }
         }
 
-        // Copy x32 DLLs to syswow64
-        let x32_path = dxvk_path.join("x32");
-        if x32_path.exists() {
-            let mut entries = fs::read_dir(&x32_path).await?;
-            while let Some(entry) = entries.next_entry().await? {
-                let src = entry.path();
-                if src.extension().and_then(|s| s.to_str()) == Some("dll") {
-                    let dest = syswow64_path.join(entry.file_name());
-                    fs::copy(&src, &dest).await?;
+        if !kept.is_empty() {
+            return Ok(kept);
+        }
+
+        if backup_root.exists() {
+            fs::remove_dir_all(&backup_root).await?;
+        }
+        let marker = prefix_path.join(DXVK_VERSION_MARKER);
+        if marker.exists() {
+            fs::remove_file(marker).await?;
+        }
+
+        Ok(kept)
+    }
+
+    /// Copy DXVK's DLLs into `dll_dir` of the prefix, optionally backing up the ones they replace
+    async fn copy_dlls(
+        &self,
+        src_dir: &Path,
+        prefix_path: &Path,
+        dll_dir: &str,
+        backup: bool,
+    ) -> Result<()> {
+        if !src_dir.exists() {
+            return Ok(());
+        }
+
+        let dest_dir = prefix_path.join(dll_dir);
+        let backup_dir = prefix_path.join(DXVK_BACKUP_DIR).join(dll_dir);
+        fs::create_dir_all(&dest_dir).await?;
+
+        let mut entries = fs::read_dir(src_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let src = entry.path();
+            if src.extension().and_then(|s| s.to_str()) == Some("dll") {
+                let dest = dest_dir.join(entry.file_name());
+                if backup && dest.is_file() {
+                    fs::create_dir_all(&backup_dir).await?;
+                    fs::copy(&dest, backup_dir.join(entry.file_name())).await?;
                 }
+                fs::copy(&src, &dest).await?;
             }
         }
 
@@ -222,3 +341,159 @@ impl RunnerManager for DxvkManager {
         self.base_runner.delete_runner_common(runner_path).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_dxvk(root: &Path) -> PathBuf {
+        let dxvk_path = root.join("dxvk-2.3");
+        for arch in ["x64", "x32"] {
+            std::fs::create_dir_all(dxvk_path.join(arch)).unwrap();
+            std::fs::write(
+                dxvk_path.join(arch).join("d3d11.dll"),
+                format!("dxvk {arch}"),
+            )
+            .unwrap();
+        }
+        dxvk_path
+    }
+
+    fn make_prefix(root: &Path, arch: &str, syswow64: bool) -> PathBuf {
+        let prefix_path = root.join(format!("prefix-{arch}-{syswow64}"));
+        std::fs::create_dir_all(prefix_path.join("drive_c/windows/system32")).unwrap();
+        if syswow64 {
+            std::fs::create_dir_all(prefix_path.join("drive_c/windows/syswow64")).unwrap();
+        }
+        std::fs::write(prefix_path.join("system.reg"), format!("#arch={arch}\n")).unwrap();
+        prefix_path
+    }
+
+    fn read_dll(prefix_path: &Path, dir: &str) -> Option<String> {
+        std::fs::read_to_string(
+            prefix_path
+                .join("drive_c/windows")
+                .join(dir)
+                .join("d3d11.dll"),
+        )
+        .ok()
+    }
+
+    #[test]
+    fn test_missing_dxvk_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dxvk_path = make_dxvk(temp_dir.path());
+        for dll in DXVK_DLLS {
+            std::fs::write(dxvk_path.join("x64").join(dll), "").unwrap();
+        }
+
+        assert_eq!(
+            missing_dxvk_files(&dxvk_path),
+            vec!["x32/d3d9.dll", "x32/d3d10core.dll", "x32/dxgi.dll"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_install_dxvk_to_prefix_layouts() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dxvk_path = make_dxvk(temp_dir.path());
+        let manager = DxvkManager::new(temp_dir.path().join("runners"));
+
+        let win64 = make_prefix(temp_dir.path(), "win64", true);
+        manager
+            .install_dxvk_to_prefix(&dxvk_path, &win64)
+            .await
+            .unwrap();
+        assert_eq!(read_dll(&win64, "system32").as_deref(), Some("dxvk x64"));
+        assert_eq!(read_dll(&win64, "syswow64").as_deref(), Some("dxvk x32"));
+
+        let wow64 = make_prefix(temp_dir.path(), "win64", false);
+        manager
+            .install_dxvk_to_prefix(&dxvk_path, &wow64)
+            .await
+            .unwrap();
+        assert_eq!(read_dll(&wow64, "system32").as_deref(), Some("dxvk x64"));
+        assert!(!wow64.join("drive_c/windows/syswow64").exists());
+
+        let win32 = make_prefix(temp_dir.path(), "win32", false);
+        manager
+            .install_dxvk_to_prefix(&dxvk_path, &win32)
+            .await
+            .unwrap();
+        assert_eq!(read_dll(&win32, "system32").as_deref(), Some("dxvk x32"));
+        assert!(!win32.join("drive_c/windows/syswow64").exists());
+    }
+
+    #[tokio::test]
+    async fn test_uninstall_dxvk_restores_original_dlls() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dxvk_path = make_dxvk(temp_dir.path());
+        let manager = DxvkManager::new(temp_dir.path().join("runners"));
+
+        let prefix = make_prefix(temp_dir.path(), "win64", true);
+        let system32 = prefix.join("drive_c/windows/system32");
+        std::fs::write(system32.join("d3d11.dll"), "wine").unwrap();
+        std::fs::write(system32.join("dxgi.dll"), "wine").unwrap();
+
+        // A second install must not back up the first install's DLLs
+        for _ in 0..2 {
+            manager
+                .install_dxvk_to_prefix(&dxvk_path, &prefix)
+                .await
+                .unwrap();
+        }
+        assert_eq!(installed_dxvk_version(&prefix).as_deref(), Some("2.3"));
+        assert_eq!(read_dll(&prefix, "system32").as_deref(), Some("dxvk x64"));
+
+        // syswow64 had no d3d11.dll to back up, so it comes from Proton's builtins
+        let proton = temp_dir.path().join("proton");
+        let builtin_dir = proton.join("files/lib/wine/i386-windows");
+        std::fs::create_dir_all(&builtin_dir).unwrap();
+        std::fs::write(builtin_dir.join("d3d11.dll"), "builtin").unwrap();
+
+        let kept = manager
+            .uninstall_dxvk_from_prefix(&prefix, Some(&proton))
+            .await
+            .unwrap();
+        assert!(kept.is_empty());
+        assert_eq!(read_dll(&prefix, "system32").as_deref(), Some("wine"));
+        assert_eq!(read_dll(&prefix, "syswow64").as_deref(), Some("builtin"));
+        // Not replaced by DXVK, so not touched
+        assert_eq!(
+            std::fs::read_to_string(system32.join("dxgi.dll")).unwrap(),
+            "wine"
+        );
+        assert_eq!(installed_dxvk_version(&prefix), None);
+        assert!(!prefix.join(DXVK_BACKUP_DIR).exists());
+    }
+
+    #[tokio::test]
+    async fn test_uninstall_dxvk_keeps_unrestorable_dlls() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dxvk_path = make_dxvk(temp_dir.path());
+        let manager = DxvkManager::new(temp_dir.path().join("runners"));
+
+        let prefix = make_prefix(temp_dir.path(), "win64", false);
+        manager
+            .install_dxvk_to_prefix(&dxvk_path, &prefix)
+            .await
+            .unwrap();
+
+        let kept = manager
+            .uninstall_dxvk_from_prefix(&prefix, None)
+            .await
+            .unwrap();
+        assert_eq!(kept, vec!["drive_c/windows/system32/d3d11.dll"]);
+        assert_eq!(read_dll(&prefix, "system32").as_deref(), Some("dxvk x64"));
+        assert!(installed_dxvk_version(&prefix).is_some());
+
+        // A vanilla prefix has nothing for Cellar to uninstall
+        let vanilla = make_prefix(temp_dir.path(), "win32", true);
+        std::fs::write(vanilla.join("drive_c/windows/system32/d3d11.dll"), "wine").unwrap();
+        assert!(manager
+            .uninstall_dxvk_from_prefix(&vanilla, None)
+            .await
+            .is_err());
+        assert_eq!(read_dll(&vanilla, "system32").as_deref(), Some("wine"));
+    }
+}
@@ -1,9 +1,12 @@
 pub mod common;
 pub mod dxvk;
 pub mod proton;
+pub mod wine;
 
 use anyhow::Result;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,15 +16,134 @@ pub struct Runner {
     pub path: PathBuf,
     pub runner_type: RunnerType,
     pub installed: bool,
+    /// Pinned runners are protected from bulk cleanup
+    #[serde(default)]
+    pub pinned: bool,
+    /// Other places the same version is installed; `path` is the one used
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub other_paths: Vec<PathBuf>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Serialized in lowercase to match the names used on the command line;
+/// the capitalized names older caches used are still accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum RunnerType {
+    #[serde(alias = "Proton")]
     Proton,
+    #[serde(alias = "Wine")]
     Wine,
+    #[serde(alias = "Dxvk")]
     Dxvk,
 }
 
+impl Runner {
+    /// Numeric version components used for ordering, parsed according to the runner type
+    pub fn version_tuple(&self) -> (u32, u32, u32) {
+        parse_runner_version(self.runner_type, &self.version)
+    }
+
+    /// Whether `version` names this runner: its exact version, its exact name,
+    /// or a full `-`-delimited suffix of the name (e.g. "GE-Proton8-26" for "wine-GE-Proton8-26")
+    pub fn matches_version(&self, version: &str) -> bool {
+        self.version == version || self.matches_name(version)
+    }
+
+    /// Like `matches_version`, but only against the runner's name
+    pub fn matches_name(&self, name: &str) -> bool {
+        self.name == name || self.name.ends_with(&format!("-{name}"))
+    }
+
+    /// Directory holding the runner's `wine` and `wineserver` binaries
+    pub fn wine_bin_dir(&self) -> PathBuf {
+        match self.runner_type {
+            RunnerType::Wine => self.path.join("bin"),
+            RunnerType::Proton | RunnerType::Dxvk => self.path.join("files/bin"),
+        }
+    }
+}
+
+/// Runners are identified by name, version and type; the install path is ignored
+/// so the same build discovered in two locations compares equal
+impl PartialEq for Runner {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.version == other.version
+            && self.runner_type == other.runner_type
+    }
+}
+
+impl Eq for Runner {}
+
+impl Hash for Runner {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.version.hash(state);
+        self.runner_type.hash(state);
+    }
+}
+
+/// Parse a version string the way runners of `runner_type` are versioned
+pub fn parse_runner_version(runner_type: RunnerType, version: &str) -> (u32, u32, u32) {
+    match runner_type {
+        RunnerType::Dxvk => parse_semver(version),
+        RunnerType::Proton | RunnerType::Wine => parse_proton_version(version),
+    }
+}
+
+/// The newest of `versions`, ordered the same way as installed runners
+pub fn newest_version(runner_type: RunnerType, versions: &[String]) -> Option<&String> {
+    versions
+        .iter()
+        .max_by_key(|version| parse_runner_version(runner_type, version))
+}
+
+/// Parse Proton-GE style versions ("GE-Proton9-1", "9-1") into (major, minor, patch)
+pub fn parse_proton_version(version: &str) -> (u32, u32, u32) {
+    parse_version_components(version, r"(\d+)(?:[.-](\d+))?(?:[.-](\d+))?")
+}
+
+/// Parse DXVK style semantic versions ("2.3.1", "v2.3") into (major, minor, patch)
+pub fn parse_semver(version: &str) -> (u32, u32, u32) {
+    parse_version_components(version, r"(\d+)(?:\.(\d+))?(?:\.(\d+))?")
+}
+
+fn parse_version_components(version: &str, pattern: &str) -> (u32, u32, u32) {
+    let captures = match Regex::new(pattern).unwrap().captures(version) {
+        Some(captures) => captures,
+        None => return (0, 0, 0),
+    };
+
+    let component = |index: usize| {
+        captures
+            .get(index)
+            .and_then(|m| m.as_str().parse().ok())
+            .unwrap_or(0)
+    };
+
+    (component(1), component(2), component(3))
+}
+
+/// Find the installed runner a game's `proton_version` refers to
+///
+/// Proton builds are matched first; Wine runners only when no Proton build
+/// matches, so they are usually selected by their full name (e.g. "wine-GE-Proton8-26").
+pub async fn find_game_runner(runners_path: &Path, version: &str) -> Result<Option<Runner>> {
+    let matches = |r: &Runner| r.matches_version(version);
+
+    let proton_runners = proton::ProtonManager::new(runners_path.to_path_buf())
+        .discover_local_runners()
+        .await?;
+    if let Some(runner) = proton_runners.into_iter().find(matches) {
+        return Ok(Some(runner));
+    }
+
+    let wine_runners = wine::WineManager::new(runners_path.to_path_buf())
+        .discover_local_runners()
+        .await?;
+    Ok(wine_runners.into_iter().find(matches))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RunnerCache {
     pub runners: Vec<Runner>,
@@ -42,27 +164,88 @@ impl RunnerCache {
         }
     }
 
+    #[allow(dead_code)]
     pub fn add_runner(&mut self, runner: Runner) {
         self.runners.push(runner);
         self.last_updated = chrono::Utc::now();
     }
 
+    #[allow(dead_code)]
     pub fn find_runner(&self, name: &str, version: Option<&str>) -> Option<&Runner> {
         self.runners
             .iter()
             .find(|r| r.name == name && (version.is_none() || version == Some(&r.version)))
     }
 
+    #[allow(dead_code)]
     pub fn get_runners_by_type(&self, runner_type: RunnerType) -> Vec<&Runner> {
         self.runners
             .iter()
-            .filter(|r| {
-                std::mem::discriminant(&r.runner_type) == std::mem::discriminant(&runner_type)
-            })
+            .filter(|r| r.runner_type == runner_type)
             .collect()
     }
 }
 
+/// Runners protected from bulk cleanup, stored in `runners/pins.toml` so they
+/// survive the runner cache being cleared or rebuilt
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunnerPins {
+    #[serde(default)]
+    pub pinned: Vec<PinnedRunner>,
+}
+
+/// A pinned runner, identified by type and install path like in the cache
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinnedRunner {
+    pub runner_type: RunnerType,
+    /// Kept for readability; matching uses the path
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl RunnerPins {
+    /// Pins recorded in a runner cache, where versions before `pins.toml` kept them
+    pub fn from_cache(cache: &RunnerCache) -> Self {
+        Self {
+            pinned: cache
+                .runners
+                .iter()
+                .filter(|r| r.pinned)
+                .map(|r| PinnedRunner {
+                    runner_type: r.runner_type,
+                    name: r.name.clone(),
+                    path: r.path.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_pinned(&self, runner: &Runner) -> bool {
+        self.pinned
+            .iter()
+            .any(|pin| pin.runner_type == runner.runner_type && pin.path == runner.path)
+    }
+
+    pub fn set_pinned(&mut self, runner: &Runner, pinned: bool) {
+        self.pinned
+            .retain(|pin| !(pin.runner_type == runner.runner_type && pin.path == runner.path));
+        if pinned {
+            self.pinned.push(PinnedRunner {
+                runner_type: runner.runner_type,
+                name: runner.name.clone(),
+                path: runner.path.clone(),
+            });
+        }
+    }
+
+    /// Set the `pinned` flag of each runner from these pins
+    pub fn apply(&self, runners: &mut [Runner]) {
+        for runner in runners {
+            runner.pinned = self.is_pinned(runner);
+        }
+    }
+}
+
 #[async_trait::async_trait]
 pub trait RunnerManager {
     async fn discover_local_runners(&self) -> Result<Vec<Runner>>;
@@ -89,6 +272,8 @@ mod tests {
             path: PathBuf::from("/test/path"),
             runner_type: RunnerType::Proton,
             installed: true,
+            pinned: false,
+            other_paths: Vec::new(),
         };
 
         let wine_runner = Runner {
@@ -97,6 +282,8 @@ mod tests {
             path: PathBuf::from("/test/wine"),
             runner_type: RunnerType::Wine,
             installed: true,
+            pinned: false,
+            other_paths: Vec::new(),
         };
 
         // Test adding runners
@@ -126,6 +313,133 @@ mod tests {
         assert_eq!(dxvk_runners.len(), 0);
     }
 
+    #[test]
+    fn test_runner_equality_ignores_path() {
+        use std::collections::HashSet;
+
+        let cellar_runner = Runner {
+            name: "GE-Proton9-1".to_string(),
+            version: "9-1".to_string(),
+            path: PathBuf::from("/cellar/runners/proton/GE-Proton9-1"),
+            runner_type: RunnerType::Proton,
+            installed: true,
+            pinned: false,
+            other_paths: Vec::new(),
+        };
+        let steam_runner = Runner {
+            path: PathBuf::from("/steam/compatibilitytools.d/GE-Proton9-1"),
+            ..cellar_runner.clone()
+        };
+        let dxvk_runner = Runner {
+            runner_type: RunnerType::Dxvk,
+            ..cellar_runner.clone()
+        };
+
+        assert_eq!(cellar_runner, steam_runner);
+        assert_ne!(cellar_runner, dxvk_runner);
+
+        let unique: HashSet<_> = [cellar_runner, steam_runner, dxvk_runner]
+            .into_iter()
+            .collect();
+        assert_eq!(unique.len(), 2);
+    }
+
+    #[test]
+    fn test_runner_matches_whole_versions_only() {
+        let runner = Runner {
+            name: "GE-Proton9-10".to_string(),
+            version: "9-10".to_string(),
+            path: PathBuf::from("/cellar/runners/proton/GE-Proton9-10"),
+            runner_type: RunnerType::Proton,
+            installed: true,
+            pinned: false,
+            other_paths: Vec::new(),
+        };
+        assert!(runner.matches_version("GE-Proton9-10"));
+        assert!(runner.matches_version("9-10"));
+        assert!(!runner.matches_version("GE-Proton9-1"));
+        assert!(!runner.matches_version("9-1"));
+
+        let wine = Runner {
+            name: "wine-GE-Proton8-26".to_string(),
+            version: "8-26".to_string(),
+            runner_type: RunnerType::Wine,
+            ..runner
+        };
+        assert!(wine.matches_name("GE-Proton8-26"));
+        assert!(!wine.matches_name("Proton8-2"));
+    }
+
+    #[test]
+    fn test_parse_proton_version() {
+        assert_eq!(parse_proton_version("GE-Proton9-1"), (9, 1, 0));
+        assert_eq!(parse_proton_version("GE-Proton10-10"), (10, 10, 0));
+        assert_eq!(parse_proton_version("GE-Proton8-32"), (8, 32, 0));
+        assert_eq!(parse_proton_version("10-10"), (10, 10, 0));
+        assert_eq!(parse_proton_version("Proton 8.0-5"), (8, 0, 5));
+
+        // Fallback for non-standard versions
+        assert_eq!(parse_proton_version("some-version-5"), (5, 0, 0));
+        assert_eq!(parse_proton_version("no-numbers"), (0, 0, 0));
+
+        // Minor versions compare numerically, not lexically
+        assert!(parse_proton_version("GE-Proton9-10") > parse_proton_version("GE-Proton9-9"));
+        assert!(parse_proton_version("GE-Proton10-1") > parse_proton_version("GE-Proton9-27"));
+    }
+
+    #[test]
+    fn test_parse_semver() {
+        assert_eq!(parse_semver("2.3.1"), (2, 3, 1));
+        assert_eq!(parse_semver("v2.3"), (2, 3, 0));
+        assert_eq!(parse_semver("2.10"), (2, 10, 0));
+        assert_eq!(parse_semver("unknown"), (0, 0, 0));
+        assert!(parse_semver("2.10") > parse_semver("2.9.1"));
+    }
+
+    #[test]
+    fn test_newest_version() {
+        let proton: Vec<String> = ["GE-Proton9-27", "GE-Proton10-1", "GE-Proton10-10"]
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        assert_eq!(
+            newest_version(RunnerType::Proton, &proton).unwrap(),
+            "GE-Proton10-10"
+        );
+
+        let dxvk: Vec<String> = ["2.9.1", "2.10", "1.10.3"]
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+        assert_eq!(newest_version(RunnerType::Dxvk, &dxvk).unwrap(), "2.10");
+        assert!(newest_version(RunnerType::Dxvk, &[]).is_none());
+    }
+
+    #[test]
+    fn test_version_tuple_by_runner_type() {
+        let proton_runner = Runner {
+            name: "GE-Proton9-27".to_string(),
+            version: "9-27".to_string(),
+            path: PathBuf::from("/path/to/proton"),
+            runner_type: RunnerType::Proton,
+            installed: true,
+            pinned: false,
+            other_paths: Vec::new(),
+        };
+        let dxvk_runner = Runner {
+            name: "DXVK-v2.3.1".to_string(),
+            version: "2.3.1".to_string(),
+            path: PathBuf::from("/path/to/dxvk"),
+            runner_type: RunnerType::Dxvk,
+            installed: true,
+            pinned: false,
+            other_paths: Vec::new(),
+        };
+
+        assert_eq!(proton_runner.version_tuple(), (9, 27, 0));
+        assert_eq!(dxvk_runner.version_tuple(), (2, 3, 1));
+    }
+
     #[test]
     fn test_runner_creation() {
         let runner = Runner {
@@ -134,6 +448,8 @@ mod tests {
             path: PathBuf::from("/path/to/proton"),
             runner_type: RunnerType::Proton,
             installed: true,
+            pinned: false,
+            other_paths: Vec::new(),
         };
 
         assert_eq!(runner.name, "GE-Proton8-32");
@@ -200,4 +516,83 @@ mod tests {
         // Should return error for nonexistent path
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_runner_cache_round_trip() {
+        let mut cache = RunnerCache::new();
+        cache.add_runner(Runner {
+            name: "GE-Proton9-1".to_string(),
+            version: "GE-Proton9-1".to_string(),
+            path: PathBuf::from("/runners/proton/GE-Proton9-1"),
+            runner_type: RunnerType::Proton,
+            installed: true,
+            pinned: false,
+            other_paths: Vec::new(),
+        });
+        cache.add_runner(Runner {
+            name: "DXVK-dxvk-2.3".to_string(),
+            version: "2.3".to_string(),
+            path: PathBuf::from("/runners/dxvk/dxvk-2.3"),
+            runner_type: RunnerType::Dxvk,
+            installed: true,
+            pinned: false,
+            other_paths: Vec::new(),
+        });
+
+        let serialized = toml::to_string(&cache).unwrap();
+        assert!(serialized.contains("runner_type = \"proton\""));
+        assert!(serialized.contains("runner_type = \"dxvk\""));
+
+        let reparsed: RunnerCache = toml::from_str(&serialized).unwrap();
+        assert_eq!(reparsed.runners, cache.runners);
+        assert_eq!(reparsed.last_updated, cache.last_updated);
+
+        // Caches from before the rename still parse
+        let old_format = serialized
+            .replace("\"proton\"", "\"Proton\"")
+            .replace("\"dxvk\"", "\"Dxvk\"");
+        let reparsed: RunnerCache = toml::from_str(&old_format).unwrap();
+        assert_eq!(reparsed.runners, cache.runners);
+    }
+
+    #[test]
+    fn test_runner_pins() {
+        let runner = |name: &str, pinned: bool| Runner {
+            name: name.to_string(),
+            version: name.to_string(),
+            path: PathBuf::from(format!("/runners/proton/{name}")),
+            runner_type: RunnerType::Proton,
+            installed: true,
+            pinned,
+            other_paths: Vec::new(),
+        };
+
+        // Pins from an old cache carry over
+        let mut old_cache = RunnerCache::new();
+        old_cache.add_runner(runner("GE-Proton9-1", true));
+        old_cache.add_runner(runner("GE-Proton9-2", false));
+        let mut pins = RunnerPins::from_cache(&old_cache);
+
+        pins.set_pinned(&runner("GE-Proton9-3", false), true);
+        pins.set_pinned(&runner("GE-Proton9-3", false), true);
+        let reparsed: RunnerPins = toml::from_str(&toml::to_string(&pins).unwrap()).unwrap();
+        assert_eq!(reparsed.pinned.len(), 2);
+
+        let mut runners = vec![
+            runner("GE-Proton9-1", false),
+            runner("GE-Proton9-2", true),
+            runner("GE-Proton9-3", false),
+        ];
+        reparsed.apply(&mut runners);
+        let pinned: Vec<_> = runners
+            .iter()
+            .filter(|r| r.pinned)
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(pinned, vec!["GE-Proton9-1", "GE-Proton9-3"]);
+
+        pins.set_pinned(&runner("GE-Proton9-1", true), false);
+        assert!(!pins.is_pinned(&runner("GE-Proton9-1", true)));
+        assert!(pins.is_pinned(&runner("GE-Proton9-3", false)));
+    }
 }
@@ -1,18 +1,62 @@
 use super::common::{AssetFilter, BaseGitHubRunner, GitHubRunnerConfig};
 use super::{Runner, RunnerManager, RunnerType};
+use crate::utils::archive::extract_tar_gz_into;
+use crate::utils::fs::ensure_executable;
 use anyhow::{anyhow, Result};
 use regex::Regex;
 use std::path::{Path, PathBuf};
 use tokio::fs;
 
+/// Files every Proton build needs, relative to its install directory
+const PROTON_REQUIRED_FILES: &[&str] = &["proton", "files/bin/wine"];
+
+/// Name fragments of Steam tools under `steamapps/common` that mention Proton but
+/// aren't runners, e.g. "Proton EasyAntiCheat Runtime" or "Steam Linux Runtime - sniper"
+const STEAM_NON_RUNNER_MARKERS: &[&str] =
+    &["runtime", "soldier", "sniper", "easyanticheat", "battleye"];
+
+/// Whether a `steamapps/common` directory is a Proton build Cellar can run
+///
+/// Real Proton tools ship a `proton` script alongside a `version` file or a
+/// `toolmanifest.vdf`; the name check alone also matches Steam's runtimes.
+fn is_steam_proton_tool(path: &Path, name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.contains("proton")
+        && !STEAM_NON_RUNNER_MARKERS
+            .iter()
+            .any(|marker| name.contains(marker))
+        && path.join("proton").is_file()
+        && (path.join("version").is_file() || path.join("toolmanifest.vdf").is_file())
+}
+
+/// Required files missing from a Proton install, e.g. after an interrupted extraction
+pub fn missing_proton_files(runner_path: &Path) -> Vec<String> {
+    PROTON_REQUIRED_FILES
+        .iter()
+        .filter(|file| !runner_path.join(file).is_file())
+        .map(|file| file.to_string())
+        .collect()
+}
+
 pub struct ProtonManager {
     pub steam_path: Option<PathBuf>,
+    /// Steam's `compatibilitytools.d` directories (custom Proton builds such as Proton-GE)
+    pub compat_tools_paths: Vec<PathBuf>,
     pub base_runner: BaseGitHubRunner,
 }
 
+/// A custom compatibility tool as described by its `compatibilitytool.vdf`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CompatTool {
+    internal_name: String,
+    display_name: Option<String>,
+    install_path: String,
+}
+
 impl ProtonManager {
     pub fn new(cellar_runners_path: PathBuf) -> Self {
         let steam_path = Self::find_steam_path();
+        let compat_tools_paths = Self::find_compat_tools_paths();
 
         fn asset_filter(name: &str) -> bool {
             name.ends_with(".tar.gz")
@@ -30,6 +74,7 @@ impl ProtonManager {
 
         Self {
             steam_path,
+            compat_tools_paths,
             base_runner,
         }
     }
@@ -46,6 +91,72 @@ impl ProtonManager {
         None
     }
 
+    fn find_compat_tools_paths() -> Vec<PathBuf> {
+        let Some(home) = dirs::home_dir() else {
+            return Vec::new();
+        };
+
+        // ~/.steam/steam is usually a symlink to ~/.local/share/Steam, so dedupe
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for steam_path in [home.join(".steam/steam"), home.join(".local/share/Steam")] {
+            let compat_tools = steam_path.join("compatibilitytools.d");
+            if let Ok(resolved) = std::fs::canonicalize(&compat_tools) {
+                if !paths.contains(&resolved) {
+                    paths.push(resolved);
+                }
+            }
+        }
+        paths
+    }
+
+    /// Discover custom Proton builds installed for Steam in `compatibilitytools.d`
+    pub async fn discover_compat_tools(&self) -> Result<Vec<Runner>> {
+        let mut runners = Vec::new();
+
+        for compat_tools_path in &self.compat_tools_paths {
+            let Ok(mut entries) = fs::read_dir(compat_tools_path).await else {
+                continue;
+            };
+
+            while let Some(entry) = entries.next_entry().await? {
+                let tool_dir = entry.path();
+                let Ok(vdf) = fs::read_to_string(tool_dir.join("compatibilitytool.vdf")).await
+                else {
+                    continue;
+                };
+                let Some(tool) = parse_compat_tool_vdf(&vdf) else {
+                    continue;
+                };
+
+                let path = match tool.install_path.as_str() {
+                    "." => tool_dir,
+                    install_path => tool_dir.join(install_path),
+                };
+                if !path.join("proton").exists() {
+                    continue;
+                }
+
+                let name = match &tool.display_name {
+                    Some(display_name) if *display_name != tool.internal_name => {
+                        format!("{} ({})", tool.internal_name, display_name)
+                    }
+                    _ => tool.internal_name.clone(),
+                };
+                runners.push(Runner {
+                    name,
+                    version: self.extract_version_from_name(&tool.internal_name),
+                    path,
+                    runner_type: RunnerType::Proton,
+                    installed: true,
+                    pinned: false,
+                    other_paths: Vec::new(),
+                });
+            }
+        }
+
+        Ok(runners)
+    }
+
     pub async fn discover_steam_proton(&self) -> Result<Vec<Runner>> {
         let mut runners = Vec::new();
 
@@ -62,20 +173,17 @@ impl ProtonManager {
                             .unwrap_or("")
                             .to_string();
 
-                        // Check if this looks like a Proton installation
-                        if name.to_lowercase().contains("proton") {
-                            // Look for proton executable
-                            let proton_exe = path.join("proton");
-                            if proton_exe.exists() {
-                                let version = self.extract_version_from_name(&name);
-                                runners.push(Runner {
-                                    name: name.clone(),
-                                    version,
-                                    path: path.clone(),
-                                    runner_type: RunnerType::Proton,
-                                    installed: true,
-                                });
-                            }
+                        if is_steam_proton_tool(&path, &name) {
+                            let version = self.extract_version_from_name(&name);
+                            runners.push(Runner {
+                                name: name.clone(),
+                                version,
+                                path: path.clone(),
+                                runner_type: RunnerType::Proton,
+                                installed: true,
+                                pinned: false,
+                                other_paths: Vec::new(),
+                            });
                         }
                     }
                 }
@@ -110,6 +218,8 @@ impl ProtonManager {
                             path: path.clone(),
                             runner_type: RunnerType::Proton,
                             installed: true,
+                            pinned: false,
+                            other_paths: Vec::new(),
                         });
                     }
                 }
@@ -144,58 +254,93 @@ impl ProtonManager {
         fs::create_dir_all(&proton_dir).await?;
 
         let extract_path = proton_dir.join(version);
-        fs::create_dir_all(&extract_path).await?;
-
-        // Extract tar.gz file
-        let file = std::fs::File::open(archive_path)?;
-        let decoder = flate2::read::GzDecoder::new(file);
-        let mut archive = tar::Archive::new(decoder);
-
-        // Extract to temporary directory first
-        let temp_extract = std::env::temp_dir().join(format!("proton-extract-{version}"));
-        std::fs::create_dir_all(&temp_extract)?;
-        archive.unpack(&temp_extract)?;
-
-        // Find the extracted directory (usually the first subdirectory)
-        let mut entries = std::fs::read_dir(&temp_extract)?;
-        if let Some(entry) = entries.next() {
-            let extracted_dir = entry?.path();
-            if extracted_dir.is_dir() {
-                // Move contents to final destination
-                self.move_directory_contents(&extracted_dir, &extract_path)
-                    .await?;
+        extract_tar_gz_into(archive_path, &extract_path, true)?;
+        std::fs::remove_file(archive_path)?;
+
+        // Some archives lose their modes; Proton can't launch without these
+        ensure_executable(&extract_path.join("proton"))?;
+        let bin_dir = extract_path.join("files/bin");
+        if bin_dir.is_dir() {
+            for entry in std::fs::read_dir(&bin_dir)? {
+                ensure_executable(&entry?.path())?;
             }
         }
 
-        // Clean up
-        std::fs::remove_dir_all(&temp_extract)?;
-        std::fs::remove_file(archive_path)?;
-
         Ok(extract_path)
     }
+}
 
-    async fn move_directory_contents(&self, src: &Path, dest: &Path) -> Result<()> {
-        use std::collections::VecDeque;
+/// Merge runners that share a version, keeping the other install paths on the first one
+fn dedupe_by_version(runners: Vec<Runner>) -> Vec<Runner> {
+    let mut deduped: Vec<Runner> = Vec::new();
 
-        let mut queue = VecDeque::new();
-        queue.push_back((src.to_path_buf(), dest.to_path_buf()));
+    for runner in runners {
+        match deduped.iter_mut().find(|r| r.version == runner.version) {
+            Some(existing) => existing.other_paths.push(runner.path),
+            None => deduped.push(runner),
+        }
+    }
 
-        while let Some((src_dir, dest_dir)) = queue.pop_front() {
-            let mut entries = fs::read_dir(&src_dir).await?;
-            while let Some(entry) = entries.next_entry().await? {
-                let src_path = entry.path();
-                let dest_path = dest_dir.join(entry.file_name());
-
-                if src_path.is_dir() {
-                    fs::create_dir_all(&dest_path).await?;
-                    queue.push_back((src_path, dest_path));
-                } else {
-                    fs::copy(&src_path, &dest_path).await?;
+    deduped
+}
+
+/// Parse the first tool out of a `compatibilitytool.vdf`
+///
+/// The file is Valve's KeyValues text format: quoted keys and values, nested
+/// `{ }` blocks and `//` comments. Tools are keyed by internal name under
+/// `compatibilitytools/compat_tools`.
+fn parse_compat_tool_vdf(contents: &str) -> Option<CompatTool> {
+    let mut tokens = Vec::new();
+    for line in contents.lines() {
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    let mut token = String::new();
+                    while let Some(c) = chars.next() {
+                        match c {
+                            '"' => break,
+                            '\\' => token.extend(chars.next()),
+                            _ => token.push(c),
+                        }
+                    }
+                    tokens.push(token);
                 }
+                '{' | '}' => tokens.push(c.to_string()),
+                '/' if chars.peek() == Some(&'/') => break,
+                _ => {}
             }
         }
-        Ok(())
     }
+
+    let compat_tools = tokens.iter().position(|t| t == "compat_tools")?;
+    let mut rest = tokens[compat_tools + 1..].iter();
+    if rest.next()? != "{" {
+        return None;
+    }
+    let internal_name = rest.next()?.clone();
+    if rest.next()? != "{" {
+        return None;
+    }
+
+    let mut tool = CompatTool {
+        internal_name,
+        display_name: None,
+        install_path: ".".to_string(),
+    };
+    while let Some(key) = rest.next() {
+        if key == "}" {
+            break;
+        }
+        let value = rest.next()?;
+        match key.as_str() {
+            "display_name" => tool.display_name = Some(value.clone()),
+            "install_path" => tool.install_path = value.clone(),
+            _ => {}
+        }
+    }
+
+    Some(tool)
 }
 
 #[async_trait::async_trait]
@@ -203,13 +348,17 @@ impl RunnerManager for ProtonManager {
     async fn discover_local_runners(&self) -> Result<Vec<Runner>> {
         let mut runners = Vec::new();
 
+        // Discover Cellar Proton installations first, so they win over the
+        // same version found elsewhere
+        runners.extend(self.discover_cellar_proton().await?);
+
         // Discover Steam Proton installations
         runners.extend(self.discover_steam_proton().await?);
 
-        // Discover Cellar Proton installations
-        runners.extend(self.discover_cellar_proton().await?);
+        // Discover custom Proton builds installed for Steam
+        runners.extend(self.discover_compat_tools().await?);
 
-        Ok(runners)
+        Ok(dedupe_by_version(runners))
     }
 
     async fn download_runner(&self, _name: &str, version: &str) -> Result<PathBuf> {
@@ -237,3 +386,182 @@ impl RunnerManager for ProtonManager {
         self.base_runner.delete_runner_common(runner_path).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn append_file(builder: &mut tar::Builder<impl std::io::Write>, path: &str, mode: u32) {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(9);
+        header.set_mode(mode);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, path, &b"#!/bin/sh"[..])
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_extract_proton_keeps_executables() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let archive_path = temp_dir.path().join("GE-Proton-test-exec.tar.gz");
+
+        let encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&archive_path).unwrap(),
+            flate2::Compression::fast(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        append_file(&mut builder, "GE-Proton-test-exec/proton", 0o755);
+        append_file(&mut builder, "GE-Proton-test-exec/files/bin/wine", 0o644);
+        append_file(&mut builder, "GE-Proton-test-exec/version", 0o644);
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let manager = ProtonManager::new(temp_dir.path().join("runners"));
+        let installed = manager
+            .extract_proton(&archive_path, "GE-Proton-test-exec")
+            .await
+            .unwrap();
+
+        let mode = |path: &str| {
+            std::fs::metadata(installed.join(path))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777
+        };
+        assert_eq!(mode("proton"), 0o755);
+        assert_eq!(mode("files/bin/wine"), 0o755);
+        assert_eq!(mode("version"), 0o644);
+    }
+
+    #[test]
+    fn test_missing_proton_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let runner_path = temp_dir.path().join("GE-Proton9-1");
+        std::fs::create_dir_all(runner_path.join("files/bin")).unwrap();
+        std::fs::write(runner_path.join("files/bin/wine"), "").unwrap();
+
+        assert_eq!(missing_proton_files(&runner_path), vec!["proton"]);
+
+        std::fs::write(runner_path.join("proton"), "").unwrap();
+        assert!(missing_proton_files(&runner_path).is_empty());
+    }
+
+    const GE_PROTON_VDF: &str = r#"
+"compatibilitytools"
+{
+  "compat_tools"
+  {
+    "GE-Proton9-20" // Internal name of this tool
+    {
+      // Can register this tool with Steam in two ways:
+      "install_path" "."
+      "display_name" "Proton-GE 9-20"
+
+      "from_oslist"  "windows"
+      "to_oslist"    "linux"
+    }
+  }
+}
+"#;
+
+    #[test]
+    fn test_parse_compat_tool_vdf() {
+        assert_eq!(
+            parse_compat_tool_vdf(GE_PROTON_VDF),
+            Some(CompatTool {
+                internal_name: "GE-Proton9-20".to_string(),
+                display_name: Some("Proton-GE 9-20".to_string()),
+                install_path: ".".to_string(),
+            })
+        );
+        assert_eq!(parse_compat_tool_vdf("\"compatibilitytools\" { }"), None);
+    }
+
+    #[tokio::test]
+    async fn test_discover_compat_tools() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let compat_tools = temp_dir.path().join("compatibilitytools.d");
+
+        let tool_dir = compat_tools.join("GE-Proton9-20");
+        std::fs::create_dir_all(&tool_dir).unwrap();
+        std::fs::write(tool_dir.join("compatibilitytool.vdf"), GE_PROTON_VDF).unwrap();
+        std::fs::write(tool_dir.join("proton"), "#!/usr/bin/env python3").unwrap();
+
+        // Not a Proton build, so it's skipped
+        let other_dir = compat_tools.join("boxtron");
+        std::fs::create_dir_all(&other_dir).unwrap();
+        std::fs::write(
+            other_dir.join("compatibilitytool.vdf"),
+            GE_PROTON_VDF.replace("GE-Proton9-20", "Boxtron"),
+        )
+        .unwrap();
+
+        let mut manager = ProtonManager::new(temp_dir.path().join("runners"));
+        manager.compat_tools_paths = vec![compat_tools];
+
+        let runners = manager.discover_compat_tools().await.unwrap();
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "GE-Proton9-20 (Proton-GE 9-20)");
+        assert_eq!(runners[0].version, "9-20");
+        assert_eq!(runners[0].path, tool_dir);
+    }
+
+    #[tokio::test]
+    async fn test_discover_steam_proton_skips_runtimes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let common = temp_dir.path().join("steamapps/common");
+
+        let make_tool = |name: &str, files: &[&str]| {
+            let dir = common.join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            for file in files {
+                std::fs::write(dir.join(file), "").unwrap();
+            }
+        };
+        make_tool("Proton 9.0", &["proton", "version", "toolmanifest.vdf"]);
+        make_tool("Proton - Experimental", &["proton", "toolmanifest.vdf"]);
+        make_tool("Proton EasyAntiCheat Runtime", &["proton", "version"]);
+        make_tool("Proton BattlEye Runtime", &["version"]);
+        make_tool("SteamLinuxRuntime_sniper", &["toolmanifest.vdf"]);
+        make_tool("Proton Stub", &["proton"]);
+
+        let mut manager = ProtonManager::new(temp_dir.path().join("runners"));
+        manager.steam_path = Some(temp_dir.path().to_path_buf());
+
+        let mut names: Vec<_> = manager
+            .discover_steam_proton()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|runner| runner.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["Proton - Experimental", "Proton 9.0"]);
+    }
+
+    #[tokio::test]
+    async fn test_discover_prefers_cellar_install_of_same_version() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let compat_tools = temp_dir.path().join("compatibilitytools.d");
+        let tool_dir = compat_tools.join("GE-Proton9-20");
+        std::fs::create_dir_all(&tool_dir).unwrap();
+        std::fs::write(tool_dir.join("compatibilitytool.vdf"), GE_PROTON_VDF).unwrap();
+        std::fs::write(tool_dir.join("proton"), "#!/usr/bin/env python3").unwrap();
+
+        let runners_path = temp_dir.path().join("runners");
+        let cellar_dir = runners_path.join("proton/GE-Proton9-20");
+        std::fs::create_dir_all(&cellar_dir).unwrap();
+        std::fs::write(cellar_dir.join("proton"), "#!/usr/bin/env python3").unwrap();
+
+        let mut manager = ProtonManager::new(runners_path);
+        manager.steam_path = None;
+        manager.compat_tools_paths = vec![compat_tools];
+
+        let runners = manager.discover_local_runners().await.unwrap();
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].path, cellar_dir);
+        assert_eq!(runners[0].other_paths, vec![tool_dir]);
+    }
+}
@@ -0,0 +1,196 @@
+use super::common::{AssetFilter, BaseGitHubRunner, GitHubRunnerConfig};
+use super::{Runner, RunnerManager, RunnerType};
+use crate::utils::archive::extract_tar_xz_into;
+use crate::utils::fs::ensure_executable;
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Manages Wine-GE builds, plain Wine run directly instead of through Proton and umu-run
+pub struct WineManager {
+    pub base_runner: BaseGitHubRunner,
+}
+
+impl WineManager {
+    pub fn new(cellar_runners_path: PathBuf) -> Self {
+        fn asset_filter(name: &str) -> bool {
+            name.ends_with(".tar.xz")
+        }
+
+        let config = GitHubRunnerConfig {
+            repo_owner: "GloriousEggroll".to_string(),
+            repo_name: "wine-ge-custom".to_string(),
+            user_agent: "cellar/0.1.0".to_string(),
+            max_download_size: 1024 * 1024 * 1024, // 1GB
+            asset_filter: asset_filter as AssetFilter,
+        };
+
+        let base_runner = BaseGitHubRunner::new(config, cellar_runners_path);
+
+        Self { base_runner }
+    }
+
+    pub async fn discover_cellar_wine(&self) -> Result<Vec<Runner>> {
+        let mut runners = Vec::new();
+        let wine_path = self.base_runner.cellar_runners_path.join("wine");
+
+        if wine_path.exists() {
+            let mut entries = fs::read_dir(&wine_path).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if !path.is_dir() {
+                    continue;
+                }
+
+                let name = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("")
+                    .to_string();
+
+                // Look for the wine binary
+                if path.join("bin/wine").exists() {
+                    runners.push(Runner {
+                        name: format!("wine-{name}"),
+                        version: self.extract_version_from_name(&name),
+                        path: path.clone(),
+                        runner_type: RunnerType::Wine,
+                        installed: true,
+                        pinned: false,
+                        other_paths: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        Ok(runners)
+    }
+
+    fn extract_version_from_name(&self, name: &str) -> String {
+        // Extract version from names like "GE-Proton8-26" or "lutris-GE-Proton8-26-x86_64"
+        if let Some(captures) = Regex::new(r"(?i)proton[^\d]*(\d+(?:[.-]\d+)*)")
+            .unwrap()
+            .captures(name)
+        {
+            captures
+                .get(1)
+                .map_or_else(|| name.to_string(), |m| m.as_str().to_string())
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Download a Wine-GE release, given as "GE-Proton8-26" or just "8-26"
+    pub async fn download_wine_ge(&self, version: &str) -> Result<PathBuf> {
+        let version = version.strip_prefix("GE-Proton").unwrap_or(version);
+        self.base_runner
+            .download_from_github(version, "GE-Proton")
+            .await
+    }
+
+    pub async fn extract_wine(&self, archive_path: &Path, version: &str) -> Result<PathBuf> {
+        let wine_dir = self.base_runner.cellar_runners_path.join("wine");
+        fs::create_dir_all(&wine_dir).await?;
+
+        let extract_path = wine_dir.join(version);
+        extract_tar_xz_into(archive_path, &extract_path, true)?;
+        std::fs::remove_file(archive_path)?;
+
+        // Some archives lose their modes; Wine can't start without these
+        let bin_dir = extract_path.join("bin");
+        if bin_dir.is_dir() {
+            for entry in std::fs::read_dir(&bin_dir)? {
+                ensure_executable(&entry?.path())?;
+            }
+        }
+
+        Ok(extract_path)
+    }
+}
+
+#[async_trait::async_trait]
+impl RunnerManager for WineManager {
+    async fn discover_local_runners(&self) -> Result<Vec<Runner>> {
+        self.discover_cellar_wine().await
+    }
+
+    async fn download_runner(&self, _name: &str, version: &str) -> Result<PathBuf> {
+        self.download_wine_ge(version).await
+    }
+
+    async fn install_runner(&self, download_path: &Path, _install_path: &Path) -> Result<()> {
+        // Extract version from download path filename,
+        // e.g. "wine-lutris-GE-Proton8-26-x86_64.tar.xz" -> "GE-Proton8-26"
+        let filename = download_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| anyhow!("Invalid download path"))?;
+
+        let version = Regex::new(r"GE-Proton\d+(?:-\d+)*")
+            .unwrap()
+            .find(filename)
+            .map(|m| m.as_str().to_string())
+            .unwrap_or_else(|| filename.replace(".tar.xz", ""));
+
+        self.extract_wine(download_path, &version).await?;
+
+        Ok(())
+    }
+
+    async fn get_available_versions(&self) -> Result<Vec<String>> {
+        self.base_runner.get_github_versions().await
+    }
+
+    async fn delete_runner(&self, runner_path: &Path) -> Result<()> {
+        self.base_runner.delete_runner_common(runner_path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn append_file(builder: &mut tar::Builder<impl std::io::Write>, path: &str, mode: u32) {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(0);
+        header.set_mode(mode);
+        header.set_cksum();
+        builder.append_data(&mut header, path, &[][..]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_install_and_discover_wine() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manager = WineManager::new(temp_dir.path().join("runners"));
+
+        let archive_path = temp_dir
+            .path()
+            .join("wine-lutris-GE-Proton8-26-x86_64.tar.xz");
+        {
+            let file = std::fs::File::create(&archive_path).unwrap();
+            let encoder = xz2::write::XzEncoder::new(file, 6);
+            let mut builder = tar::Builder::new(encoder);
+            append_file(&mut builder, "lutris-GE-Proton8-26-x86_64/bin/wine", 0o644);
+            builder.into_inner().unwrap().finish().unwrap();
+        }
+
+        manager
+            .install_runner(&archive_path, Path::new(""))
+            .await
+            .unwrap();
+
+        let runners = manager.discover_local_runners().await.unwrap();
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "wine-GE-Proton8-26");
+        assert_eq!(runners[0].version, "8-26");
+        assert_eq!(runners[0].runner_type, RunnerType::Wine);
+
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(runners[0].path.join("bin/wine"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_ne!(mode & 0o100, 0);
+    }
+}
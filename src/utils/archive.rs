@@ -0,0 +1,304 @@
+use anyhow::{anyhow, Result};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use crate::utils::fs::copy_dir_recursive;
+
+/// Default zstd level; favours speed since prefixes are large
+const ZSTD_LEVEL: i32 = 3;
+
+/// Suffix of the hidden directories archives are unpacked into before being
+/// copied into place; one left behind means an extraction was interrupted
+pub const STAGING_SUFFIX: &str = ".partial";
+
+/// Stream `src_dir` into a zstd-compressed tarball at `output`
+///
+/// Symlinks are stored as links rather than followed, since prefixes link
+/// `dosdevices/z:` to `/`.
+pub fn create_tar_zst(src_dir: &Path, output: &Path) -> Result<()> {
+    let file = File::create(output)?;
+    let encoder = zstd::Encoder::new(file, ZSTD_LEVEL)?;
+
+    let mut builder = tar::Builder::new(encoder);
+    builder.follow_symlinks(false);
+    builder.append_dir_all(".", src_dir)?;
+
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// Bundle `entries`, given relative to `base_dir`, into a gzip-compressed tarball
+///
+/// Entries that don't exist are skipped.
+pub fn create_tar_gz(base_dir: &Path, entries: &[&str], output: &Path) -> Result<()> {
+    let file = File::create(output)?;
+    let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+
+    let mut builder = tar::Builder::new(encoder);
+    builder.follow_symlinks(false);
+    for entry in entries {
+        let path = base_dir.join(entry);
+        if path.is_dir() {
+            builder.append_dir_all(entry, &path)?;
+        } else if path.is_file() {
+            builder.append_path_with_name(&path, entry)?;
+        }
+    }
+
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// Paths of the regular files in a gzip-compressed tarball, checked the same
+/// way as when extracting
+pub fn list_tar_gz_files(archive_path: &Path) -> Result<Vec<PathBuf>> {
+    let file = File::open(archive_path)?;
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+
+    let mut files = Vec::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let entry_path = entry.path()?.into_owned();
+        validate_entry_path(&entry_path)?;
+
+        if entry.header().entry_type().is_file() {
+            files.push(entry_path);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Unpack a gzip-compressed tarball into `dest`, with the same path checks as
+/// [`extract_tar_zst_secure`]
+pub fn extract_tar_gz_secure(archive_path: &Path, dest: &Path) -> Result<()> {
+    let file = File::open(archive_path)?;
+    unpack_secure(tar::Archive::new(flate2::read::GzDecoder::new(file)), dest)
+}
+
+/// Unpack a gzip-compressed tarball into `dest`, as used for Proton and DXVK releases
+///
+/// See [`extract_into`] for how `strip_top_level` is handled.
+pub fn extract_tar_gz_into(archive_path: &Path, dest: &Path, strip_top_level: bool) -> Result<()> {
+    let file = File::open(archive_path)?;
+    extract_into(
+        tar::Archive::new(flate2::read::GzDecoder::new(file)),
+        dest,
+        strip_top_level,
+    )
+}
+
+/// Unpack an xz-compressed tarball into `dest`, as used for Wine-GE releases
+pub fn extract_tar_xz_into(archive_path: &Path, dest: &Path, strip_top_level: bool) -> Result<()> {
+    let file = File::open(archive_path)?;
+    extract_into(
+        tar::Archive::new(xz2::read::XzDecoder::new(file)),
+        dest,
+        strip_top_level,
+    )
+}
+
+/// Unpack into a staging directory beside `dest`, then move the result into `dest`
+///
+/// Release tarballs wrap everything in one directory such as `GE-Proton9-20/`;
+/// with `strip_top_level` that directory's contents land directly in `dest`.
+/// Staging next to `dest` keeps large runners off a possibly small `/tmp` and
+/// makes the move a rename on the same filesystem; the tree is only copied
+/// when `dest` already exists with contents.
+#[tracing::instrument(skip(archive), fields(dest = %dest.display()))]
+fn extract_into<R: Read>(
+    archive: tar::Archive<R>,
+    dest: &Path,
+    strip_top_level: bool,
+) -> Result<()> {
+    let name = dest
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid extraction destination: {}", dest.display()))?;
+    let staging = dest.with_file_name(format!(".{}{STAGING_SUFFIX}", name.to_string_lossy()));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+
+    let result = unpack_secure(archive, &staging).and_then(|()| {
+        let source = if strip_top_level {
+            top_level_dir(&staging)?
+        } else {
+            staging.clone()
+        };
+        tracing::debug!(source = %source.display(), "Unpacked, moving into place");
+        if fs::rename(&source, dest).is_ok() {
+            tracing::info!("Extracted");
+        } else {
+            let copied = copy_dir_recursive(&source, dest)?;
+            tracing::info!(bytes = copied, "Extracted");
+        }
+        Ok(())
+    });
+
+    // Without stripping, the staging directory itself was renamed away
+    let cleanup = match fs::remove_dir_all(&staging) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        cleanup => cleanup,
+    };
+    result?;
+    Ok(cleanup?)
+}
+
+/// The single directory an archive was unpacked into
+fn top_level_dir(dir: &Path) -> Result<PathBuf> {
+    let mut entries = fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    match entries.pop() {
+        Some(entry) if entries.is_empty() && entry.file_type()?.is_dir() => Ok(entry.path()),
+        _ => Err(anyhow!(
+            "Expected the archive to contain a single top-level directory"
+        )),
+    }
+}
+
+/// Unpack a zstd-compressed tarball into `dest`, rejecting entries that would
+/// escape it (absolute paths or `..` components)
+pub fn extract_tar_zst_secure(archive_path: &Path, dest: &Path) -> Result<()> {
+    let file = File::open(archive_path)?;
+    unpack_secure(tar::Archive::new(zstd::Decoder::new(file)?), dest)
+}
+
+fn unpack_secure<R: Read>(mut archive: tar::Archive<R>, dest: &Path) -> Result<()> {
+    fs::create_dir_all(dest)?;
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_path = entry.path()?.into_owned();
+
+        validate_entry_path(&entry_path)?;
+
+        if !entry.unpack_in(dest)? {
+            return Err(anyhow!(
+                "Refusing to extract entry outside destination: {}",
+                entry_path.display()
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_entry_path(path: &Path) -> Result<()> {
+    for component in path.components() {
+        match component {
+            Component::Normal(_) | Component::CurDir => {}
+            _ => {
+                return Err(anyhow!(
+                    "Archive contains an unsafe path: {}",
+                    path.display()
+                ))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_tar_zst_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let src = temp_dir.path().join("prefix");
+        fs::create_dir_all(src.join("drive_c/windows/system32")).unwrap();
+        fs::write(src.join("system.reg"), "WINE REGISTRY").unwrap();
+        std::os::unix::fs::symlink("/", src.join("z:")).unwrap();
+
+        let archive = temp_dir.path().join("prefix.tar.zst");
+        create_tar_zst(&src, &archive).unwrap();
+
+        let dest = temp_dir.path().join("restored");
+        extract_tar_zst_secure(&archive, &dest).unwrap();
+
+        assert!(dest.join("drive_c/windows/system32").is_dir());
+        assert_eq!(
+            fs::read_to_string(dest.join("system.reg")).unwrap(),
+            "WINE REGISTRY"
+        );
+        assert_eq!(fs::read_link(dest.join("z:")).unwrap(), Path::new("/"));
+    }
+
+    #[test]
+    fn test_tar_gz_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().join("cellar");
+        fs::create_dir_all(base.join("configs")).unwrap();
+        fs::create_dir_all(base.join("cache")).unwrap();
+        fs::write(base.join("configs/game.toml"), "[game]").unwrap();
+        fs::write(base.join("cache/runners.toml"), "runners = []").unwrap();
+
+        let archive = temp_dir.path().join("backup.tar.gz");
+        create_tar_gz(&base, &["configs", "icons", "cache/runners.toml"], &archive).unwrap();
+
+        let mut files = list_tar_gz_files(&archive).unwrap();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("cache/runners.toml"),
+                PathBuf::from("configs/game.toml")
+            ]
+        );
+
+        let dest = temp_dir.path().join("restored");
+        extract_tar_gz_secure(&archive, &dest).unwrap();
+        assert_eq!(
+            fs::read_to_string(dest.join("configs/game.toml")).unwrap(),
+            "[game]"
+        );
+    }
+
+    #[test]
+    fn test_extract_tar_gz_into_strips_top_level() {
+        let temp_dir = TempDir::new().unwrap();
+        let release = temp_dir.path().join("GE-Proton9-1");
+        fs::create_dir_all(release.join("files/bin")).unwrap();
+        fs::write(release.join("files/bin/wine"), "wine").unwrap();
+        fs::write(release.join("version"), "GE-Proton9-1").unwrap();
+
+        let archive = temp_dir.path().join("GE-Proton9-1.tar.gz");
+        create_tar_gz(temp_dir.path(), &["GE-Proton9-1"], &archive).unwrap();
+
+        let dest = temp_dir.path().join("runners/GE-Proton9-1");
+        extract_tar_gz_into(&archive, &dest, true).unwrap();
+        assert!(dest.join("files/bin/wine").is_file());
+        assert!(dest.join("version").is_file());
+        assert!(!temp_dir
+            .path()
+            .join("runners/.GE-Proton9-1.partial")
+            .exists());
+
+        let unstripped = temp_dir.path().join("unstripped");
+        extract_tar_gz_into(&archive, &unstripped, false).unwrap();
+        assert!(unstripped.join("GE-Proton9-1/version").is_file());
+        assert!(!temp_dir.path().join(".unstripped.partial").exists());
+
+        // Extracting over an existing install merges into it
+        fs::remove_file(dest.join("version")).unwrap();
+        fs::write(dest.join("user_settings.py"), "").unwrap();
+        extract_tar_gz_into(&archive, &dest, true).unwrap();
+        assert!(dest.join("version").is_file());
+        assert!(dest.join("user_settings.py").is_file());
+
+        let loose = temp_dir.path().join("loose.tar.gz");
+        create_tar_gz(&release, &["files", "version"], &loose).unwrap();
+        assert!(extract_tar_gz_into(&loose, &temp_dir.path().join("loose"), true).is_err());
+        assert!(!temp_dir.path().join(".loose.partial").exists());
+    }
+
+    #[test]
+    fn test_validate_entry_path() {
+        assert!(validate_entry_path(Path::new("./drive_c/windows")).is_ok());
+        assert!(validate_entry_path(Path::new("../escape")).is_err());
+        assert!(validate_entry_path(Path::new("drive_c/../../escape")).is_err());
+        assert!(validate_entry_path(Path::new("/etc/passwd")).is_err());
+    }
+}
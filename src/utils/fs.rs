@@ -2,6 +2,9 @@ use anyhow::{anyhow, Result};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Environment variable that moves runners out of Cellar's data directory
+pub const RUNNERS_DIR_ENV: &str = "CELLAR_RUNNERS_DIR";
+
 /// Expand tilde (~) in paths to the actual home directory
 pub fn expand_tilde<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
     let path = path.as_ref();
@@ -19,6 +22,7 @@ pub fn expand_tilde<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct CellarDirectories {
     pub base_dir: PathBuf,
     pub runners_dir: PathBuf,
@@ -26,26 +30,67 @@ pub struct CellarDirectories {
     pub configs_dir: PathBuf,
     pub icons_dir: PathBuf,
     pub applications_dir: PathBuf,
+    /// hicolor icon theme that per-size game icons are installed into
+    pub icon_theme_dir: PathBuf,
     pub cache_dir: PathBuf,
+    /// Global defaults for new games (`config.toml`)
+    pub defaults_file: PathBuf,
+    /// Base directory given with `--config-dir`, which desktop shortcuts must
+    /// pass back to Cellar
+    pub base_override: Option<PathBuf>,
 }
 
 impl CellarDirectories {
-    pub fn new() -> Result<Self> {
-        let home_dir =
-            dirs::home_dir().ok_or_else(|| anyhow!("Unable to determine home directory"))?;
-
-        let base_dir = home_dir.join(".local").join("share").join("cellar");
-        let applications_dir = home_dir.join(".local").join("share").join("applications");
+    /// Resolve Cellar's directories, rooted at `base_override` when given.
+    ///
+    /// An overridden base is fully isolated: desktop entries are written to
+    /// `<base>/applications` instead of the user's applications directory, themed
+    /// icons to `<base>/hicolor`, and global defaults are read from `<base>/config.toml`
+    /// instead of `~/.config/cellar/config.toml`.
+    ///
+    /// Runners live in `<base>/runners` unless `CELLAR_RUNNERS_DIR` or `paths.runners_dir`
+    /// in `config.toml` names another directory, in that order.
+    pub fn new(base_override: Option<&Path>) -> Result<Self> {
+        let (base_dir, applications_dir, icon_theme_dir, defaults_file) = match base_override {
+            Some(base) => {
+                let base_dir = expand_tilde(base)?;
+                let applications_dir = base_dir.join("applications");
+                let icon_theme_dir = base_dir.join("hicolor");
+                let defaults_file = base_dir.join("config.toml");
+                (base_dir, applications_dir, icon_theme_dir, defaults_file)
+            }
+            None => {
+                let home_dir = dirs::home_dir()
+                    .ok_or_else(|| anyhow!("Unable to determine home directory"))?;
+                let data_dir = home_dir.join(".local").join("share");
+                let config_dir = dirs::config_dir().unwrap_or_else(|| home_dir.join(".config"));
+                (
+                    data_dir.join("cellar"),
+                    data_dir.join("applications"),
+                    data_dir.join("icons").join("hicolor"),
+                    config_dir.join("cellar").join("config.toml"),
+                )
+            }
+        };
         let cache_dir = base_dir.join("cache");
+        let base_override = base_override.map(|_| base_dir.clone());
+        let runners_dir = resolve_runners_dir(
+            &base_dir,
+            std::env::var(RUNNERS_DIR_ENV).ok(),
+            configured_runners_dir(&defaults_file),
+        )?;
 
         let dirs = CellarDirectories {
-            runners_dir: base_dir.join("runners"),
+            runners_dir,
             prefixes_dir: base_dir.join("prefixes"),
             configs_dir: base_dir.join("configs"),
             icons_dir: base_dir.join("icons"),
             base_dir,
             applications_dir,
+            icon_theme_dir,
             cache_dir,
+            defaults_file,
+            base_override,
         };
 
         Ok(dirs)
@@ -75,9 +120,108 @@ impl CellarDirectories {
         Ok(())
     }
 
+    /// Config file of a game, given its name or its config name
+    ///
+    /// Different names can sanitize to the same file name, so a config whose file is
+    /// taken by another game gets a numeric suffix (`-2`, `-3`, ...). The suffixed files
+    /// are matched on the `game.name` stored inside them, ignoring case when no name
+    /// matches exactly and only one matches that way. When no config matches, the
+    /// returned path does not exist, so a config holding another game is never picked.
     pub fn get_game_config_path(&self, game_name: &str) -> PathBuf {
-        self.configs_dir
-            .join(format!("{}.toml", sanitize_filename(game_name)))
+        let base = sanitize_filename(game_name);
+        let existing = self.existing_game_configs(&base);
+        let by_name = existing
+            .iter()
+            .find(|path| stored_game_name(path).as_deref() == Some(game_name));
+        let by_stem = || {
+            existing
+                .iter()
+                .find(|path| path.file_stem().and_then(|s| s.to_str()) == Some(game_name))
+        };
+        let by_name_ignoring_case = || {
+            let query = game_name.to_lowercase();
+            let mut matches = existing.iter().filter(|path| {
+                stored_game_name(path).is_some_and(|name| name.to_lowercase() == query)
+            });
+            match (matches.next(), matches.next()) {
+                (Some(path), None) => Some(path),
+                _ => None,
+            }
+        };
+        // A config without a readable name can't belong to another game; returning it
+        // lets loading it report what's wrong with it
+        let unreadable = || {
+            let path = self.configs_dir.join(format!("{base}.toml"));
+            (path.is_file() && stored_game_name(&path).is_none()).then_some(path)
+        };
+        by_name
+            .or_else(by_stem)
+            .or_else(by_name_ignoring_case)
+            .cloned()
+            .or_else(unreadable)
+            .unwrap_or_else(|| self.free_game_config_path(&base))
+    }
+
+    /// Path a game's config should be written to: its existing file, or the first
+    /// file name not used by another game
+    pub fn game_config_path_for_save(&self, game_name: &str) -> PathBuf {
+        let base = sanitize_filename(game_name);
+        self.existing_game_configs(&base)
+            .into_iter()
+            .find(|path| stored_game_name(path).as_deref() == Some(game_name))
+            .unwrap_or_else(|| self.free_game_config_path(&base))
+    }
+
+    /// Config name of a game (its config file stem), which names its other files too
+    pub fn get_game_file_stem(&self, game_name: &str) -> String {
+        self.get_game_config_path(game_name)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| sanitize_filename(game_name))
+    }
+
+    /// Existing `<base>.toml` and `<base>-N.toml` config files, unsuffixed first
+    ///
+    /// Suffixes can have gaps once a game is removed, so the whole directory is listed.
+    fn existing_game_configs(&self, base: &str) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(&self.configs_dir) else {
+            return Vec::new();
+        };
+        let mut configs: Vec<(u32, PathBuf)> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("toml")
+            })
+            .filter_map(|path| {
+                let stem = path.file_stem()?.to_str()?;
+                let suffix = if stem == base {
+                    1
+                } else {
+                    let digits = stem.strip_prefix(base)?.strip_prefix('-')?;
+                    if !digits.bytes().all(|b| b.is_ascii_digit()) || digits.starts_with('0') {
+                        return None;
+                    }
+                    digits.parse().ok().filter(|&n| n >= 2)?
+                };
+                Some((suffix, path))
+            })
+            .collect();
+        configs.sort();
+        configs.into_iter().map(|(_, path)| path).collect()
+    }
+
+    /// First of `<base>.toml`, `<base>-2.toml`, ... that doesn't exist yet
+    fn free_game_config_path(&self, base: &str) -> PathBuf {
+        let unsuffixed = self.configs_dir.join(format!("{base}.toml"));
+        if !unsuffixed.exists() {
+            return unsuffixed;
+        }
+        (2..)
+            .map(|suffix| self.configs_dir.join(format!("{base}-{suffix}.toml")))
+            .find(|path| !path.exists())
+            .expect("some config suffix is free")
     }
 
     // pub fn get_game_prefix_path(&self, game_name: &str) -> PathBuf {
@@ -86,14 +230,37 @@ impl CellarDirectories {
 
     #[allow(dead_code)]
     pub fn get_game_icon_path(&self, game_name: &str, extension: &str) -> PathBuf {
+        self.icons_dir.join(format!(
+            "{}.{}",
+            self.get_game_file_stem(game_name),
+            extension
+        ))
+    }
+
+    /// User-supplied icon; kept apart from extracted icons so removing a shortcut leaves it alone
+    pub fn get_custom_icon_path(&self, game_name: &str) -> PathBuf {
         self.icons_dir
-            .join(format!("{}.{}", sanitize_filename(game_name), extension))
+            .join(format!("{}-custom.png", self.get_game_file_stem(game_name)))
+    }
+
+    /// Icon theme name for a game, as used in `Icon=` of its desktop entry
+    pub fn get_themed_icon_name(&self, game_name: &str) -> String {
+        format!("cellar-{}", self.get_game_file_stem(game_name))
+    }
+
+    pub fn get_themed_icon_path(&self, game_name: &str, size: u32) -> PathBuf {
+        self.icon_theme_dir
+            .join(format!("{size}x{size}"))
+            .join("apps")
+            .join(format!("{}.png", self.get_themed_icon_name(game_name)))
     }
 
     #[allow(dead_code)]
     pub fn get_game_shortcut_path(&self, game_name: &str) -> PathBuf {
-        self.applications_dir
-            .join(format!("cellar-{}.desktop", sanitize_filename(game_name)))
+        self.applications_dir.join(format!(
+            "cellar-{}.desktop",
+            self.get_game_file_stem(game_name)
+        ))
     }
 
     pub fn list_game_configs(&self) -> Result<Vec<String>> {
@@ -120,6 +287,11 @@ impl CellarDirectories {
         self.runners_dir.clone()
     }
 
+    /// Runners pinned against bulk cleanup; not in the cache so clearing it keeps them
+    pub fn get_runner_pins_path(&self) -> PathBuf {
+        self.runners_dir.join("pins.toml")
+    }
+
     pub fn get_prefixes_path(&self) -> PathBuf {
         self.prefixes_dir.clone()
     }
@@ -127,19 +299,379 @@ impl CellarDirectories {
     pub fn get_cache_path(&self) -> PathBuf {
         self.cache_dir.clone()
     }
+
+    pub fn get_logs_path(&self) -> PathBuf {
+        self.cache_dir.join("logs")
+    }
+
+    /// Where runner archives and installers are downloaded, kept on disk rather
+    /// than in a possibly RAM-backed temp dir so large downloads fit and can resume
+    pub fn get_downloads_path(&self) -> PathBuf {
+        self.cache_dir.join("downloads")
+    }
+
+    pub fn get_game_log_path(&self, game_name: &str) -> PathBuf {
+        self.get_logs_path()
+            .join(format!("{}.log", self.get_game_file_stem(game_name)))
+    }
+
+    pub fn get_game_lock_path(&self, game_name: &str) -> PathBuf {
+        self.cache_dir
+            .join("locks")
+            .join(format!("{}.lock", self.get_game_file_stem(game_name)))
+    }
+}
+
+/// Runners directory: from the environment, then the config file, then under `base_dir`
+fn resolve_runners_dir(
+    base_dir: &Path,
+    from_env: Option<String>,
+    from_config: Option<PathBuf>,
+) -> Result<PathBuf> {
+    match from_env.filter(|dir| !dir.trim().is_empty()) {
+        Some(dir) => expand_tilde(dir),
+        None => match from_config {
+            Some(dir) => expand_tilde(dir),
+            None => Ok(base_dir.join("runners")),
+        },
+    }
+}
+
+/// `paths.runners_dir` from the defaults file, read on its own so a mistake
+/// elsewhere in the file doesn't stop every command
+fn configured_runners_dir(defaults_file: &Path) -> Option<PathBuf> {
+    let content = fs::read_to_string(defaults_file).ok()?;
+    let table = match content.parse::<toml::Table>() {
+        Ok(table) => table,
+        Err(e) => {
+            eprintln!(
+                "Warning: Failed to parse {}, using the default runners directory: {}",
+                defaults_file.display(),
+                e
+            );
+            return None;
+        }
+    };
+
+    let runners_dir = table.get("paths")?.get("runners_dir")?;
+    match runners_dir.as_str() {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => {
+            eprintln!(
+                "Warning: paths.runners_dir in {} must be a string, using the default runners directory",
+                defaults_file.display()
+            );
+            None
+        }
+    }
+}
+
+/// `game.name` stored in a config file, without parsing the rest of the config
+fn stored_game_name(config_path: &Path) -> Option<String> {
+    let content = fs::read_to_string(config_path).ok()?;
+    let table = content.parse::<toml::Table>().ok()?;
+    table.get("game")?.get("name")?.as_str().map(str::to_string)
+}
+
+/// Replace `path` with `contents` so readers see either the old or the new file, never a partial one
+///
+/// The data goes to a temporary file next to `path` first, which is then renamed over it.
+pub fn write_file_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid file path: {}", path.display()))?
+        .to_string_lossy();
+    // The PID keeps two Cellar processes writing at once from sharing a temporary file
+    let tmp_path = path.with_file_name(format!("{file_name}.{}.tmp", std::process::id()));
+
+    fs::write(&tmp_path, contents)
+        .map_err(|e| anyhow!("Failed to write {}: {}", tmp_path.display(), e))?;
+    if let Err(e) = fs::rename(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(anyhow!("Failed to replace {}: {}", path.display(), e));
+    }
+
+    Ok(())
 }
 
+/// Total size in bytes of the files under `path`
+///
+/// Symlinks are never followed, so a prefix's `dosdevices` links to `/` are not counted.
+/// Entries that can't be read are skipped.
+pub fn dir_size(path: &Path) -> Result<u64> {
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(if metadata.is_file() {
+            metadata.len()
+        } else {
+            0
+        });
+    }
+
+    let mut total = 0;
+    let mut pending = vec![path.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries.flatten() {
+            // DirEntry::metadata does not traverse symlinks
+            if let Ok(metadata) = entry.metadata() {
+                if metadata.is_dir() {
+                    pending.push(entry.path());
+                } else if metadata.is_file() {
+                    total += metadata.len();
+                }
+            }
+        }
+    }
+
+    Ok(total)
+}
+
+/// Recursively copy `src` to `dst`, preserving file permissions and modification times
+///
+/// Symlinks are copied as links. Absolute links that point inside `src` are
+/// re-pointed at the same place inside `dst` so the copy doesn't depend on the
+/// original. Returns the number of bytes copied.
+pub fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<u64> {
+    let mut copied = 0;
+    let mut pending = vec![(src.to_path_buf(), dst.to_path_buf())];
+
+    while let Some((src_dir, dst_dir)) = pending.pop() {
+        fs::create_dir_all(&dst_dir)?;
+        fs::set_permissions(&dst_dir, fs::metadata(&src_dir)?.permissions())?;
+
+        for entry in fs::read_dir(&src_dir)? {
+            let entry = entry?;
+            let src_path = entry.path();
+            let dst_path = dst_dir.join(entry.file_name());
+            let file_type = entry.file_type()?;
+
+            if file_type.is_symlink() {
+                let target = fs::read_link(&src_path)?;
+                let target = match target.strip_prefix(src) {
+                    Ok(relative) if target.is_absolute() => dst.join(relative),
+                    _ => target,
+                };
+                std::os::unix::fs::symlink(&target, &dst_path)?;
+            } else if file_type.is_dir() {
+                pending.push((src_path, dst_path));
+            } else {
+                copied += copy_file_preserving(&src_path, &dst_path)?;
+            }
+        }
+    }
+
+    Ok(copied)
+}
+
+/// Copy one file and re-apply the source's mode and modification time
+///
+/// `fs::copy` usually carries the mode over, but not on every filesystem, and it
+/// never keeps timestamps. Runner binaries such as Proton's `wine` must stay
+/// executable, so the mode is set explicitly rather than trusted.
+fn copy_file_preserving(src: &Path, dst: &Path) -> Result<u64> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let metadata = fs::metadata(src)?;
+    let copied = fs::copy(src, dst)?;
+
+    // futimens only needs ownership, so a read-only handle works even for 0o444 files
+    let times = fs::FileTimes::new()
+        .set_accessed(metadata.accessed()?)
+        .set_modified(metadata.modified()?);
+    fs::File::open(dst)?.set_times(times)?;
+    fs::set_permissions(
+        dst,
+        fs::Permissions::from_mode(metadata.permissions().mode()),
+    )?;
+
+    Ok(copied)
+}
+
+/// Add execute permission wherever `path` is readable; symlinks and missing paths are ignored
+pub fn ensure_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_file() => metadata,
+        _ => return Ok(()),
+    };
+
+    let mode = metadata.permissions().mode();
+    let executable_mode = mode | ((mode & 0o444) >> 2);
+    if executable_mode != mode {
+        fs::set_permissions(path, fs::Permissions::from_mode(executable_mode))?;
+    }
+
+    Ok(())
+}
+
+/// Bytes available to unprivileged users on the filesystem holding `path`, which
+/// doesn't have to exist yet
+pub fn available_space(path: &Path) -> Result<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .unwrap_or(Path::new("/"));
+    let c_path = std::ffi::CString::new(existing.as_os_str().as_bytes())?;
+
+    // SAFETY: c_path is NUL-terminated and stat is a valid statvfs to write into
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(anyhow!(
+            "Failed to check free space on {}: {}",
+            existing.display(),
+            std::io::Error::last_os_error()
+        ));
+    }
+
+    // The field types are u32 on some targets
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Fail early when the filesystem holding `path` has less than `required` bytes free
+///
+/// If the free space can't be determined this only warns, so it never blocks an install.
+pub fn ensure_free_space(path: &Path, required: u64) -> Result<()> {
+    let available = match available_space(path) {
+        Ok(available) => available,
+        Err(e) => {
+            eprintln!("Warning: {e}");
+            return Ok(());
+        }
+    };
+
+    if available < required {
+        return Err(anyhow!(
+            "Not enough disk space for {}: need ~{} free, have {}",
+            path.display(),
+            format_size(required),
+            format_size(available)
+        ));
+    }
+
+    Ok(())
+}
+
+/// Format a byte count for display, e.g. "1.5 GiB"
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+/// Lowercase file name for a game, with spaces and characters that aren't safe in
+/// file names replaced by single underscores
 pub fn sanitize_filename(name: &str) -> String {
-    name.chars()
+    let replaced = name
+        .chars()
         .map(|c| match c {
-            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | ' ' => '_',
             c if c.is_control() => '_',
             c => c,
         })
         .collect::<String>()
-        .trim()
-        .to_lowercase()
-        .replace(' ', "_")
+        .to_lowercase();
+
+    replaced
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Root of a GOG install containing `exe`: the nearest directory holding a
+/// `goggame-<id>.info` file
+pub fn find_gog_root(exe: &Path) -> Option<PathBuf> {
+    exe.ancestors().skip(1).find_map(|dir| {
+        let is_root = fs::read_dir(dir).ok()?.flatten().any(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("goggame-") && name.ends_with(".info")
+        });
+        is_root.then(|| dir.to_path_buf())
+    })
+}
+
+/// Root of a Steam install containing `exe`: the game's directory directly under
+/// `steamapps/common`
+pub fn find_steam_root(exe: &Path) -> Option<PathBuf> {
+    exe.ancestors().skip(1).find_map(|dir| {
+        let common = dir.parent()?;
+        let steamapps = common.parent()?;
+        let is_root = common.file_name()? == "common"
+            && steamapps
+                .file_name()?
+                .eq_ignore_ascii_case(std::ffi::OsStr::new("steamapps"));
+        is_root.then(|| dir.to_path_buf())
+    })
+}
+
+/// Check that `path` is a Windows PE executable rather than, say, a shell script
+///
+/// Looks for the `MZ` DOS header and the `PE\0\0` signature it points to.
+pub fn check_windows_executable(path: &Path) -> Result<()> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = fs::File::open(path)?;
+    let mut dos_header = [0u8; 64];
+    let read = file.read(&mut dos_header)?;
+    let dos_header = &dos_header[..read];
+
+    if dos_header.starts_with(b"#!") {
+        return Err(anyhow!(
+            "{} is a script, not a Windows executable",
+            path.display()
+        ));
+    }
+    if dos_header.starts_with(b"\x7fELF") {
+        return Err(anyhow!(
+            "{} is a Linux binary, not a Windows executable",
+            path.display()
+        ));
+    }
+    if !dos_header.starts_with(b"MZ") || dos_header.len() < 64 {
+        return Err(anyhow!(
+            "{} is not a Windows executable (no MZ header)",
+            path.display()
+        ));
+    }
+
+    let pe_offset = u32::from_le_bytes([
+        dos_header[60],
+        dos_header[61],
+        dos_header[62],
+        dos_header[63],
+    ]);
+    let mut signature = [0u8; 4];
+    file.seek(SeekFrom::Start(u64::from(pe_offset)))?;
+    if file.read_exact(&mut signature).is_err() || &signature != b"PE\0\0" {
+        return Err(anyhow!(
+            "{} is a DOS program, not a Windows executable (no PE signature)",
+            path.display()
+        ));
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -149,16 +681,17 @@ mod tests {
     #[test]
     fn test_sanitize_filename() {
         assert_eq!(sanitize_filename("My Game"), "my_game");
-        assert_eq!(sanitize_filename("Game: The Sequel"), "game__the_sequel");
+        assert_eq!(sanitize_filename("Game: The Sequel"), "game_the_sequel");
         assert_eq!(sanitize_filename("Game/Part\\Two"), "game_part_two");
         assert_eq!(sanitize_filename("Game With Spaces"), "game_with_spaces");
         assert_eq!(
             sanitize_filename("Game*With?Special<Chars>"),
-            "game_with_special_chars_"
+            "game_with_special_chars"
         );
         assert_eq!(sanitize_filename("UPPERCASE GAME"), "uppercase_game");
         assert_eq!(sanitize_filename(""), ""); // Edge case: empty string
         assert_eq!(sanitize_filename("123 Game"), "123_game");
+        assert_eq!(sanitize_filename("  _Game?? "), "game");
     }
 
     #[test]
@@ -166,7 +699,7 @@ mod tests {
         let temp_dir = tempfile::tempdir().unwrap();
         std::env::set_var("HOME", temp_dir.path());
 
-        let dirs = CellarDirectories::new().unwrap();
+        let dirs = CellarDirectories::new(None).unwrap();
 
         // Test that directory creation works
         assert!(dirs.base_dir.ends_with("cellar"));
@@ -184,12 +717,83 @@ mod tests {
         assert!(prefixes_path.ends_with("prefixes"));
     }
 
+    #[test]
+    fn test_game_config_path_skips_removed_suffix() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dirs = CellarDirectories::new(Some(temp_dir.path())).unwrap();
+        dirs.ensure_all_exist().unwrap();
+
+        let write = |file: &str, name: &str| {
+            fs::write(
+                dirs.configs_dir.join(file),
+                format!("[game]\nname = \"{name}\"\n"),
+            )
+            .unwrap();
+        };
+        write("game_one.toml", "Game One");
+        write("game_one-2.toml", "Game: One");
+        write("game_one-3.toml", "Game * One");
+        fs::remove_file(dirs.configs_dir.join("game_one-2.toml")).unwrap();
+
+        assert_eq!(
+            dirs.get_game_config_path("Game * One"),
+            dirs.configs_dir.join("game_one-3.toml")
+        );
+        assert_eq!(
+            dirs.get_game_config_path("game_one-3"),
+            dirs.configs_dir.join("game_one-3.toml")
+        );
+        assert_eq!(
+            dirs.get_game_config_path("Game One"),
+            dirs.configs_dir.join("game_one.toml")
+        );
+
+        // Names that sanitize to a taken file but match no config resolve to a free path
+        let missing = dirs.get_game_config_path("Game? One");
+        assert!(!missing.exists());
+        assert_eq!(missing, dirs.configs_dir.join("game_one-2.toml"));
+        assert_eq!(dirs.game_config_path_for_save("Game? One"), missing);
+        assert_eq!(
+            dirs.game_config_path_for_save("Game * One"),
+            dirs.configs_dir.join("game_one-3.toml")
+        );
+    }
+
+    #[test]
+    fn test_game_config_path_ignores_case() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dirs = CellarDirectories::new(Some(temp_dir.path())).unwrap();
+        dirs.ensure_all_exist().unwrap();
+
+        let write = |file: &str, name: &str| {
+            fs::write(
+                dirs.configs_dir.join(file),
+                format!("[game]\nname = \"{name}\"\n"),
+            )
+            .unwrap();
+        };
+        write("portal_2.toml", "Portal 2");
+        assert_eq!(
+            dirs.get_game_config_path("portal 2"),
+            dirs.configs_dir.join("portal_2.toml")
+        );
+        assert_eq!(dirs.get_game_file_stem("PORTAL 2"), "portal_2");
+
+        // Only a single case-insensitive match counts
+        write("portal_2-2.toml", "PORTAL 2");
+        assert!(!dirs.get_game_config_path("portal 2").exists());
+        assert_eq!(
+            dirs.get_game_config_path("PORTAL 2"),
+            dirs.configs_dir.join("portal_2-2.toml")
+        );
+    }
+
     #[test]
     fn test_directory_structure() {
         let temp_dir = tempfile::tempdir().unwrap();
         std::env::set_var("HOME", temp_dir.path());
 
-        let dirs = CellarDirectories::new().unwrap();
+        let dirs = CellarDirectories::new(None).unwrap();
 
         // Test that we can ensure directories exist
         let result = dirs.ensure_all_exist();
@@ -202,6 +806,139 @@ mod tests {
         assert!(dirs.prefixes_dir.exists());
     }
 
+    #[test]
+    fn test_cellar_directories_base_override() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let base = temp_dir.path().join("profile");
+
+        let dirs = CellarDirectories::new(Some(&base)).unwrap();
+        dirs.ensure_all_exist().unwrap();
+
+        assert_eq!(dirs.base_dir, base);
+        assert_eq!(dirs.configs_dir, base.join("configs"));
+        assert_eq!(dirs.get_runners_path(), base.join("runners"));
+        assert_eq!(dirs.get_prefixes_path(), base.join("prefixes"));
+        assert_eq!(dirs.applications_dir, base.join("applications"));
+        assert!(dirs.get_cache_path().exists());
+    }
+
+    #[test]
+    fn test_resolve_runners_dir() {
+        let base = Path::new("/data/cellar");
+
+        assert_eq!(
+            resolve_runners_dir(base, None, None).unwrap(),
+            base.join("runners")
+        );
+        assert_eq!(
+            resolve_runners_dir(base, None, Some(PathBuf::from("/mnt/games/runners"))).unwrap(),
+            PathBuf::from("/mnt/games/runners")
+        );
+        assert_eq!(
+            resolve_runners_dir(
+                base,
+                Some("/mnt/ssd/runners".to_string()),
+                Some(PathBuf::from("/mnt/games/runners"))
+            )
+            .unwrap(),
+            PathBuf::from("/mnt/ssd/runners")
+        );
+        assert_eq!(
+            resolve_runners_dir(base, Some(String::new()), None).unwrap(),
+            base.join("runners")
+        );
+    }
+
+    #[test]
+    fn test_configured_runners_dir_ignores_other_mistakes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let defaults_file = temp_dir.path().join("config.toml");
+
+        fs::write(
+            &defaults_file,
+            "launch = \"oops\"\n\n[paths]\nrunners_dir = \"/mnt/runners\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            configured_runners_dir(&defaults_file),
+            Some(PathBuf::from("/mnt/runners"))
+        );
+
+        fs::write(&defaults_file, "[paths\n").unwrap();
+        assert_eq!(configured_runners_dir(&defaults_file), None);
+        assert!(CellarDirectories::new(Some(temp_dir.path())).is_ok());
+    }
+
+    #[test]
+    fn test_find_install_roots() {
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        let gog = temp_dir.path().join("GOG Games/Witcher");
+        fs::create_dir_all(gog.join("bin/x64")).unwrap();
+        fs::write(gog.join("goggame-1207658924.info"), "{}").unwrap();
+        assert_eq!(find_gog_root(&gog.join("bin/x64/witcher.exe")), Some(gog));
+
+        let steam = temp_dir
+            .path()
+            .join("SteamLibrary/steamapps/common/Portal 2");
+        fs::create_dir_all(steam.join("bin")).unwrap();
+        let steam_exe = steam.join("bin/portal2.exe");
+        assert_eq!(find_steam_root(&steam_exe), Some(steam));
+        assert_eq!(find_gog_root(&steam_exe), None);
+        assert_eq!(find_steam_root(&temp_dir.path().join("game.exe")), None);
+    }
+
+    #[test]
+    fn test_ensure_free_space() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let missing = temp_dir.path().join("runners/proton");
+
+        assert!(available_space(&missing).unwrap() > 0);
+        assert!(ensure_free_space(&missing, 1).is_ok());
+        assert!(ensure_free_space(&missing, u64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_dir_size_skips_symlinks() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("prefix");
+        fs::create_dir_all(root.join("drive_c/users")).unwrap();
+        fs::write(root.join("system.reg"), vec![0u8; 100]).unwrap();
+        fs::write(root.join("drive_c/users/save.dat"), vec![0u8; 250]).unwrap();
+
+        // A link to a large directory outside the tree must not be followed
+        let outside = temp_dir.path().join("outside");
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("big.bin"), vec![0u8; 10_000]).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("z:")).unwrap();
+
+        assert_eq!(dir_size(&root).unwrap(), 350);
+        assert_eq!(dir_size(&root.join("system.reg")).unwrap(), 100);
+        assert!(dir_size(&root.join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_write_file_atomic() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("runners.toml");
+
+        write_file_atomic(&path, "runners = []").unwrap();
+        write_file_atomic(&path, "runners = [1]").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "runners = [1]");
+        // Only the target is left behind, no temporary files
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024 / 2), "1.5 GiB");
+    }
+
     #[test]
     fn test_expand_tilde() {
         // Test regular path (should remain unchanged)
@@ -226,4 +963,94 @@ mod tests {
         assert!(!expanded.to_string_lossy().contains("~"));
         assert!(expanded.is_absolute());
     }
+
+    #[test]
+    fn test_copy_dir_recursive_repoints_internal_symlinks() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let src = temp_dir.path().join("src");
+        fs::create_dir_all(src.join("drive_c/windows")).unwrap();
+        fs::create_dir_all(src.join("dosdevices")).unwrap();
+        fs::write(src.join("drive_c/game.sh"), "#!/bin/sh").unwrap();
+        fs::set_permissions(
+            src.join("drive_c/game.sh"),
+            fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+        symlink("../drive_c", src.join("dosdevices/c:")).unwrap();
+        symlink("/", src.join("dosdevices/z:")).unwrap();
+        symlink(src.join("drive_c"), src.join("dosdevices/d:")).unwrap();
+
+        let dst = temp_dir.path().join("dst");
+        let copied = copy_dir_recursive(&src, &dst).unwrap();
+
+        assert_eq!(copied, 9);
+        let mode = fs::metadata(dst.join("drive_c/game.sh"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert_eq!(
+            fs::metadata(dst.join("drive_c/game.sh"))
+                .unwrap()
+                .modified()
+                .unwrap(),
+            fs::metadata(src.join("drive_c/game.sh"))
+                .unwrap()
+                .modified()
+                .unwrap()
+        );
+        assert_eq!(
+            fs::read_link(dst.join("dosdevices/c:")).unwrap(),
+            Path::new("../drive_c")
+        );
+        assert_eq!(
+            fs::read_link(dst.join("dosdevices/z:")).unwrap(),
+            Path::new("/")
+        );
+        assert_eq!(
+            fs::read_link(dst.join("dosdevices/d:")).unwrap(),
+            dst.join("drive_c")
+        );
+    }
+
+    #[test]
+    fn test_ensure_executable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let script = temp_dir.path().join("proton");
+        fs::write(&script, "#!/bin/sh").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o640)).unwrap();
+
+        ensure_executable(&script).unwrap();
+        ensure_executable(&temp_dir.path().join("missing")).unwrap();
+
+        let mode = fs::metadata(&script).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o750);
+    }
+
+    #[test]
+    fn test_check_windows_executable() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let write = |name: &str, data: &[u8]| {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, data).unwrap();
+            path
+        };
+
+        let mut pe = vec![0u8; 0x44];
+        pe[0..2].copy_from_slice(b"MZ");
+        pe[60..64].copy_from_slice(&0x40u32.to_le_bytes());
+        pe[0x40..0x44].copy_from_slice(b"PE\0\0");
+        assert!(check_windows_executable(&write("game.exe", &pe)).is_ok());
+
+        let mut dos = pe.clone();
+        dos[0x40] = 0;
+        assert!(check_windows_executable(&write("dos.exe", &dos)).is_err());
+        assert!(check_windows_executable(&write("game.sh", b"#!/bin/sh\n")).is_err());
+        assert!(check_windows_executable(&write("game", b"\x7fELF\x02\x01")).is_err());
+        assert!(check_windows_executable(&write("empty.exe", b"")).is_err());
+    }
 }
@@ -0,0 +1,29 @@
+use std::io::IsTerminal;
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+
+/// Set up the subscriber that Cellar's diagnostics are written to, on stderr
+///
+/// `level` applies to Cellar's own events, with other crates kept at warnings.
+/// Without it `RUST_LOG` is used, and failing that only warnings and errors are
+/// shown. User-facing output stays on stdout and isn't affected.
+pub fn init(level: Option<LevelFilter>, json: bool) {
+    let filter = match level {
+        Some(level) => EnvFilter::new(format!("warn,cellar={level}")),
+        None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")),
+    };
+
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+
+    // Ignore the error from a second init, e.g. when a subscriber is already set
+    let _ = if json {
+        builder.json().try_init()
+    } else {
+        builder
+            .without_time()
+            .with_target(false)
+            .with_ansi(std::io::stderr().is_terminal())
+            .try_init()
+    };
+}
@@ -1 +1,6 @@
+pub mod archive;
 pub mod fs;
+pub mod logging;
+pub mod output;
+pub mod prefix;
+pub mod tools;
@@ -0,0 +1,317 @@
+use std::io::IsTerminal;
+use std::process::{Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncBufReadExt;
+
+const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Longest status shown next to a spinner, so the line doesn't wrap
+const MAX_SPINNER_STATUS: usize = 60;
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+/// Print Wine's full stderr instead of only the lines that look like errors
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Answer yes to confirmation prompts without reading stdin, for scripts and CI
+pub fn set_assume_yes(assume_yes: bool) {
+    ASSUME_YES.store(assume_yes, Ordering::Relaxed);
+}
+
+pub fn assume_yes() -> bool {
+    ASSUME_YES.load(Ordering::Relaxed)
+}
+
+/// Print captured Wine stderr unfiltered when `--verbose` is set
+pub fn print_verbose_stderr(stderr: &str) {
+    if is_verbose() && !stderr.trim().is_empty() {
+        eprintln!("--- wine stderr ---");
+        eprint!("{stderr}");
+        eprintln!("--- end wine stderr ---");
+    }
+}
+
+/// Lines from Wine or umu-run stderr that look like real errors
+///
+/// A line counts if it mentions an error or failure, or is logged on Wine's `err` class.
+/// Known noise is dropped unless `verbose` is set: Wine's `fixme`/`warn`/`trace` messages
+/// (which is where stubs are reported), `winediag` hints, the wine-staging banner and
+/// umu-run/Proton status lines. Noise is recognized by where a line comes from rather
+/// than by words anywhere in it, so a real error that mentions e.g. "stub" is kept.
+pub fn filter_wine_errors(stderr: &str, verbose: bool) -> Vec<String> {
+    stderr
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .filter(|line| {
+            let lower = line.to_lowercase();
+            lower.contains("error")
+                || lower.contains("failed")
+                || wine_debug_class(line) == Some("err")
+        })
+        .filter(|line| verbose || !is_wine_noise(line))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Wine debug message class (`err`, `fixme`, ...) of a line like `0024:fixme:d3d:func ...`
+fn wine_debug_class(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    // Messages may be prefixed with the thread ID in hex
+    let line = match line.split_once(':') {
+        Some((tid, rest)) if !tid.is_empty() && tid.chars().all(|c| c.is_ascii_hexdigit()) => rest,
+        _ => line,
+    };
+    let (class, rest) = line.split_once(':')?;
+    matches!(class, "err" | "warn" | "fixme" | "trace")
+        .then_some(class)
+        .filter(|_| rest.contains(':'))
+}
+
+/// Spinner with the elapsed time on stderr, for steps that run a while without output
+///
+/// It's only drawn when stderr is a terminal; otherwise the message is printed once.
+/// The line is cleared when the spinner is dropped.
+pub struct Spinner {
+    status: Arc<Mutex<String>>,
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl Spinner {
+    pub fn start(message: &str) -> Self {
+        let status = Arc::new(Mutex::new(String::new()));
+        if !std::io::stderr().is_terminal() {
+            println!("{message}...");
+            return Self {
+                status,
+                stop: None,
+                thread: None,
+            };
+        }
+
+        let (stop, stopped) = mpsc::channel::<()>();
+        let message = message.to_string();
+        let shared_status = Arc::clone(&status);
+        let thread = std::thread::spawn(move || {
+            let started = Instant::now();
+            for frame in SPINNER_FRAMES.iter().cycle() {
+                let status = shared_status.lock().map(|s| s.clone()).unwrap_or_default();
+                let elapsed = started.elapsed().as_secs();
+                if status.is_empty() {
+                    eprint!("\r\x1b[2K{frame} {message}... {elapsed}s");
+                } else {
+                    eprint!("\r\x1b[2K{frame} {message}... {elapsed}s ({status})");
+                }
+
+                // Dropping the sender wakes this up immediately
+                if stopped.recv_timeout(Duration::from_millis(100))
+                    != Err(mpsc::RecvTimeoutError::Timeout)
+                {
+                    break;
+                }
+            }
+            eprint!("\r\x1b[2K");
+        });
+
+        Self {
+            status,
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+
+    /// Show what the step is doing right now after the elapsed time
+    pub fn set_status(&self, status: &str) {
+        if let Ok(mut current) = self.status.lock() {
+            *current = status.chars().take(MAX_SPINNER_STATUS).collect();
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Run a command with a spinner, showing umu-run and Proton status lines from its
+/// stderr as they arrive
+///
+/// Stdout is discarded; stderr is captured in the returned output as usual. A
+/// command still running after `timeout` is killed and a `TimedOut` error returned.
+pub async fn output_with_spinner(
+    command: &mut tokio::process::Command,
+    message: &str,
+    timeout: Duration,
+) -> std::io::Result<Output> {
+    let mut child = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let spinner = Spinner::start(message);
+
+    let mut stderr = Vec::new();
+    let pipe = child.stderr.take();
+    let run = async {
+        if let Some(pipe) = pipe {
+            let mut reader = tokio::io::BufReader::new(pipe);
+            let mut line = Vec::new();
+            while reader.read_until(b'\n', &mut line).await? > 0 {
+                if let Some(status) = progress_status(&String::from_utf8_lossy(&line)) {
+                    spinner.set_status(status);
+                }
+                stderr.append(&mut line);
+            }
+        }
+        child.wait().await
+    };
+
+    let status = match tokio::time::timeout(timeout, run).await {
+        Ok(status) => status?,
+        Err(_) => {
+            drop(spinner);
+            // Wine processes the command started may outlive it, but stop on their own
+            child.kill().await?;
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("no result after {} seconds", timeout.as_secs()),
+            ));
+        }
+    };
+    drop(spinner);
+
+    Ok(Output {
+        status,
+        stdout: Vec::new(),
+        stderr,
+    })
+}
+
+/// Status text of an umu-run or Proton progress line, e.g. `Proton: Upgrading prefix ...`
+fn progress_status(line: &str) -> Option<&str> {
+    let line = line.trim();
+    // umu-run may put its logger name in brackets first
+    let line = match line.strip_prefix('[') {
+        Some(rest) => rest
+            .split_once(']')
+            .map_or(line, |(_, rest)| rest.trim_start()),
+        None => line,
+    };
+
+    ["INFO:", "Proton:"]
+        .iter()
+        .find_map(|prefix| line.strip_prefix(prefix))
+        .map(str::trim)
+        .filter(|status| !status.is_empty())
+}
+
+fn is_wine_noise(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    matches!(wine_debug_class(line), Some("fixme" | "warn" | "trace"))
+        || line.contains(":winediag:")
+        || trimmed.starts_with("winediag:")
+        || line.contains("err:setupapi:create_dest_file")
+        || line.contains("wine-staging")
+        || line.contains("experimental patches")
+        // umu-run and Proton status output
+        || ["INFO:", "WARN:", "Proton:", "ProtonFixes", "fsync:"]
+            .iter()
+            .any(|prefix| trimmed.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_output_with_spinner_times_out() {
+        let err = output_with_spinner(
+            tokio::process::Command::new("sleep").arg("10"),
+            "Waiting",
+            Duration::from_millis(100),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+
+        let output = output_with_spinner(
+            tokio::process::Command::new("sh").args(["-c", "echo 'Proton: done' >&2"]),
+            "Waiting",
+            Duration::from_secs(10),
+        )
+        .await
+        .unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "Proton: done\n");
+    }
+
+    #[test]
+    fn test_progress_status() {
+        assert_eq!(
+            progress_status("Proton: Upgrading prefix from None to GE-Proton9-20\n"),
+            Some("Upgrading prefix from None to GE-Proton9-20")
+        );
+        assert_eq!(
+            progress_status("[umu.umu_run:520] INFO: Downloading GE-Proton9-20"),
+            Some("Downloading GE-Proton9-20")
+        );
+        assert_eq!(progress_status("0024:fixme:ntdll:stub"), None);
+        assert_eq!(progress_status("INFO:"), None);
+    }
+
+    #[test]
+    fn test_filter_wine_errors_skips_noise() {
+        let stderr = "fixme:d3d:wined3d_stub failed\n\
+                      winediag: error in something\n\
+                      err:module:import_dll Library d3dx9_43.dll not found, failed\n\
+                      Game started\n";
+
+        assert_eq!(
+            filter_wine_errors(stderr, false),
+            vec!["err:module:import_dll Library d3dx9_43.dll not found, failed"]
+        );
+    }
+
+    #[test]
+    fn test_filter_wine_errors_keeps_real_errors_mentioning_noise_words() {
+        let stderr = "0024:fixme:ntdll:NtQuerySystemInformation stub failed\n\
+                      0024:err:module:LdrInitializeThunk \"stub.dll\" failed to initialize\n\
+                      Fatal error: stub loader could not find game data\n\
+                      0030:err:virtual:allocate_virtual_memory out of memory\n\
+                      INFO: umu-launcher: failed to fetch protonfixes, using cached copy\n\
+                      wine: wine-staging 9.0 is a testing version containing experimental patches.\n";
+
+        assert_eq!(
+            filter_wine_errors(stderr, false),
+            vec![
+                "0024:err:module:LdrInitializeThunk \"stub.dll\" failed to initialize",
+                "Fatal error: stub loader could not find game data",
+                "0030:err:virtual:allocate_virtual_memory out of memory",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_wine_errors_verbose_keeps_noise() {
+        let stderr = "0024:fixme:d3d:wined3d_stub failed\nGame started\n";
+
+        assert!(filter_wine_errors(stderr, false).is_empty());
+        assert_eq!(
+            filter_wine_errors(stderr, true),
+            vec!["0024:fixme:d3d:wined3d_stub failed"]
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use std::fs;
+use std::path::Path;
+
+/// Layout of a wine prefix, as far as 32-bit DLL placement is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefixArch {
+    /// 32-bit prefix; 32-bit DLLs live in system32
+    Win32,
+    /// Classic 64-bit prefix; 32-bit DLLs live in syswow64
+    Win64,
+    /// New-style WoW64 prefix: 64-bit, but without a syswow64 directory
+    Wow64,
+}
+
+impl PrefixArch {
+    /// Directory 32-bit DLLs are loaded from, if the prefix has one
+    pub fn dll32_dir(&self) -> Option<&'static str> {
+        match self {
+            PrefixArch::Win32 => Some("drive_c/windows/system32"),
+            PrefixArch::Win64 => Some("drive_c/windows/syswow64"),
+            PrefixArch::Wow64 => None,
+        }
+    }
+
+    /// Directory 64-bit DLLs are loaded from, if the prefix can run them
+    pub fn dll64_dir(&self) -> Option<&'static str> {
+        match self {
+            PrefixArch::Win32 => None,
+            PrefixArch::Win64 | PrefixArch::Wow64 => Some("drive_c/windows/system32"),
+        }
+    }
+}
+
+/// Work out a prefix's architecture from its registry header and directory layout
+///
+/// Wine records the architecture as `#arch=win32`/`#arch=win64` in `system.reg`;
+/// a 64-bit prefix without `syswow64` is a new-style WoW64 prefix. Without a
+/// registry the layout alone decides, and `None` means the prefix isn't
+/// initialized enough to tell.
+pub fn detect_prefix_arch(prefix_path: &Path) -> Option<PrefixArch> {
+    let windows = prefix_path.join("drive_c/windows");
+    let has_syswow64 = windows.join("syswow64").is_dir();
+
+    match fs::read_to_string(prefix_path.join("system.reg")) {
+        Ok(registry) if registry.contains("#arch=win32") => Some(PrefixArch::Win32),
+        Ok(registry) if registry.contains("#arch=win64") => Some(if has_syswow64 {
+            PrefixArch::Win64
+        } else {
+            PrefixArch::Wow64
+        }),
+        _ if has_syswow64 => Some(PrefixArch::Win64),
+        _ => None,
+    }
+}
+
+/// The Windows version a prefix reports, e.g. "Microsoft Windows 10 (build 19045)"
+///
+/// Read from the `Windows NT\CurrentVersion` key in `system.reg`; `None` when the
+/// registry is missing or doesn't have a product name.
+pub fn windows_version(prefix_path: &Path) -> Option<String> {
+    let registry = fs::read_to_string(prefix_path.join("system.reg")).ok()?;
+    let key = "[software\\\\microsoft\\\\windows nt\\\\currentversion]";
+
+    let mut product_name = None;
+    let mut build = None;
+    let mut in_key = false;
+    for line in registry.lines() {
+        if line.starts_with('[') {
+            in_key = line.to_lowercase().starts_with(key);
+            continue;
+        }
+        if !in_key {
+            continue;
+        }
+
+        let Some((name, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().trim_matches('"').to_string();
+        match name.trim().to_lowercase().as_str() {
+            "\"productname\"" => product_name = Some(value),
+            "\"currentbuild\"" => build = Some(value),
+            _ => {}
+        }
+    }
+
+    match (product_name, build) {
+        (Some(name), Some(build)) => Some(format!("{name} (build {build})")),
+        (name, _) => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_prefix(arch: Option<&str>, syswow64: bool) -> tempfile::TempDir {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let windows = temp_dir.path().join("drive_c/windows");
+        fs::create_dir_all(windows.join("system32")).unwrap();
+        if syswow64 {
+            fs::create_dir_all(windows.join("syswow64")).unwrap();
+        }
+        if let Some(arch) = arch {
+            fs::write(
+                temp_dir.path().join("system.reg"),
+                format!("WINE REGISTRY Version 2\n;; All keys relative to \\\\Machine\n\n#arch={arch}\n"),
+            )
+            .unwrap();
+        }
+        temp_dir
+    }
+
+    #[test]
+    fn test_detect_prefix_arch() {
+        let win64 = make_prefix(Some("win64"), true);
+        assert_eq!(detect_prefix_arch(win64.path()), Some(PrefixArch::Win64));
+
+        let wow64 = make_prefix(Some("win64"), false);
+        assert_eq!(detect_prefix_arch(wow64.path()), Some(PrefixArch::Wow64));
+
+        let win32 = make_prefix(Some("win32"), false);
+        assert_eq!(detect_prefix_arch(win32.path()), Some(PrefixArch::Win32));
+
+        let unregistered = make_prefix(None, true);
+        assert_eq!(
+            detect_prefix_arch(unregistered.path()),
+            Some(PrefixArch::Win64)
+        );
+
+        let uninitialized = make_prefix(None, false);
+        assert_eq!(detect_prefix_arch(uninitialized.path()), None);
+    }
+
+    #[test]
+    fn test_windows_version() {
+        let prefix = make_prefix(Some("win64"), true);
+        assert_eq!(windows_version(prefix.path()), None);
+
+        fs::write(
+            prefix.path().join("system.reg"),
+            r#"WINE REGISTRY Version 2
+#arch=win64
+
+[Software\\Microsoft\\Windows\\CurrentVersion] 1700000000
+"ProductName"="Not this one"
+
+[Software\\Microsoft\\Windows NT\\CurrentVersion] 1700000000
+#time=1da0c9b2b0a8c3e
+"CurrentBuild"="19045"
+"CurrentVersion"="6.3"
+"ProductName"="Microsoft Windows 10"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            windows_version(prefix.path()).as_deref(),
+            Some("Microsoft Windows 10 (build 19045)")
+        );
+    }
+
+    #[test]
+    fn test_dll_dirs() {
+        assert_eq!(PrefixArch::Wow64.dll32_dir(), None);
+        assert_eq!(
+            PrefixArch::Wow64.dll64_dir(),
+            Some("drive_c/windows/system32")
+        );
+        assert_eq!(
+            PrefixArch::Win32.dll32_dir(),
+            Some("drive_c/windows/system32")
+        );
+        assert_eq!(PrefixArch::Win32.dll64_dir(), None);
+    }
+}
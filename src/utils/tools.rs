@@ -0,0 +1,111 @@
+use anyhow::{anyhow, Result};
+
+/// Where to get `umu-run`, which Cellar uses to run everything through Proton
+pub const UMU_LAUNCHER_URL: &str = "https://github.com/Open-Wine-Components/umu-launcher";
+
+/// External program Cellar runs, as checked by `cellar doctor`
+pub struct ExternalTool {
+    pub program: &'static str,
+    /// What Cellar needs it for, e.g. "launch.mangohud"
+    pub purpose: &'static str,
+    /// Missing required tools are doctor failures; missing optional ones are warnings
+    pub required: bool,
+}
+
+pub const EXTERNAL_TOOLS: &[ExternalTool] = &[
+    ExternalTool {
+        program: "umu-run",
+        purpose: "running games and prefixes with Proton",
+        required: true,
+    },
+    ExternalTool {
+        program: "wine",
+        purpose: "prefixes without a Proton version",
+        required: false,
+    },
+    ExternalTool {
+        program: "gamescope",
+        purpose: "gamescope.enabled",
+        required: false,
+    },
+    ExternalTool {
+        program: "mangohud",
+        purpose: "launch.mangohud",
+        required: false,
+    },
+    ExternalTool {
+        program: "gamemoderun",
+        purpose: "launch.gamemode",
+        required: false,
+    },
+    ExternalTool {
+        program: "wrestool",
+        purpose: "extracting icons from executables",
+        required: false,
+    },
+    ExternalTool {
+        program: "magick",
+        purpose: "converting icons for desktop shortcuts",
+        required: false,
+    },
+];
+
+pub async fn command_available(program: &str) -> bool {
+    tokio::process::Command::new("which")
+        .arg(program)
+        .output()
+        .await
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// First line `program --version` prints, if the program runs and exits
+/// successfully within a few seconds
+pub async fn tool_version(program: &str) -> Option<String> {
+    let output = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        tokio::process::Command::new(program)
+            .arg("--version")
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    // Some tools print their version on stderr
+    [output.stdout, output.stderr].iter().find_map(|stream| {
+        String::from_utf8_lossy(stream)
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .map(str::to_string)
+    })
+}
+
+/// Fail with an install hint when `umu-run` is missing, instead of a bare spawn error
+pub async fn require_umu_run() -> Result<()> {
+    if command_available("umu-run").await {
+        return Ok(());
+    }
+
+    Err(anyhow!(
+        "umu-run not found in PATH. Cellar runs Proton through umu-launcher; install it from {} or your distribution's umu-launcher package",
+        UMU_LAUNCHER_URL
+    ))
+}
+
+/// Fail with an install hint when system Wine is missing
+pub async fn require_wine() -> Result<()> {
+    if command_available("wine").await {
+        return Ok(());
+    }
+
+    Err(anyhow!(
+        "wine not found in PATH. Install Wine, or pass --proton <version> to use Proton instead"
+    ))
+}